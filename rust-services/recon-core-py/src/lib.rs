@@ -0,0 +1,160 @@
+//! Python bindings for `recon-core`.
+//!
+//! ```python
+//! import recon_core
+//! vol = recon_core.mart(proj, sysmat, n_iters=50, relaxation=0.5)
+//! proj_hat = recon_core.forward_project(sysmat, vol)
+//! ```
+//!
+//! The dtype of `sysmat` picks the compute precision: float32 and float64
+//! matrices are borrowed directly from NumPy (no copy, any strides), other
+//! dtypes are converted to float64 first. The length-M/N vectors are
+//! converted to the same dtype and copied. Results come back as new NumPy
+//! arrays that take ownership of the Rust buffer.
+//!
+//! Shape mismatches and invalid inputs raise `ValueError`; the GIL is
+//! released while reconstructing.
+
+use ndarray::Array1;
+use numpy::{
+    dtype_bound, Element, IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyUntypedArray,
+    PyUntypedArrayMethods,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use recon_core::{ReconFloat, ReconOptions};
+
+/// `numpy.asarray(obj, dtype=T)`, checking that the result has `ndim`
+/// dimensions.
+fn as_array<'py, T: Element>(
+    obj: &Bound<'py, PyAny>,
+    ndim: usize,
+    name: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = obj.py();
+    let array = py
+        .import_bound("numpy")?
+        .call_method1("asarray", (obj, dtype_bound::<T>(py)))?;
+    let untyped = array.downcast::<PyUntypedArray>()?;
+    if untyped.ndim() != ndim {
+        return Err(PyValueError::new_err(format!(
+            "{name} must be {ndim}-D, got shape {:?}",
+            untyped.shape()
+        )));
+    }
+    Ok(array)
+}
+
+/// Copy a 1-D array-like into a Rust vector of dtype `T`.
+fn to_vector<T: ReconFloat + Element>(obj: &Bound<'_, PyAny>, name: &str) -> PyResult<Array1<T>> {
+    let array = as_array::<T>(obj, 1, name)?;
+    Ok(array
+        .downcast::<PyArray1<T>>()?
+        .readonly()
+        .as_array()
+        .to_owned())
+}
+
+/// The system matrix as a 2-D NumPy array of its own float dtype, or of
+/// float64 when it is not float32/float64 already.
+enum Matrix<'py> {
+    F32(Bound<'py, PyArray2<f32>>),
+    F64(Bound<'py, PyArray2<f64>>),
+}
+
+impl<'py> Matrix<'py> {
+    fn extract(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = obj.downcast::<PyArray2<f32>>() {
+            return Ok(Matrix::F32(array.clone()));
+        }
+        let array = as_array::<f64>(obj, 2, "system_matrix")?;
+        Ok(Matrix::F64(array.downcast_into::<PyArray2<f64>>()?))
+    }
+}
+
+fn recon_error(err: recon_core::ReconError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn mart_typed<'py, T: ReconFloat + Element>(
+    projections: &Bound<'py, PyAny>,
+    system_matrix: &Bound<'py, PyArray2<T>>,
+    n_iters: usize,
+    relaxation: f64,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = projections.py();
+    let projections = to_vector::<T>(projections, "projections")?;
+    let system_matrix = system_matrix.readonly();
+    let system_matrix = system_matrix.as_array();
+    let relaxation = T::from(relaxation).unwrap();
+
+    let volume = py
+        .allow_threads(|| {
+            recon_core::mart_reconstruct(
+                &projections,
+                &system_matrix,
+                n_iters,
+                relaxation,
+                &ReconOptions::default(),
+            )
+        })
+        .map_err(recon_error)?;
+    Ok(volume.into_pyarray_bound(py).into_any())
+}
+
+fn forward_project_typed<'py, T: ReconFloat + Element>(
+    system_matrix: &Bound<'py, PyArray2<T>>,
+    volume: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = volume.py();
+    let volume = to_vector::<T>(volume, "volume")?;
+    let system_matrix = system_matrix.readonly();
+    let system_matrix = system_matrix.as_array();
+    if volume.len() != system_matrix.ncols() {
+        return Err(PyValueError::new_err(format!(
+            "volume has length {} but the system matrix has {} columns",
+            volume.len(),
+            system_matrix.ncols()
+        )));
+    }
+
+    let projections = py.allow_threads(|| recon_core::forward_project(&system_matrix, &volume));
+    Ok(projections.into_pyarray_bound(py).into_any())
+}
+
+/// MART reconstruction of `projections` (length M) through `system_matrix`
+/// (M x N); returns the volume (length N).
+#[pyfunction]
+#[pyo3(signature = (projections, system_matrix, n_iters = 50, relaxation = 0.5))]
+fn mart<'py>(
+    projections: &Bound<'py, PyAny>,
+    system_matrix: &Bound<'py, PyAny>,
+    n_iters: usize,
+    relaxation: f64,
+) -> PyResult<Bound<'py, PyAny>> {
+    match Matrix::extract(system_matrix)? {
+        Matrix::F32(a) => mart_typed(projections, &a, n_iters, relaxation),
+        Matrix::F64(a) => mart_typed(projections, &a, n_iters, relaxation),
+    }
+}
+
+/// Forward projection `system_matrix @ volume` (length M).
+#[pyfunction]
+fn forward_project<'py>(
+    system_matrix: &Bound<'py, PyAny>,
+    volume: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    match Matrix::extract(system_matrix)? {
+        Matrix::F32(a) => forward_project_typed(&a, volume),
+        Matrix::F64(a) => forward_project_typed(&a, volume),
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "recon_core")]
+fn recon_core_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(mart, m)?)?;
+    m.add_function(wrap_pyfunction!(forward_project, m)?)?;
+    Ok(())
+}
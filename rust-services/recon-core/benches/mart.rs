@@ -0,0 +1,185 @@
+//! Criterion benchmarks for the MART hot paths.
+//!
+//! Matrices are drawn from a fixed-seed ChaCha8 RNG, so every run and every
+//! machine benchmarks the same problems. Run with:
+//!
+//!   cargo bench --bench mart
+//!   cargo bench --bench mart --features rayon
+//!
+//! and pass a filter (e.g. `cargo bench --bench mart -- sparse`) to run a
+//! subset.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ndarray::{Array1, Array2};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse, mart_step_sparse, mart_step_unchecked,
+    os_mart_reconstruct, InitialGuess, ReconOptions, SparseSystemMatrix,
+};
+
+/// (M, N) problem sizes.
+const SIZES: [(usize, usize); 3] = [(256, 256), (1024, 1024), (2048, 4096)];
+
+/// Fraction of nonzero system-matrix entries.
+const DENSITIES: [f64; 2] = [0.05, 0.5];
+
+/// Iterations per `mart_reconstruct` call.
+const N_ITERS: usize = 5;
+
+const RELAXATION: f32 = 0.5;
+
+/// Synthetic problem: an (M, N) matrix whose entries are nonzero with
+/// probability `density`, and the projections of a smooth positive volume.
+fn problem(m: usize, n: usize, density: f64) -> (Array1<f32>, Array2<f32>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(0x5eed ^ ((m as u64) << 32) ^ n as u64);
+    let system_matrix = Array2::from_shape_simple_fn((m, n), || {
+        if rng.gen_bool(density) {
+            rng.gen_range(0.1..1.0)
+        } else {
+            0.0
+        }
+    });
+    let truth = Array1::from_shape_fn(n, |j| 1.0 + (j % 13) as f32 / 13.0);
+    (system_matrix.dot(&truth), system_matrix)
+}
+
+fn label(m: usize, n: usize, density: f64) -> String {
+    format!("{m}x{n}/{}%", density * 100.0)
+}
+
+fn bench_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mart_step");
+    for (m, n) in SIZES {
+        for density in DENSITIES {
+            let (projections, system_matrix) = problem(m, n, density);
+            let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+            let id = label(m, n, density);
+            group.throughput(Throughput::Elements((m * n) as u64));
+
+            group.bench_function(BenchmarkId::new("dense", &id), |b| {
+                let mut volume = Array1::from_elem(n, 1.0);
+                b.iter(|| {
+                    mart_step_unchecked(&projections, &system_matrix, &mut volume, RELAXATION)
+                });
+            });
+            group.bench_function(BenchmarkId::new("sparse", &id), |b| {
+                let mut volume = Array1::from_elem(n, 1.0);
+                b.iter(|| mart_step_sparse(&projections, &sparse, &mut volume, RELAXATION));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_reconstruct(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mart_reconstruct");
+    group.sample_size(10);
+    let options = ReconOptions::default();
+    for (m, n) in SIZES {
+        for density in DENSITIES {
+            let (projections, system_matrix) = problem(m, n, density);
+            let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+            let id = label(m, n, density);
+
+            group.bench_function(BenchmarkId::new("dense", &id), |b| {
+                b.iter(|| {
+                    mart_reconstruct(&projections, &system_matrix, N_ITERS, RELAXATION, &options)
+                        .unwrap()
+                });
+            });
+            group.bench_function(BenchmarkId::new("sparse", &id), |b| {
+                b.iter(|| {
+                    mart_reconstruct_sparse(&projections, &sparse, N_ITERS, RELAXATION, &options)
+                        .unwrap()
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// `ReconOptions::skip_eps` near convergence: resume a sparse problem from
+/// the volume after `WARM_ITERS` iterations, when most rays are explained.
+fn bench_skip(c: &mut Criterion) {
+    const WARM_ITERS: usize = 2000;
+    let mut group = c.benchmark_group("mart_skip");
+    group.sample_size(10);
+    let (m, n) = SIZES[1];
+    let (projections, system_matrix) = problem(m, n, DENSITIES[0]);
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let warm = mart_reconstruct_sparse(
+        &projections,
+        &sparse,
+        WARM_ITERS,
+        RELAXATION,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let id = label(m, n, DENSITIES[0]);
+
+    for skip_eps in [0.0, 1e-6, 1e-4] {
+        let options = ReconOptions {
+            initial_guess: InitialGuess::FromArray(warm.mapv(f64::from)),
+            skip_eps,
+            ..ReconOptions::default()
+        };
+        group.bench_function(
+            BenchmarkId::new(format!("skip_eps={skip_eps:e}"), &id),
+            |b| {
+                b.iter(|| {
+                    mart_reconstruct_sparse(&projections, &sparse, N_ITERS, RELAXATION, &options)
+                        .unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Serial OS-MART against `ReconOptions::parallel_subsets`, which only pays
+/// off with the `rayon` feature.
+fn bench_os_parallel(c: &mut Criterion) {
+    const N_SUBSETS: usize = 8;
+    let mut group = c.benchmark_group("os_mart_parallel");
+    group.sample_size(10);
+    let (m, n) = SIZES[1];
+    let (projections, system_matrix) = problem(m, n, DENSITIES[1]);
+    let id = label(m, n, DENSITIES[1]);
+
+    for parallel_subsets in [false, true] {
+        let options = ReconOptions {
+            parallel_subsets,
+            ..ReconOptions::default()
+        };
+        let name = if parallel_subsets {
+            "parallel"
+        } else {
+            "serial"
+        };
+        group.bench_function(BenchmarkId::new(name, &id), |b| {
+            b.iter(|| {
+                os_mart_reconstruct(
+                    &projections,
+                    &system_matrix,
+                    N_ITERS,
+                    N_SUBSETS,
+                    RELAXATION,
+                    &options,
+                )
+                .unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_step,
+    bench_reconstruct,
+    bench_skip,
+    bench_os_parallel
+);
+criterion_main!(benches);
@@ -1,79 +1,4678 @@
 use std::fs::File;
-use std::path::PathBuf;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use clap::Parser;
-use ndarray::{Array1, Array2};
-use ndarray_npy::{read_npy, write_npy};
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use image::{GrayImage, ImageFormat, Luma};
+use log::{debug, error, info, warn};
+use ndarray::{Array, Array1, Array2, Array3, ArrayD, Axis, Dimension, IxDyn, Zip};
+use ndarray_npy::{read_npy, write_npy, NpzReader, NpzWriter, ReadableElement, WritableElement};
+use serde_json::json;
+use tiff::encoder::{colortype, TiffEncoder};
+use xxhash_rust::xxh3::Xxh3;
 
-use recon_core::mart_reconstruct;
+#[cfg(feature = "hdf5")]
+use recon_core::h5;
+#[cfg(feature = "mlflow")]
+use recon_core::mlflow::MlflowLogger;
+use recon_core::{
+    add_noise, art_reconstruct_with_callback, backprojection, estimate_condition,
+    estimate_condition_sparse, estimate_landweber_spectral_radius, estimate_sirt_spectral_radius,
+    filter::{gaussian_blur, resample_linear},
+    flat_dark_correct, forward_project_sparse, io, landweber_reconstruct_with_callback,
+    log_transform, metrics, mlem_reconstruct_with_callback, normalize_rows, normalize_rows_sparse,
+    phantom,
+    preprocess::MIN_FLAT_DARK_GAP,
+    relative_residual, residual_vector, restore_rows, restore_rows_sparse, safe_relaxation,
+    sanitize_inputs, sanitize_inputs_sparse, sart_reconstruct_with_callback,
+    sirt_reconstruct_with_callback, smart_reconstruct_with_callback, total_variation,
+    validate_system_matrix, validate_system_matrix_sparse, AutoRelaxation, Geometry,
+    HuberRegularization, InitialGuess, L2Regularization, MartBuilder, NoiseModel, PhantomKind,
+    Quantization, ReconError, ReconFloat, ReconOptions, ReconReport, RelaxationSchedule, RowOrder,
+    SparseSystemMatrix, StopCriterion, TvRegularization,
+};
 
-/// Simple MART CLI for RBYRCT.
+/// Reconstruction algorithm selected with --algorithm.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Algorithm {
+    /// Multiplicative ART (ray-by-ray)
+    Mart,
+    /// Additive ART (Kaczmarz)
+    Art,
+    /// Simultaneous Iterative Reconstruction Technique
+    Sirt,
+    /// Simultaneous ART: every ray updated at once with area weights, the
+    /// same iteration as SIRT
+    Sart,
+    /// Maximum-likelihood EM for Poisson (photon-limited) data; ignores
+    /// --relaxation
+    Mlem,
+    /// Simultaneous MART: every ray's log-update averaged into one update
+    /// per iteration, independent of the ray order
+    Smart,
+    /// Landweber iteration x += relaxation * A^T (y - A x); the relaxation
+    /// depends on the scale of A (see --relaxation auto)
+    Landweber,
+}
+
+impl Algorithm {
+    fn label(self) -> &'static str {
+        match self {
+            Algorithm::Mart => "MART",
+            Algorithm::Art => "ART",
+            Algorithm::Sirt => "SIRT",
+            Algorithm::Sart => "SART",
+            Algorithm::Mlem => "MLEM",
+            Algorithm::Smart => "SMART",
+            Algorithm::Landweber => "Landweber",
+        }
+    }
+
+    /// Whether the algorithm applies MART's per-ray ratio update (and so
+    /// honors --weights, --floor-eps and --ratio-clamp).
+    fn is_multiplicative(self) -> bool {
+        matches!(self, Algorithm::Mart | Algorithm::Smart)
+    }
+}
+
+/// File format of the reconstructed volume.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Flat 1D array (.npy)
+    Npy,
+    /// Multi-page TIFF, one page per Z slice (needs a volume shape)
+    Tiff,
+}
+
+/// Integer encoding of the output volume selected with --quantize.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Quantize {
+    /// 16-bit unsigned integers
+    U16,
+}
+
+/// Floating-point precision used for the reconstruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Dtype {
+    F32,
+    F64,
+}
+
+impl Dtype {
+    /// NumPy's name for the element type.
+    fn numpy_name(self) -> &'static str {
+        match self {
+            Dtype::F32 => "float32",
+            Dtype::F64 => "float64",
+        }
+    }
+}
+
+/// Element types the CLI can load, reconstruct in, and write back out.
+trait CliFloat: ReconFloat + ReadableElement + WritableElement {
+    const DTYPE: Dtype;
+}
+
+impl CliFloat for f32 {
+    const DTYPE: Dtype = Dtype::F32;
+}
+
+impl CliFloat for f64 {
+    const DTYPE: Dtype = Dtype::F64;
+}
+
+/// Volume dimensions given as `WxH` or `XxYxZ` (X = columns, Y = rows,
+/// Z = slices); the flat volume is stored in C order as `[Z, Y, X]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct VolumeShape(Vec<usize>);
+
+impl VolumeShape {
+    /// Shape of a 2D `[rows, cols]` grid as given in a geometry file.
+    fn from_grid([rows, cols]: [usize; 2]) -> Self {
+        VolumeShape(vec![cols, rows])
+    }
+
+    fn num_voxels(&self) -> usize {
+        self.0.iter().product()
+    }
+
+    /// Shape as `[rows, cols]`, the order used by `recon_core`. A 3D shape
+    /// is accepted only when it has a single slice.
+    fn grid_2d(&self) -> Result<[usize; 2]> {
+        match self.0[..] {
+            [width, height] | [width, height, 1] => Ok([height, width]),
+            _ => bail!("expected a 2D volume shape (WxH), got {}", self),
+        }
+    }
+
+    /// NumPy shape of the volume: `[rows, cols]` for WxH, `[slices, rows,
+    /// cols]` for XxYxZ.
+    fn array_shape(&self) -> Vec<usize> {
+        self.0.iter().rev().copied().collect()
+    }
+
+    /// Axis names matching `array_shape`.
+    fn axes(&self) -> &'static [&'static str] {
+        if self.0.len() == 2 {
+            &["y", "x"]
+        } else {
+            &["z", "y", "x"]
+        }
+    }
+
+    /// Shape as `[slices, rows, cols]`; a 2D shape is a single slice.
+    fn stack_3d(&self) -> Result<[usize; 3]> {
+        match self.0[..] {
+            [width, height] => Ok([1, height, width]),
+            [width, height, depth] => Ok([depth, height, width]),
+            _ => bail!("expected a volume shape WxH or XxYxZ, got {}", self),
+        }
+    }
+}
+
+impl std::fmt::Display for VolumeShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dims: Vec<String> = self.0.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", dims.join("x"))
+    }
+}
+
+/// Starting volume selected with `--init`.
+#[derive(Clone, Debug, PartialEq)]
+enum InitSpec {
+    /// `uniform:<value>`
+    Uniform(f64),
+    /// `backproj`
+    Backprojection,
+    /// `npy:<path>`, a volume with N elements (flattened in C order)
+    Npy(PathBuf),
+}
+
+fn parse_init(s: &str) -> std::result::Result<InitSpec, String> {
+    match s.split_once(':') {
+        None if s == "backproj" => Ok(InitSpec::Backprojection),
+        None if s == "uniform" => Ok(InitSpec::Uniform(1.0)),
+        Some(("uniform", value)) => value
+            .parse()
+            .map(InitSpec::Uniform)
+            .map_err(|_| format!("invalid uniform value {value:?}")),
+        Some(("npy", path)) => Ok(InitSpec::Npy(PathBuf::from(path))),
+        _ => Err(format!(
+            "expected uniform:<value>, backproj or npy:<path>, got {s:?}"
+        )),
+    }
+}
+
+fn parse_relax_schedule(s: &str) -> std::result::Result<RelaxationSchedule, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let values = parts[1..]
+        .iter()
+        .map(|v| {
+            v.parse::<f64>()
+                .map_err(|_| format!("invalid number {v:?} in relaxation schedule {s:?}"))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    match (parts[0], &values[..]) {
+        ("constant", &[value]) => Ok(RelaxationSchedule::Constant(value)),
+        ("linear", &[start, end]) => Ok(RelaxationSchedule::Linear { start, end }),
+        ("geometric", &[start, factor]) => Ok(RelaxationSchedule::Geometric { start, factor }),
+        _ => Err(format!(
+            "expected constant:<value>, linear:<start>:<end> or geometric:<start>:<factor>, got \
+            {s:?}"
+        )),
+    }
+}
+
+/// 0-based angle range selected with --use-angles (`stop` exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AngleRange {
+    start: usize,
+    stop: Option<usize>,
+    step: usize,
+}
+
+impl AngleRange {
+    /// The selected angle indices out of `num_angles`.
+    fn indices(&self, num_angles: usize) -> Result<Vec<usize>> {
+        let stop = self.stop.unwrap_or(num_angles);
+        if stop > num_angles {
+            bail!(
+                "--use-angles stops at {} but the geometry has {} angles",
+                stop,
+                num_angles
+            );
+        }
+        Ok((self.start..stop).step_by(self.step).collect())
+    }
+}
+
+fn parse_angle_range(s: &str) -> std::result::Result<AngleRange, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return Err(format!("expected <start>:<stop>[:<step>], got {s:?}"));
+    }
+    let parse = |v: &str| {
+        (!v.is_empty())
+            .then(|| {
+                v.parse::<usize>()
+                    .map_err(|_| format!("invalid angle index {v:?} in {s:?}"))
+            })
+            .transpose()
+    };
+    let range = AngleRange {
+        start: parse(parts[0])?.unwrap_or(0),
+        stop: parse(parts[1])?,
+        step: parts
+            .get(2)
+            .copied()
+            .map(parse)
+            .transpose()?
+            .flatten()
+            .unwrap_or(1),
+    };
+    if range.step == 0 {
+        return Err(format!("angle step must be at least 1, got {s:?}"));
+    }
+    Ok(range)
+}
+
+fn parse_ratio_clamp(s: &str) -> std::result::Result<(f64, f64), String> {
+    let (lo, hi) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected <lo>:<hi>, got {s:?}"))?;
+    let parse = |v: &str| {
+        v.parse::<f64>()
+            .map_err(|_| format!("invalid number {v:?} in ratio clamp {s:?}"))
+    };
+    let (lo, hi) = (parse(lo)?, parse(hi)?);
+    if !(lo > 0.0 && lo <= hi) {
+        return Err(format!("ratio clamp needs 0 < lo <= hi, got {s:?}"));
+    }
+    Ok((lo, hi))
+}
+
+fn parse_intensity_range(s: &str) -> std::result::Result<(f64, f64), String> {
+    let (lo, hi) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected <lo>:<hi>, got {s:?}"))?;
+    let parse = |v: &str| {
+        v.parse::<f64>()
+            .map_err(|_| format!("invalid number {v:?} in intensity range {s:?}"))
+    };
+    let (lo, hi) = (parse(lo)?, parse(hi)?);
+    if !(lo.is_finite() && hi.is_finite() && lo < hi) {
+        return Err(format!("intensity range needs finite lo < hi, got {s:?}"));
+    }
+    Ok((lo, hi))
+}
+
+fn parse_huber(s: &str) -> std::result::Result<(f64, f64), String> {
+    let (delta, weight) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected <delta>:<lambda>, got {s:?}"))?;
+    let parse = |v: &str| {
+        v.parse::<f64>()
+            .map_err(|_| format!("invalid number {v:?} in Huber penalty {s:?}"))
+    };
+    let (delta, weight) = (parse(delta)?, parse(weight)?);
+    if !(delta > 0.0 && weight >= 0.0) {
+        return Err(format!(
+            "Huber penalty needs delta > 0 and lambda >= 0, got {s:?}"
+        ));
+    }
+    Ok((delta, weight))
+}
+
+/// Flat-field intensity given with `--i0`.
+#[derive(Clone, Debug, PartialEq)]
+enum FlatField {
+    /// The same I0 for every ray
+    Value(f64),
+    /// `.npy` with one I0 per ray
+    Npy(PathBuf),
+}
+
+/// Relaxation given with `--relaxation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Relaxation {
+    Value(f64),
+    /// Derived from the estimated spectral radius of the SIRT/SART iteration
+    Auto,
+}
+
+impl std::fmt::Display for Relaxation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relaxation::Value(value) => write!(f, "{value}"),
+            Relaxation::Auto => f.write_str("auto"),
+        }
+    }
+}
+
+fn parse_relaxation(s: &str) -> std::result::Result<Relaxation, String> {
+    if s == "auto" {
+        return Ok(Relaxation::Auto);
+    }
+    s.parse()
+        .map(Relaxation::Value)
+        .map_err(|_| format!("expected a number or `auto`, got {s:?}"))
+}
+
+fn parse_flat_field(s: &str) -> std::result::Result<FlatField, String> {
+    Ok(match s.parse() {
+        Ok(value) => FlatField::Value(value),
+        Err(_) => FlatField::Npy(PathBuf::from(s)),
+    })
+}
+
+/// The 2D grid a regularizer enabled by `flag` runs on.
+fn require_grid(volume_shape: Option<&VolumeShape>, flag: &str) -> Result<[usize; 2]> {
+    match volume_shape {
+        Some(shape) => shape.grid_2d().map_err(|e| anyhow::anyhow!("{flag}: {e}")),
+        None => bail!("{flag} needs --volume-shape (or a matrix built from --geometry)"),
+    }
+}
+
+/// Like `require_grid`, but for penalties that also take 3D stacks: the
+/// shape as `[slices, rows, cols]`.
+fn require_stack(volume_shape: Option<&VolumeShape>, flag: &str) -> Result<[usize; 3]> {
+    match volume_shape {
+        Some(shape) => shape.stack_3d().map_err(|e| anyhow::anyhow!("{flag}: {e}")),
+        None => bail!("{flag} needs --volume-shape (or a matrix built from --geometry)"),
+    }
+}
+
+fn parse_volume_shape(s: &str) -> std::result::Result<VolumeShape, String> {
+    let dims = s
+        .split('x')
+        .map(|d| match d.trim().parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!("invalid dimension {d:?} in volume shape {s:?}")),
+            Ok(n) => Ok(n),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(VolumeShape(dims))
+}
+
+/// Simple MART/ART/SIRT CLI for RBYRCT.
 ///
 /// Expects:
-///   --projections: path to projections.npy (1D array, length M)
-///   --system-matrix: path to system_matrix.npy (2D array, shape (M, N))
-///   --geometry: path to geometry.json (currently unused, just validated)
+///   --projections: path to projections.npy (1D array, length M; float or
+///                  u16/i16/u8 detector counts)
+///   --system-matrix: path to system_matrix.npy (2D array, shape (M, N)),
+///                    or a scipy CSR .npz (keys indptr, indices, data, shape;
+///                    COO row, col, data, shape also accepted);
+///                    if omitted, the matrix is built from --geometry
+///   --geometry: path to geometry.json (parallel-beam description, see
+///               `recon_core::geometry`; a given system matrix must
+///               match its ray count and volume size)
+///
+/// Ctrl-C during the reconstruction stops after the current iteration and
+/// writes the volume so far to --output; a second Ctrl-C aborts.
+///
+/// The subcommands below do everything else; a reconstruction takes no
+/// subcommand.
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    propagate_version = true,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+/// `mart_cli` subcommands; without one it reconstructs (see `Args`).
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Simulate projections y = A * x of a volume
+    Forward(ForwardArgs),
+    /// Write one slice of a reconstruction as a PNG
+    Slice(SliceArgs),
+    /// Tile evenly spaced slices of a reconstruction into one PNG
+    Montage(MontageArgs),
+    /// Score a reconstruction against a known ground truth
+    Compare(CompareArgs),
+    /// Generate a synthetic test problem from a geometry JSON
+    Phantom(PhantomArgs),
+    /// MART-reconstruct many scans that share one system matrix
+    Batch(BatchArgs),
+}
+
+/// Reconstruction flags, used when no subcommand is given.
+#[derive(clap::Args, Debug)]
 struct Args {
-    /// Path to projections .npy file (shape (M,))
+    /// Path to projections .npy file (shape (M,)): float, or raw u16/i16/u8
+    /// detector counts, which are cast to the compute dtype
     #[arg(long)]
     projections: PathBuf,
 
-    /// Path to system matrix .npy file (shape (M, N))
+    /// Multiply the projections as loaded by this factor, e.g. a detector
+    /// gain converting counts to intensities. --flat, --dark and --i0 come
+    /// from the same detector and are scaled alike
+    #[arg(long, default_value_t = 1.0)]
+    scale: f64,
+
+    /// Treat --projections as transmitted intensities and convert them to
+    /// line integrals -ln(I / I0) before reconstructing (requires --i0 or
+    /// --flat)
+    #[arg(long)]
+    log_transform: bool,
+
+    /// Flat-field (unattenuated) intensity I0 for --log-transform: a number,
+    /// or a .npy with one value per ray
+    #[arg(
+        long,
+        value_name = "VALUE|NPY",
+        value_parser = parse_flat_field,
+        requires = "log_transform"
+    )]
+    i0: Option<FlatField>,
+
+    /// Dark-field .npy (detector reading with the beam off) subtracted by
+    /// --flat correction; one value, or one per ray (requires --flat)
+    #[arg(long, value_name = "NPY", requires = "flat")]
+    dark: Option<PathBuf>,
+
+    /// Flat-field .npy (reading without an object); normalizes the raw
+    /// projections to (I - dark) / (flat - dark) before --log-transform,
+    /// which then uses I0 = 1. One value, or one per ray
+    #[arg(long, value_name = "NPY", conflicts_with = "i0")]
+    flat: Option<PathBuf>,
+
+    /// Path to system matrix .npy file (shape (M, N)) or sparse CSR/COO .npz;
+    /// built from --geometry when omitted
     #[arg(long = "system-matrix")]
-    system_matrix: PathBuf,
+    system_matrix: Option<PathBuf>,
 
-    /// Path to geometry JSON (currently unused, just validated)
+    /// Write the matrix built from --geometry to this CSR .npz for reuse
+    /// with --system-matrix. The file records a hash of the geometry, and
+    /// loading it with a different --geometry fails as stale
+    #[arg(long, value_name = "NPZ", conflicts_with = "system_matrix")]
+    save_matrix: Option<PathBuf>,
+
+    /// Path to geometry JSON
     #[arg(long)]
     geometry: PathBuf,
 
-    /// Number of MART iterations
+    /// Reconstruction algorithm
+    #[arg(long, value_enum, default_value_t = Algorithm::Mart)]
+    algorithm: Algorithm,
+
+    /// Number of iterations
     #[arg(long, default_value_t = 50)]
     n_iters: usize,
 
-    /// Relaxation parameter
-    #[arg(long, default_value_t = 0.5)]
-    relaxation: f32,
+    /// Relaxation parameter, or `auto` (SIRT, SART and Landweber) to
+    /// estimate the spectral radius rho of the iteration by power iteration
+    /// and use 90% of the convergence bound 2 / rho
+    #[arg(long, alias = "relax", default_value = "0.5", value_parser = parse_relaxation)]
+    relaxation: Relaxation,
 
-    /// Output path for reconstructed volume (.npy)
+    /// Run even when the relaxation (or a --relax-schedule or --auto-relax
+    /// value) reaches the algorithm's stability bound: 2 for MART, SMART
+    /// and ART, 2 / rho for SIRT, SART and Landweber
     #[arg(long)]
-    output: PathBuf,
+    allow_unsafe_relax: bool,
+
+    /// Per-iteration relaxation, overriding --relaxation:
+    /// constant:<value>, linear:<start>:<end> or geometric:<start>:<factor>
+    #[arg(long, value_parser = parse_relax_schedule)]
+    relax_schedule: Option<RelaxationSchedule>,
+
+    /// Tune the MART relaxation automatically: before every block of
+    /// --auto-relax-every iterations, try each candidate on a held-out 10%
+    /// of the rays and keep the best (--relaxation is the starting value)
+    #[arg(long, conflicts_with = "relax_schedule")]
+    auto_relax: bool,
+
+    /// Comma-separated relaxation values tried by --auto-relax
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "0.05,0.1,0.25,0.5,1.0",
+        requires = "auto_relax"
+    )]
+    auto_relax_candidates: Vec<f64>,
+
+    /// Iterations between --auto-relax searches
+    #[arg(long, default_value_t = 5, requires = "auto_relax")]
+    auto_relax_every: usize,
+
+    /// Seed for the rays --auto-relax holds out
+    #[arg(long, default_value_t = 0, requires = "auto_relax")]
+    auto_relax_seed: u64,
+
+    /// Number of interleaved ray subsets for ordered-subset MART
+    /// (1 = plain MART)
+    #[arg(long, default_value_t = 1)]
+    n_subsets: usize,
+
+    /// Run the --n-subsets subsets concurrently on copies of the volume and
+    /// merge their updates after each iteration (block-Jacobi); faster per
+    /// iteration with threads, but each iteration converges less
+    #[arg(long)]
+    os_parallel: bool,
+
+    /// Block-coordinate MART: split the volume into this many contiguous
+    /// voxel ranges and update one at a time (sparse MART only; 1 = update
+    /// the whole volume)
+    #[arg(long, value_name = "K", default_value_t = 1)]
+    blocks: usize,
+
+    /// Compute precision (default: f64 if the projections are stored as
+    /// f64, otherwise f32)
+    #[arg(long, value_enum)]
+    dtype: Option<Dtype>,
+
+    /// Worker threads for the reconstruction in rayon builds (default: one
+    /// per core). 1 reproduces the serial build exactly; other counts can
+    /// change the last bits of the result
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Sum MART's per-ray dot products in a fixed pairwise order, so the
+    /// result is bit-identical for every --threads value
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Visit MART rays in a random order seeded with this value
+    /// (reshuffled each iteration; default is sequential order)
+    #[arg(long)]
+    shuffle_seed: Option<u64>,
+
+    /// Column-weighted MART: scale each voxel's update exponent by
+    /// A_ij / colsum_j (MART only)
+    #[arg(long)]
+    weighted: bool,
+
+    /// Per-ray confidence weights (.npy of length M, non-negative): MART and
+    /// SMART apply ray i with relaxation * w_i, and a weight of 0 drops the
+    /// ray
+    #[arg(long, value_name = "NPY")]
+    weights: Option<PathBuf>,
+
+    /// Per-voxel exponent scale (.npy with N values in any shape, e.g. laid
+    /// out like --volume-shape, non-negative): MART and SMART update voxel j
+    /// with relaxation * v_j, and a weight of 0 freezes the voxel
+    #[arg(long, value_name = "NPY")]
+    voxel_weights: Option<PathBuf>,
+
+    /// Skip MART/SMART rays whose estimated projection is at or below this
+    /// value
+    #[arg(long, default_value_t = 0.0)]
+    floor_eps: f64,
+
+    /// Clamp the MART/SMART per-ray ratio y / y_hat into [lo, hi], e.g. 0.5:2
+    #[arg(long, value_name = "LO:HI", value_parser = parse_ratio_clamp)]
+    ratio_clamp: Option<(f64, f64)>,
+
+    /// Skip the MART update of rays whose factor (y / y_hat)^relaxation is
+    /// within EPS of 1, e.g. 1e-6, to save work near convergence
+    #[arg(long, value_name = "EPS", default_value_t = 0.0)]
+    skip_eps: f64,
+
+    /// Clamp negative voxels to zero after each iteration
+    #[arg(long)]
+    nonneg: bool,
+
+    /// Lower bound applied to every voxel after each iteration
+    #[arg(long)]
+    min_val: Option<f64>,
+
+    /// Upper bound applied to every voxel after each iteration
+    #[arg(long)]
+    max_val: Option<f64>,
+
+    /// Stop once the relative residual changes by less than this between
+    /// iterations (e.g. 1e-4)
+    #[arg(long)]
+    tol: Option<f64>,
+
+    /// Stop once an iteration changes the volume by less than this relative
+    /// to its norm, ||x_new - x_old|| / ||x_old|| (e.g. 1e-5); combines with
+    /// --tol, stopping on whichever fires first
+    #[arg(long, value_name = "TOL")]
+    tol_x: Option<f64>,
+
+    /// Tikhonov (L2) smoothing weight applied after each iteration
+    /// (needs --volume-shape unless the matrix is built from --geometry)
+    #[arg(long)]
+    reg_l2: Option<f64>,
+
+    /// Total-variation denoising weight (needs a volume shape, like
+    /// --reg-l2)
+    #[arg(long)]
+    tv_weight: Option<f64>,
+
+    /// Apply the TV step after every K-th iteration
+    #[arg(long, default_value_t = 1)]
+    tv_every: usize,
+
+    /// Edge-preserving Huber smoothing after each iteration: quadratic for
+    /// voxel gradients below DELTA, linear above, with weight LAMBDA (needs
+    /// a volume shape, like --reg-l2; 2D or 3D)
+    #[arg(long, value_name = "DELTA:LAMBDA", value_parser = parse_huber)]
+    huber: Option<(f64, f64)>,
+
+    /// Initial guess: uniform:<value>, backproj (normalized A^T y) or
+    /// npy:<path>
+    #[arg(long, value_parser = parse_init, default_value = "uniform:1.0")]
+    init: InitSpec,
+
+    /// Warm start from a coarser reconstruction (.npy with the voxels of
+    /// --init-from-shape), upsampled linearly to --volume-shape
+    #[arg(
+        long,
+        value_name = "NPY",
+        requires = "init_from_shape",
+        conflicts_with = "init"
+    )]
+    init_from: Option<PathBuf>,
+
+    /// Volume dimensions of --init-from as WxH or XxYxZ
+    #[arg(long, value_parser = parse_volume_shape, requires = "init_from")]
+    init_from_shape: Option<VolumeShape>,
+
+    /// Voxel mask (.npy of bool, or numbers where nonzero means keep, any
+    /// shape with N elements); masked-out voxels are fixed at zero
+    #[arg(long)]
+    mask: Option<PathBuf>,
+
+    /// Known support of the object, e.g. the central cylinder it sits in
+    /// (.npy like --mask): voxels outside start at zero and stay zero.
+    /// Combined with --mask, only voxels inside both are reconstructed
+    #[arg(long, value_name = "NPY")]
+    support_mask: Option<PathBuf>,
+
+    /// Volume dimensions as WxH or XxYxZ (default: volume_shape from
+    /// --geometry when the system matrix is built from it, which it must
+    /// then match). Voxels are stored in C order, X fastest, so the output
+    /// .npy has NumPy shape (H, W) or (Z, Y, X)
+    #[arg(long, value_parser = parse_volume_shape)]
+    volume_shape: Option<VolumeShape>,
+
+    /// Reconstruct from a subset of the --geometry angles, a 0-based range
+    /// with STOP exclusive; parts may be left empty, e.g. ::2 for every
+    /// other angle or 0:60 for the first 60
+    #[arg(
+        long,
+        value_name = "START:STOP:STEP",
+        value_parser = parse_angle_range,
+        conflicts_with = "use_angle_indices"
+    )]
+    use_angles: Option<AngleRange>,
+
+    /// Reconstruct from these 0-based --geometry angles only, e.g. 0,3,7
+    #[arg(long, value_name = "I,J,...", value_delimiter = ',')]
+    use_angle_indices: Option<Vec<usize>>,
+
+    /// Write one JSON line per iteration ({"iter", "residual", "time_ms"})
+    /// to this path
+    #[arg(long)]
+    residual_log: Option<PathBuf>,
+
+    /// Write the same trace as CSV (iter,residual,relaxation,time_ms) to this
+    /// path, flushed after every iteration; relaxation is left empty for
+    /// MLEM and --auto-relax
+    #[arg(long, value_name = "CSV")]
+    residual_csv: Option<PathBuf>,
+
+    /// Write the final per-ray residual A*x - y of the output volume to this
+    /// .npy: shape (angles, detectors) when the matrix is built from
+    /// --geometry, otherwise (M,)
+    #[arg(long, value_name = "NPY")]
+    residual_output: Option<PathBuf>,
+
+    /// Print no-reference quality proxies of the output volume: its data-fit
+    /// residual, total variation (needs a volume shape) and the fraction of
+    /// voxels at the --nonneg/--min-val/--max-val bounds; also recorded in
+    /// the metadata sidecar
+    #[arg(long)]
+    report_quality: bool,
+
+    /// Save a checkpoint (current volume and iteration count) every N
+    /// iterations
+    #[arg(long, value_name = "N")]
+    checkpoint_every: Option<usize>,
+
+    /// Checkpoint file written by --checkpoint-every [default: the output
+    /// path with a .ckpt.npz extension]
+    #[arg(long)]
+    checkpoint_path: Option<PathBuf>,
+
+    /// Save the volume every N iterations as <snapshot-dir>/iter_<i>.npy,
+    /// where i counts the completed iterations (e.g. for convergence
+    /// animations)
+    #[arg(long, value_name = "N", requires = "snapshot_dir")]
+    snapshot_every: Option<usize>,
+
+    /// Directory for --snapshot-every, created if missing
+    #[arg(long, value_name = "DIR", requires = "snapshot_every")]
+    snapshot_dir: Option<PathBuf>,
+
+    /// Write the per-voxel total movement over the run to this .npy (f32,
+    /// shape (N,)): the summed |ln(x_k / x_k-1)| for MART, SMART and MLEM,
+    /// the summed |x_k - x_k-1| for the additive algorithms. Voxels no ray
+    /// crosses stay at 0; large values flag oscillating voxels
+    #[arg(long, value_name = "NPY")]
+    voxel_stats: Option<PathBuf>,
+
+    /// Warn when --snapshot-every would write more than this many files
+    #[arg(long, value_name = "COUNT", default_value_t = 1000)]
+    snapshot_max: usize,
+
+    /// Continue from a checkpoint written by --checkpoint-every instead of
+    /// starting from --init; --n-iters stays the total iteration count, so
+    /// an interrupted run finishes where it was meant to (see --continue
+    /// for extending a finished one)
+    #[arg(long, value_name = "FILE")]
+    resume: Option<PathBuf>,
+
+    /// Run --n-iters more iterations on top of the existing --output: its
+    /// volume is the initial guess, and the iteration count in its
+    /// metadata sidecar grows by the iterations run, and its provenance
+    /// records the hash of the overwritten volume. Unlike --resume this
+    /// needs no checkpoint, only an .npy output with metadata; note that a
+    /// --smooth-sigma output continues from the smoothed volume
+    #[arg(
+        long = "continue",
+        conflicts_with_all = ["resume", "init", "init_from", "no_metadata"]
+    )]
+    continue_run: bool,
+
+    /// MLflow tracking server to send the per-iteration residual and final
+    /// metrics to (requires --run-id); delivery is best-effort
+    #[cfg(feature = "mlflow")]
+    #[arg(long, value_name = "URI", requires = "run_id")]
+    mlflow_uri: Option<String>,
+
+    /// MLflow run to log metrics under (requires --mlflow-uri)
+    #[cfg(feature = "mlflow")]
+    #[arg(long, requires = "mlflow_uri")]
+    run_id: Option<String>,
+
+    /// Dataset holding the projections when --projections is an .h5 file
+    #[cfg(feature = "hdf5")]
+    #[arg(long, default_value = "projections")]
+    projections_dataset: String,
+
+    /// Dataset (dense 2D) or group (CSR data/indices/indptr/shape) holding
+    /// the system matrix when --system-matrix is an .h5 file
+    #[cfg(feature = "hdf5")]
+    #[arg(long, default_value = "system_matrix")]
+    system_matrix_dataset: String,
+
+    /// Skip the system matrix sanity checks (all-zero rows/columns,
+    /// negative or non-finite entries)
+    #[arg(long)]
+    skip_validation: bool,
+
+    /// Print the wall-clock time of each phase (loading, validation,
+    /// reconstruction, output) and per-iteration statistics to stderr
+    #[arg(long)]
+    timing: bool,
+
+    /// Replace NaN/Inf projections and matrix entries (e.g. detector
+    /// dropouts) with zero and skip the affected rays, instead of failing
+    #[arg(long)]
+    sanitize: bool,
+
+    /// Divide every system matrix row and its projection by the row's L2
+    /// norm before reconstructing, so that one relaxation value suits rays
+    /// of very different lengths. The volume is unchanged; the scaling is
+    /// undone afterwards, so the final residual and --residual-output are
+    /// in the original units (per-iteration residuals and --tol use the
+    /// scaled rays)
+    #[arg(long)]
+    normalize_rows: bool,
+
+    /// Load the system matrix with bounded memory: stream CSR `.npz` arrays
+    /// straight into the sparse matrix (memory-mapping uncompressed
+    /// archives) and convert a dense `.npy` to CSR through a memory map
+    /// instead of reading it whole
+    #[arg(long)]
+    mmap: bool,
+
+    /// Abort as diverged when the residual rises for this many consecutive
+    /// iterations (0 disables; NaN/Inf voxels always abort)
+    #[arg(long, default_value_t = 5)]
+    patience: usize,
+
+    /// Output path for reconstructed volume. When the volume shape is known
+    /// the NPY holds an (H, W) or (Z, Y, X) array, and the layout is also
+    /// recorded in a sidecar <output>.meta.json
+    #[arg(long, required_unless_present_any = ["diagnose", "dry_run"])]
+    output: Option<PathBuf>,
+
+    /// Estimate the system matrix's extreme singular values and condition
+    /// number, print them and exit without reconstructing
+    #[arg(long)]
+    diagnose: bool,
+
+    /// Load the inputs, print the problem size, matrix storage and an
+    /// estimate of the peak memory of the chosen algorithm, and exit
+    /// without reconstructing
+    #[arg(long, conflicts_with = "diagnose")]
+    dry_run: bool,
+
+    /// Output file format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Npy)]
+    output_format: OutputFormat,
+
+    /// Precision of the stored volume, independent of the compute
+    /// precision (default: --dtype; .npy output only)
+    #[arg(long, value_enum, conflicts_with = "quantize")]
+    output_dtype: Option<Dtype>,
+
+    /// Fail instead of overwriting an existing --output (except with
+    /// --continue, which updates it on purpose)
+    #[arg(long)]
+    no_clobber: bool,
+
+    /// Leave the provenance (version, command line, input hashes, run
+    /// summary) out of the sidecar JSON; it is then only written when the
+    /// layout, quantization or units need recording
+    #[arg(long)]
+    no_metadata: bool,
+
+    /// Overwrite existing outputs even with --no-clobber
+    #[arg(long)]
+    force: bool,
+
+    /// Blur the final volume with a Gaussian of this standard deviation (in
+    /// voxels) before writing it; needs --volume-shape (or a matrix built
+    /// from --geometry). 0 disables
+    #[arg(long, value_name = "S", default_value_t = 0.0)]
+    smooth_sigma: f64,
+
+    /// Write TIFF pages as raw 32-bit float intensities instead of
+    /// normalizing the volume's min/max to the full 16-bit range
+    #[arg(long)]
+    raw_intensity: bool,
+
+    /// Store the volume as integers, mapping its min/max (or
+    /// --intensity-range) linearly onto the full integer range; the sidecar
+    /// JSON records the scale and offset to dequantize (.npy output only)
+    #[arg(long, value_enum)]
+    quantize: Option<Quantize>,
+
+    /// Value range mapped onto the --quantize codes; voxels outside it are
+    /// clamped [default: the volume's min/max]
+    #[arg(long, value_name = "LO:HI", value_parser = parse_intensity_range, requires = "quantize")]
+    intensity_range: Option<(f64, f64)>,
+
+    /// Physical edge length of one voxel in mm: write the volume as linear
+    /// attenuation in cm^-1 instead of per path-length unit (see
+    /// `PhysicalUnits` for the formula). --intensity-range is then in cm^-1
+    #[arg(long, value_name = "MM")]
+    voxel_size: Option<f64>,
+
+    /// Extra factor applied to the output volume, e.g. a calibration of the
+    /// effective beam energy; with --voxel-size it multiplies the cm^-1
+    /// values
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0)]
+    unit_scale: f64,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Periodic checkpoints for `--checkpoint-every`.
+///
+/// Each checkpoint is an `.npz` holding `volume` (f64) and `iteration` (the
+/// number of completed iterations). It is written to a temporary file and
+/// renamed over the previous one, so a crash mid-write never leaves a
+/// truncated checkpoint behind.
+struct Checkpointer {
+    target: Option<(PathBuf, usize)>,
+    error: Option<anyhow::Error>,
+}
 
-    // --- Load projections + system matrix from .npy files ---
-    let projections: Array1<f32> = read_npy(&args.projections)
-        .map_err(|e| anyhow::anyhow!("Failed to read projections NPY {:?}: {}", args.projections, e))?;
+impl Checkpointer {
+    fn new(path: Option<PathBuf>, every: Option<usize>) -> Self {
+        Self {
+            target: path.zip(every),
+            error: None,
+        }
+    }
 
-    let system_matrix: Array2<f32> = read_npy(&args.system_matrix)
-        .map_err(|e| anyhow::anyhow!("Failed to read system matrix NPY {:?}: {}", args.system_matrix, e))?;
+    /// Save after the 0-based iteration `iter` if a checkpoint is due.
+    fn record<T: ReconFloat>(&mut self, iter: usize, volume: &Array1<T>) {
+        let Some((path, every)) = &self.target else {
+            return;
+        };
+        let completed = iter + 1;
+        if self.error.is_some() || !completed.is_multiple_of(*every) {
+            return;
+        }
+        if let Err(e) = write_checkpoint(path, volume, completed) {
+            self.error = Some(e);
+        }
+    }
 
-    // --- Check geometry file exists (not yet used) ---
-    let _geom_file = File::open(&args.geometry)
-        .map_err(|e| anyhow::anyhow!("Failed to open geometry JSON {:?}: {}", args.geometry, e))?;
-    // Future: parse geometry and verify consistency.
+    fn finish(self) -> Result<()> {
+        match (self.error, self.target) {
+            (Some(e), Some((path, _))) => bail!("Failed to write checkpoint {:?}: {}", path, e),
+            _ => Ok(()),
+        }
+    }
+}
 
-    println!(
-        "Running MART with M = {}, N = {}, n_iters = {}, relaxation = {}",
-        system_matrix.dim().0,
-        system_matrix.dim().1,
-        args.n_iters,
-        args.relaxation
-    );
+/// Intermediate volumes for `--snapshot-every`, written as
+/// `iter_<i>.npy` (i completed iterations) in the snapshot directory with
+/// the output's shape and precision. Like `Checkpointer`, it keeps the
+/// first write error until `finish`.
+struct Snapshots {
+    target: Option<(PathBuf, usize)>,
+    shape: Vec<usize>,
+    dtype: Dtype,
+    error: Option<anyhow::Error>,
+}
+
+impl Snapshots {
+    fn new(dir: Option<PathBuf>, every: Option<usize>, shape: Vec<usize>, dtype: Dtype) -> Self {
+        Self {
+            target: dir.zip(every),
+            shape,
+            dtype,
+            error: None,
+        }
+    }
+
+    /// Save after the 0-based iteration `iter` if a snapshot is due.
+    fn record<T: CliFloat>(&mut self, iter: usize, volume: &Array1<T>) {
+        let Some((dir, every)) = &self.target else {
+            return;
+        };
+        let completed = iter + 1;
+        if self.error.is_some() || !completed.is_multiple_of(*every) {
+            return;
+        }
+        let path = dir.join(format!("iter_{}.npy", completed));
+        if let Err(e) = write_volume_npy(&path, volume, &self.shape, self.dtype) {
+            self.error = Some(e.context(format!("Failed to write snapshot {:?}", path)));
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Per-voxel accumulated update for --voxel-stats.
+///
+/// Each `record` adds the change of every voxel since the previous
+/// iteration (or the starting volume), in log space for the multiplicative
+/// algorithms. A voxel that is zero on either side of a multiplicative step
+/// contributes nothing.
+struct VoxelStats {
+    path: PathBuf,
+    previous: Array1<f64>,
+    total: Array1<f64>,
+    multiplicative: bool,
+}
 
-    // --- Run MART reconstruction ---
-    let volume = mart_reconstruct(&projections, &system_matrix, args.n_iters, args.relaxation);
+impl VoxelStats {
+    fn new<T: ReconFloat>(path: PathBuf, start: &Array1<T>, multiplicative: bool) -> Self {
+        Self {
+            path,
+            previous: start.mapv(|v| v.to_f64().unwrap()),
+            total: Array1::zeros(start.len()),
+            multiplicative,
+        }
+    }
 
-    // --- Save volume as .npy ---
-    write_npy(&args.output, &volume)
-        .map_err(|e| anyhow::anyhow!("Failed to write output NPY {:?}: {}", args.output, e))?;
+    fn record<T: ReconFloat>(&mut self, volume: &Array1<T>) {
+        Zip::from(&mut self.total)
+            .and(&mut self.previous)
+            .and(volume)
+            .for_each(|total, previous, &v| {
+                let v = v.to_f64().unwrap();
+                *total += if !self.multiplicative {
+                    (v - *previous).abs()
+                } else if v > 0.0 && *previous > 0.0 {
+                    (v / *previous).ln().abs()
+                } else {
+                    0.0
+                };
+                *previous = v;
+            });
+    }
 
-    println!("Reconstruction written to {:?}", args.output);
+    fn finish(self) -> Result<()> {
+        let still = self.total.iter().filter(|&&t| t == 0.0).count();
+        info!("{} of {} voxels never changed", still, self.total.len());
+        write_npy(&self.path, &self.total.mapv(|t| t as f32))
+            .map_err(|e| anyhow::anyhow!("Failed to write voxel stats {:?}: {}", self.path, e))
+    }
+}
+
+fn write_checkpoint<T: ReconFloat>(
+    path: &Path,
+    volume: &Array1<T>,
+    iteration: usize,
+) -> Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
 
+    let mut npz = NpzWriter::new(File::create(&partial)?);
+    npz.add_array("volume.npy", &volume.mapv(|v| v.to_f64().unwrap()))?;
+    npz.add_array("iteration.npy", &Array1::from_elem(1, iteration as i64))?;
+    npz.finish()?;
+    std::fs::rename(&partial, path)?;
     Ok(())
 }
 
+/// Read the output of an earlier run for `--continue`: its volume, back in
+/// solver units, and the cumulative iteration count from the provenance in
+/// its sidecar.
+///
+/// Warns when the earlier run used a different algorithm than `algorithm`.
+fn read_previous_output(output: &Path, algorithm: Algorithm) -> Result<(Array1<f64>, usize)> {
+    let sidecar = sidecar_path(output);
+    let text = std::fs::read_to_string(&sidecar).map_err(|e| {
+        anyhow::anyhow!(
+            "--continue needs the metadata sidecar {:?} of the earlier run: {}",
+            sidecar,
+            e
+        )
+    })?;
+    let meta: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("Failed to parse volume metadata {:?}: {}", sidecar, e))?;
+    if meta.get("quantization").is_some() {
+        bail!(
+            "--continue cannot start from the quantized output {:?}; rerun it without --quantize",
+            output
+        );
+    }
+    let provenance = &meta["provenance"];
+    let Some(iterations) = provenance["iterations_run"].as_u64() else {
+        bail!(
+            "{:?} records no iteration count (was the earlier run made with --no-metadata?)",
+            sidecar
+        );
+    };
+    if let Some(previous) = provenance["algorithm"]
+        .as_str()
+        .filter(|&label| label != algorithm.label())
+    {
+        warn!(
+            "{:?} was reconstructed with {}; continuing it with {}",
+            output,
+            previous,
+            algorithm.label()
+        );
+    }
+
+    let volume: ArrayD<f64> = read_float_npy(output)
+        .map_err(|e| anyhow::anyhow!("Failed to read the previous output {:?}: {}", output, e))?;
+    // undo --unit-scale / --voxel-size
+    let factor = meta["unit_factor"].as_f64().unwrap_or(1.0);
+    Ok((
+        volume.iter().map(|&v| v / factor).collect(),
+        iterations as usize,
+    ))
+}
+
+/// Read a checkpoint written by `write_checkpoint`.
+fn read_checkpoint(path: &Path) -> Result<(Array1<f64>, usize)> {
+    let file = File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open checkpoint {:?}: {}", path, e))?;
+    let mut npz = NpzReader::new(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read checkpoint {:?}: {}", path, e))?;
+    let volume: Array1<f64> = npz.by_name("volume.npy").map_err(|e| {
+        anyhow::anyhow!("Failed to read 'volume' from checkpoint {:?}: {}", path, e)
+    })?;
+    let iteration: Array1<i64> = npz.by_name("iteration.npy").map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read 'iteration' from checkpoint {:?}: {}",
+            path,
+            e
+        )
+    })?;
+    match iteration.as_slice() {
+        Some(&[k]) if k >= 0 => Ok((volume, k as usize)),
+        _ => bail!(
+            "Checkpoint {:?} has an invalid iteration count {:?}",
+            path,
+            iteration
+        ),
+    }
+}
+
+/// `mart_cli forward`: simulate projections y = A * x of a volume through
+/// the system matrix built from a geometry JSON.
+#[derive(clap::Args, Debug)]
+struct ForwardArgs {
+    /// Path to the volume .npy file (shape (N,))
+    #[arg(long)]
+    volume: PathBuf,
+
+    /// Path to geometry JSON describing the scan
+    #[arg(long)]
+    geometry: PathBuf,
+
+    /// Compute precision (default: f64 if the volume is stored as f64,
+    /// otherwise f32)
+    #[arg(long, value_enum)]
+    dtype: Option<Dtype>,
+
+    /// Output path for the simulated projections (.npy)
+    #[arg(long)]
+    output: PathBuf,
+}
+
+/// `mart_cli slice`: write one slice of a reconstructed volume as a PNG for
+/// a quick visual check.
+#[derive(clap::Args, Debug)]
+struct SliceArgs {
+    /// Reconstructed volume .npy (flat, or shaped as written by a
+    /// reconstruction with a known volume shape)
+    #[arg(long)]
+    volume: PathBuf,
+
+    /// Volume dimensions as WxH or XxYxZ [default: the shape stored in the
+    /// .npy, read as (Y, X) or (Z, Y, X)]
+    #[arg(long, value_parser = parse_volume_shape)]
+    volume_shape: Option<VolumeShape>,
+
+    /// Axis the slice is taken perpendicular to
+    #[arg(long, value_enum, default_value_t = SliceAxis::Z)]
+    axis: SliceAxis,
+
+    /// Slice index along --axis [default: the central slice]
+    #[arg(long)]
+    index: Option<usize>,
+
+    /// Output PNG path (8-bit grayscale, the slice's min mapped to black
+    /// and its max to white)
+    #[arg(long)]
+    output: PathBuf,
+}
+
+/// Volume axis selected with `mart_cli slice --axis`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SliceAxis {
+    /// Columns; the slice is a (Z, Y) image
+    X,
+    /// Rows; the slice is a (Z, X) image
+    Y,
+    /// Slices; the slice is a (Y, X) image
+    Z,
+}
+
+/// `mart_cli montage`: tile evenly spaced slices of a reconstructed volume
+/// into one labeled PNG for a whole-volume overview.
+#[derive(clap::Args, Debug)]
+struct MontageArgs {
+    /// Reconstructed volume .npy (flat, or shaped as written by a
+    /// reconstruction with a known volume shape)
+    #[arg(long)]
+    volume: PathBuf,
+
+    /// Volume dimensions as WxH or XxYxZ [default: the shape stored in the
+    /// .npy, read as (Y, X) or (Z, Y, X)]
+    #[arg(long, value_parser = parse_volume_shape)]
+    volume_shape: Option<VolumeShape>,
+
+    /// Axis the slices are taken perpendicular to
+    #[arg(long, value_enum, default_value_t = SliceAxis::Z)]
+    axis: SliceAxis,
+
+    /// Number of tile rows
+    #[arg(long, default_value_t = 3)]
+    rows: usize,
+
+    /// Number of tile columns
+    #[arg(long, default_value_t = 4)]
+    cols: usize,
+
+    /// Output PNG path (8-bit grayscale, the min and max over all shown
+    /// slices mapped to black and white, so tiles compare directly)
+    #[arg(long)]
+    output: PathBuf,
+}
+
+/// `mart_cli compare`: score a reconstruction against a known ground truth
+/// (RMSE, PSNR and per-slice SSIM, see `recon_core::metrics`).
+#[derive(clap::Args, Debug)]
+struct CompareArgs {
+    /// Reconstructed volume .npy
+    #[arg(long)]
+    volume: PathBuf,
+
+    /// Ground-truth volume .npy, e.g. the phantom from `mart_cli phantom`;
+    /// PSNR and SSIM use its value range
+    #[arg(long)]
+    reference: PathBuf,
+
+    /// Dimensions of both volumes as WxH or XxYxZ [default: the shape
+    /// stored in the .npy files, read as (Y, X) or (Z, Y, X)]
+    #[arg(long, value_parser = parse_volume_shape)]
+    volume_shape: Option<VolumeShape>,
+}
+
+/// `mart_cli phantom`: synthesize a test problem (phantom, system matrix and
+/// projections) from a geometry JSON.
+#[derive(clap::Args, Debug)]
+struct PhantomArgs {
+    /// Test object to draw
+    #[arg(long, value_enum, default_value_t = PhantomChoice::SheppLogan)]
+    kind: PhantomChoice,
+
+    /// Path to geometry JSON describing the scan
+    #[arg(long)]
+    geometry: PathBuf,
+
+    /// Draw an N x N phantom, overriding the geometry's volume_shape
+    #[arg(long, value_name = "N")]
+    size: Option<usize>,
+
+    /// Noise standard deviation relative to the largest projection (0 for
+    /// noise-free projections)
+    #[arg(long, default_value_t = 0.0)]
+    noise_level: f64,
+
+    /// Noise model for --noise-level
+    #[arg(long, value_enum, default_value_t = NoiseChoice::Poisson)]
+    noise: NoiseChoice,
+
+    /// Seed for the noise
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Output .npz with `projections` (M,), a dense `system_matrix` (M, N)
+    /// and the ground-truth `volume` (N,), all f64
+    #[arg(long)]
+    output: PathBuf,
+}
+
+/// Test object selected with `mart_cli phantom --kind`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum PhantomChoice {
+    /// Modified Shepp-Logan head phantom
+    SheppLogan,
+    /// A disk and a square on a faint round background
+    Shapes,
+}
+
+/// Noise model selected with `mart_cli phantom --noise`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum NoiseChoice {
+    /// Additive Gaussian noise, clipped at zero
+    Gaussian,
+    /// Photon-counting (Poisson) noise
+    Poisson,
+}
+
+/// `mart_cli batch`: MART-reconstruct many scans that share one system
+/// matrix, loading and validating the matrix only once.
+#[derive(clap::Args, Debug)]
+struct BatchArgs {
+    /// Directory of projection .npy files, one scan (shape (M,)) each
+    #[arg(
+        long,
+        value_name = "DIR",
+        required_unless_present = "projection_stack",
+        conflicts_with = "projection_stack"
+    )]
+    projection_dir: Option<PathBuf>,
+
+    /// Stacked projections .npy of shape (num_scans, M)
+    #[arg(long, value_name = "NPY")]
+    projection_stack: Option<PathBuf>,
+
+    /// Path to system matrix .npy file (shape (M, N)) or sparse CSR/COO .npz
+    /// shared by every scan; built from --geometry when omitted
+    #[arg(long = "system-matrix")]
+    system_matrix: Option<PathBuf>,
+
+    /// Path to geometry JSON
+    #[arg(long)]
+    geometry: PathBuf,
+
+    /// Number of iterations per scan
+    #[arg(long, default_value_t = 50)]
+    n_iters: usize,
+
+    /// Relaxation parameter
+    #[arg(long, default_value_t = 0.5)]
+    relaxation: f64,
+
+    /// Run even with a relaxation at or above MART's stability bound of 2
+    #[arg(long)]
+    allow_unsafe_relax: bool,
+
+    /// Compute precision (default: f64 if the projections are stored as
+    /// f64, otherwise f32)
+    #[arg(long, value_enum)]
+    dtype: Option<Dtype>,
+
+    /// Skip the system matrix validation (zero rows/columns, negative or
+    /// non-finite entries)
+    #[arg(long)]
+    skip_validation: bool,
+
+    /// Directory for the reconstructions: `<name>.npy` for each file of
+    /// --projection-dir, `<stack>_<k>.npy` for row k of --projection-stack
+    #[arg(long, value_name = "DIR")]
+    output_dir: PathBuf,
+
+    /// Precision of the stored volumes (default: --dtype)
+    #[arg(long, value_enum)]
+    output_dtype: Option<Dtype>,
+
+    /// Fail before reconstructing if any output file already exists
+    #[arg(long)]
+    no_clobber: bool,
+
+    /// Leave the provenance out of the per-scan sidecar JSON (see
+    /// `mart_cli --no-metadata`)
+    #[arg(long)]
+    no_metadata: bool,
+
+    /// Overwrite existing outputs even with --no-clobber
+    #[arg(long)]
+    force: bool,
+}
+
+/// System matrix as loaded from disk.
+enum SystemMatrix<T> {
+    Dense(Array2<T>),
+    Sparse(SparseSystemMatrix<T>),
+}
+
+impl<T: ReconFloat> SystemMatrix<T> {
+    fn dim(&self) -> (usize, usize) {
+        match self {
+            SystemMatrix::Dense(a) => a.dim(),
+            SystemMatrix::Sparse(a) => a.dim(),
+        }
+    }
+
+    /// The per-ray residual `A * x - y` of `volume`.
+    fn residual(&self, projections: &Array1<T>, volume: &Array1<T>) -> Array1<T> {
+        match self {
+            SystemMatrix::Dense(a) => residual_vector(projections, a, volume),
+            SystemMatrix::Sparse(a) => residual_vector(projections, a, volume),
+        }
+    }
+
+    /// The relative L2 residual `||A * x - y|| / ||y||` of `volume`.
+    fn relative_residual(&self, projections: &Array1<T>, volume: &Array1<T>) -> T {
+        match self {
+            SystemMatrix::Dense(a) => relative_residual(projections, a, volume),
+            SystemMatrix::Sparse(a) => relative_residual(projections, a, volume),
+        }
+    }
+
+    /// Whether any stored entry is below zero.
+    fn has_negative_entries(&self) -> bool {
+        match self {
+            SystemMatrix::Dense(a) => a.iter().any(|&a_ij| a_ij < T::zero()),
+            SystemMatrix::Sparse(a) => a.data().iter().any(|&a_ij| a_ij < T::zero()),
+        }
+    }
+
+    /// The normalized backprojection of `projections` (see `backprojection`).
+    fn backprojection(&self, projections: &Array1<T>) -> Array1<T> {
+        match self {
+            SystemMatrix::Dense(a) => backprojection(projections, a),
+            SystemMatrix::Sparse(a) => backprojection(projections, a),
+        }
+    }
+
+    /// The matrix made of rows `rows`, in that order.
+    fn select_rows(&self, rows: &[usize]) -> Self {
+        match self {
+            SystemMatrix::Dense(a) => SystemMatrix::Dense(a.select(Axis(0), rows)),
+            SystemMatrix::Sparse(a) => SystemMatrix::Sparse(a.select_rows(rows)),
+        }
+    }
+}
+
+/// Per-iteration convergence trace, written as JSON lines and/or CSV.
+///
+/// Each sink is a no-op when no path was given. Write errors are kept until
+/// `finish` so the reconstruction callback itself stays infallible.
+struct ResidualLog {
+    json: Option<LogSink>,
+    csv: Option<LogSink>,
+    last: Instant,
+}
+
+/// One output file of a `ResidualLog`.
+struct LogSink {
+    path: PathBuf,
+    what: &'static str,
+    writer: BufWriter<File>,
+    error: Option<std::io::Error>,
+}
+
+impl LogSink {
+    fn create(path: Option<&Path>, what: &'static str) -> Result<Option<Self>> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create residual {} {:?}: {}", what, path, e))?;
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            what,
+            writer: BufWriter::new(file),
+            error: None,
+        }))
+    }
+
+    /// Append `line`, flushing it straight away if `flush` is set.
+    fn write_line(&mut self, line: std::fmt::Arguments<'_>, flush: bool) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = writeln!(self.writer, "{line}").and_then(|()| {
+            if flush {
+                self.writer.flush()
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+
+    fn finish(mut self) -> Result<()> {
+        if let Some(e) = self.error {
+            bail!(
+                "Failed to write residual {} {:?}: {}",
+                self.what,
+                self.path,
+                e
+            );
+        }
+        self.writer.flush().map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to write residual {} {:?}: {}",
+                self.what,
+                self.path,
+                e
+            )
+        })?;
+        println!("Residual {} written to {:?}", self.what, self.path);
+        Ok(())
+    }
+}
+
+impl ResidualLog {
+    fn create(json: Option<&Path>, csv: Option<&Path>) -> Result<Self> {
+        let json = LogSink::create(json, "log")?;
+        let mut csv = LogSink::create(csv, "CSV")?;
+        if let Some(csv) = csv.as_mut() {
+            csv.write_line(format_args!("iter,residual,relaxation,time_ms"), true);
+        }
+        Ok(Self {
+            json,
+            csv,
+            last: Instant::now(),
+        })
+    }
+
+    /// Record iteration `iter` run with `relaxation` (`None` if unknown or
+    /// not applicable); `time_ms` is the wall time since the previous record
+    /// (or since the log was created).
+    ///
+    /// CSV rows are flushed immediately so a killed run keeps its trace.
+    fn record<T: ReconFloat>(&mut self, iter: usize, residual: T, relaxation: Option<f64>) {
+        let now = Instant::now();
+        let time_ms = now.duration_since(self.last).as_secs_f64() * 1000.0;
+        self.last = now;
+        let residual = residual.to_f64().unwrap();
+
+        if let Some(json) = self.json.as_mut() {
+            let line = json!({ "iter": iter, "residual": residual, "time_ms": time_ms });
+            json.write_line(format_args!("{line}"), false);
+        }
+        if let Some(csv) = self.csv.as_mut() {
+            // `{}` prints the shortest representation that round-trips exactly
+            let relaxation = relaxation.map(|r| r.to_string()).unwrap_or_default();
+            csv.write_line(
+                format_args!("{iter},{residual},{relaxation},{time_ms}"),
+                true,
+            );
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        for sink in [self.json, self.csv].into_iter().flatten() {
+            sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Wall-clock time per phase of a run, reported with `--timing`.
+///
+/// Phases are consecutive: each `phase` call credits the time since the
+/// previous one. Timing is cheap enough to always collect; only `print`
+/// depends on the flag.
+struct Timing {
+    enabled: bool,
+    started: Instant,
+    mark: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    iteration_mark: Instant,
+    iterations: Vec<Duration>,
+}
+
+impl Timing {
+    fn new(enabled: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            enabled,
+            started: now,
+            mark: now,
+            phases: Vec::new(),
+            iteration_mark: now,
+            iterations: Vec::new(),
+        }
+    }
+
+    /// End the current phase, adding its time to `name`.
+    fn phase(&mut self, name: &'static str) {
+        let now = Instant::now();
+        let elapsed = now - self.mark;
+        self.mark = now;
+        match self.phases.iter_mut().find(|(phase, _)| *phase == name) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((name, elapsed)),
+        }
+    }
+
+    /// Record the end of a solver iteration. The first one is timed from the
+    /// start of the current phase, later ones from the previous iteration.
+    fn iteration(&mut self) {
+        let now = Instant::now();
+        let since = if self.iterations.is_empty() {
+            self.mark
+        } else {
+            self.iteration_mark
+        };
+        self.iterations.push(now - since);
+        self.iteration_mark = now;
+    }
+
+    /// Print the phase table to stderr (stdout stays parseable).
+    fn print(&self) {
+        if !self.enabled {
+            return;
+        }
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        eprintln!("{:<22} {:>12}", "phase", "time (ms)");
+        for (name, elapsed) in &self.phases {
+            eprintln!("{:<22} {:>12.3}", name, ms(*elapsed));
+        }
+        eprintln!("{:<22} {:>12.3}", "total", ms(self.started.elapsed()));
+        if let (Some(min), Some(max)) = (self.iterations.iter().min(), self.iterations.iter().max())
+        {
+            let mean = self.iterations.iter().sum::<Duration>() / self.iterations.len() as u32;
+            eprintln!(
+                "{} iterations: min {:.3} ms, mean {:.3} ms, max {:.3} ms",
+                self.iterations.len(),
+                ms(*min),
+                ms(mean),
+                ms(*max)
+            );
+        }
+    }
+}
+
+/// Write `volume` (C order `[slices, rows, cols]`) as a multi-page TIFF.
+///
+/// Pages are 16-bit grayscale scaled so the volume's min maps to 0 and its
+/// max to 65535 (a constant volume becomes all zeros), or 32-bit float
+/// copies of the voxel values when `raw_intensity` is set.
+fn write_tiff_stack<T: ReconFloat>(
+    path: &Path,
+    volume: &Array1<T>,
+    [slices, rows, cols]: [usize; 3],
+    raw_intensity: bool,
+) -> Result<()> {
+    let page_len = rows * cols;
+    let (width, height) = (u32::try_from(cols)?, u32::try_from(rows)?);
+    let mut encoder = TiffEncoder::new(BufWriter::new(File::create(path)?))?;
+
+    let values: Vec<f64> = volume.iter().map(|v| v.to_f64().unwrap()).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    for page in values.chunks(page_len).take(slices) {
+        if raw_intensity {
+            let data: Vec<f32> = page.iter().map(|&v| v as f32).collect();
+            encoder.write_image::<colortype::Gray32Float>(width, height, &data)?;
+        } else {
+            let data: Vec<u16> = page
+                .iter()
+                .map(|&v| {
+                    if range > 0.0 {
+                        ((v - min) / range * 65535.0).round() as u16
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            encoder.write_image::<colortype::Gray16>(width, height, &data)?;
+        }
+    }
+    Ok(())
+}
+
+/// Load the `--init-from` volume of shape `coarse_shape` and upsample it to
+/// the reconstruction grid.
+fn upsampled_init(
+    path: &Path,
+    coarse_shape: &VolumeShape,
+    volume_shape: Option<&VolumeShape>,
+) -> Result<Array1<f64>> {
+    let Some(volume_shape) = volume_shape else {
+        bail!("--init-from needs --volume-shape (or a matrix built from --geometry)");
+    };
+    if coarse_shape.0.len() != volume_shape.0.len() {
+        bail!(
+            "--init-from-shape {} and the volume shape {} must have the same number of dimensions",
+            coarse_shape,
+            volume_shape
+        );
+    }
+    let coarse: ArrayD<f64> = read_float_npy(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read initial volume {:?}: {}", path, e))?;
+    let coarse: Array1<f64> = coarse.iter().copied().collect();
+    if coarse.len() != coarse_shape.num_voxels() {
+        bail!(
+            "Initial volume {:?} has {} voxels but --init-from-shape {} has {}",
+            path,
+            coarse.len(),
+            coarse_shape,
+            coarse_shape.num_voxels()
+        );
+    }
+    info!(
+        "Warm start: upsampling {:?} from {} to {}",
+        path, coarse_shape, volume_shape
+    );
+    Ok(resample_linear(
+        &coarse,
+        &coarse_shape.array_shape(),
+        &volume_shape.array_shape(),
+    ))
+}
+
+/// Read a float array stored as `T` from a `.npy` file; see
+/// `load_float_array`.
+fn read_float_npy<T: CliFloat, D: Dimension>(path: &Path) -> Result<Array<T, D>> {
+    load_float_array(NpyFile(path), &format!("{:?}", path))
+        .map_err(|e| anyhow::anyhow!("Failed to read NPY {:?}: {}", path, e))
+}
+
+/// Read detector readings from a `.npy` file as `T`, times `scale`.
+///
+/// Float data is read like `read_float_npy`; integer counts as detectors
+/// write them (`u16`, `i16` or `u8`) are cast to `T`, which is exact for
+/// these ranges in both f32 and f64.
+fn read_counts_npy<T: CliFloat>(path: &Path, scale: T) -> Result<Array1<T>> {
+    let values = match read_float_npy(path) {
+        Ok(values) => values,
+        Err(e) => read_integer_npy(path).ok_or(e)?,
+    };
+    Ok(if scale == T::one() {
+        values
+    } else {
+        values.mapv(|v| v * scale)
+    })
+}
+
+/// A `u16`, `i16` or `u8` `.npy` cast to `T`, or `None` if the file holds
+/// none of those.
+fn read_integer_npy<T: CliFloat>(path: &Path) -> Option<Array1<T>> {
+    fn cast<T: CliFloat, I: Copy + Into<f64>>(
+        counts: Array1<I>,
+        dtype: &str,
+        path: &Path,
+    ) -> Array1<T> {
+        info!(
+            "Casting {} {} counts in {:?} to {:?}",
+            counts.len(),
+            dtype,
+            path,
+            T::DTYPE
+        );
+        counts.mapv(|c| T::from(c.into()).unwrap())
+    }
+    if let Ok(counts) = read_npy::<_, Array1<u16>>(path) {
+        return Some(cast(counts, "u16", path));
+    }
+    if let Ok(counts) = read_npy::<_, Array1<i16>>(path) {
+        return Some(cast(counts, "i16", path));
+    }
+    read_npy::<_, Array1<u8>>(path)
+        .ok()
+        .map(|counts| cast(counts, "u8", path))
+}
+
+/// Somewhere `load_float_array` can read an array from, with a choice of
+/// stored element type.
+trait FloatSource {
+    fn read<A: ReadableElement, D: Dimension>(
+        &mut self,
+    ) -> std::result::Result<Array<A, D>, String>;
+}
+
+/// A standalone `.npy` file.
+struct NpyFile<'a>(&'a Path);
+
+impl FloatSource for NpyFile<'_> {
+    fn read<A: ReadableElement, D: Dimension>(
+        &mut self,
+    ) -> std::result::Result<Array<A, D>, String> {
+        read_npy(self.0).map_err(|e| e.to_string())
+    }
+}
+
+/// One array inside an `.npz` archive.
+struct NpzEntry<'a, R: std::io::Read + std::io::Seek> {
+    npz: &'a mut NpzReader<R>,
+    name: &'a str,
+}
+
+impl<R: std::io::Read + std::io::Seek> FloatSource for NpzEntry<'_, R> {
+    fn read<A: ReadableElement, D: Dimension>(
+        &mut self,
+    ) -> std::result::Result<Array<A, D>, String> {
+        self.npz.by_name(self.name).map_err(|e| e.to_string())
+    }
+}
+
+/// Read a float array as `T`, accepting data stored as the other common
+/// float dtype.
+///
+/// NumPy exports mix f32 and f64 freely (e.g. f64 projections next to an
+/// f32 matrix), so when the array is not stored as `T` it is read as the
+/// other dtype and cast, with a warning naming `what`. Narrowing f64 to f32
+/// rounds to f32 precision. The error is the one for `T` if neither dtype
+/// fits.
+fn load_float_array<T: CliFloat, D: Dimension>(
+    mut source: impl FloatSource,
+    what: &str,
+) -> Result<Array<T, D>> {
+    let err = match source.read::<T, D>() {
+        Ok(array) => return Ok(array),
+        Err(e) => e,
+    };
+    let (other, cast) = match T::DTYPE {
+        Dtype::F32 => (
+            Dtype::F64,
+            source
+                .read::<f64, D>()
+                .map(|a| a.mapv(|v| T::from(v).unwrap())),
+        ),
+        Dtype::F64 => (
+            Dtype::F32,
+            source
+                .read::<f32, D>()
+                .map(|a| a.mapv(|v| T::from(v).unwrap())),
+        ),
+    };
+    match cast {
+        Ok(array) => {
+            warn!(
+                "{} is stored as {}; converting to {}",
+                what,
+                other.numpy_name(),
+                T::DTYPE.numpy_name()
+            );
+            Ok(array)
+        }
+        Err(_) => bail!(err),
+    }
+}
+
+/// Read a voxel mask of any shape as a flat (C order) boolean array.
+fn read_mask(path: &Path) -> Result<Array1<bool>> {
+    // only zero vs nonzero matters, so any float dtype is read without a
+    // conversion warning
+    let mask: ArrayD<bool> = match read_npy(path) {
+        Ok(mask) => mask,
+        Err(_) => match read_npy::<_, ArrayD<f32>>(path) {
+            Ok(mask) => mask.mapv(|v| v != 0.0),
+            Err(_) => read_float_npy::<f64, IxDyn>(path)?.mapv(|v| v != 0.0),
+        },
+    };
+    Ok(mask.iter().copied().collect())
+}
+
+/// Pick the compute precision: `--dtype` if given, else f64 when the
+/// projections are stored as f64, else f32.
+fn detect_dtype(args: &Args) -> Dtype {
+    if let Some(dtype) = args.dtype {
+        return dtype;
+    }
+
+    if is_hdf5(&args.projections) {
+        return hdf5_dtype(args);
+    }
+    stored_dtype(&args.projections)
+}
+
+/// F64 if the array at `path` is stored as f64, otherwise F32.
+fn stored_dtype(path: &Path) -> Dtype {
+    match read_npy::<_, ArrayD<f64>>(path) {
+        Ok(_) => Dtype::F64,
+        Err(_) => Dtype::F32,
+    }
+}
+
+/// True for `.h5` / `.hdf5` paths, which are read with the `hdf5` feature;
+/// everything else is treated as NumPy.
+fn is_hdf5(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "h5" || ext == "hdf5")
+}
+
+/// Compute precision for HDF5 projections, like `stored_dtype`.
+#[cfg(feature = "hdf5")]
+fn hdf5_dtype(args: &Args) -> Dtype {
+    match h5::dataset_is_f64(&args.projections, &args.projections_dataset) {
+        Ok(true) => Dtype::F64,
+        _ => Dtype::F32,
+    }
+}
+
+#[cfg(feature = "hdf5")]
+fn read_hdf5_projections<T: CliFloat>(args: &Args) -> Result<Array1<T>> {
+    h5::read_array1_h5(&args.projections, &args.projections_dataset).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read projections {:?} from {:?}: {}",
+            args.projections_dataset,
+            args.projections,
+            e
+        )
+    })
+}
+
+#[cfg(feature = "hdf5")]
+fn read_hdf5_system_matrix<T: CliFloat>(path: &Path, args: &Args) -> Result<SystemMatrix<T>> {
+    let matrix = h5::read_system_matrix_h5(path, &args.system_matrix_dataset).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read system matrix {:?} from {:?}: {}",
+            args.system_matrix_dataset,
+            path,
+            e
+        )
+    })?;
+    Ok(match matrix {
+        h5::H5SystemMatrix::Dense(dense) => SystemMatrix::Dense(dense),
+        h5::H5SystemMatrix::Sparse(sparse) => SystemMatrix::Sparse(sparse),
+    })
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn hdf5_dtype(_args: &Args) -> Dtype {
+    Dtype::F32
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn read_hdf5_projections<T: CliFloat>(args: &Args) -> Result<Array1<T>> {
+    bail!(
+        "{:?} is an HDF5 file; rebuild mart_cli with --features hdf5 to read it",
+        args.projections
+    )
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn read_hdf5_system_matrix<T: CliFloat>(path: &Path, _args: &Args) -> Result<SystemMatrix<T>> {
+    bail!(
+        "{:?} is an HDF5 file; rebuild mart_cli with --features hdf5 to read it",
+        path
+    )
+}
+
+/// Load a dense `.npy` matrix, or a CSR matrix from a `.npz` archive.
+///
+/// With `mmap`, both formats go through the low-memory loaders in
+/// `recon_core::io` and always yield a sparse matrix.
+fn load_system_matrix<T: CliFloat>(path: &Path, mmap: bool) -> Result<SystemMatrix<T>> {
+    let is_npz = path.extension().is_some_and(|ext| ext == "npz");
+    if mmap {
+        let matrix = if is_npz {
+            io::load_csr_npz(path, true)
+        } else {
+            io::load_dense_npy_as_csr(path)
+        };
+        return matrix
+            .map(SystemMatrix::Sparse)
+            .map_err(|e| anyhow::anyhow!("Failed to load system matrix {:?}: {}", path, e));
+    }
+    if is_npz {
+        return load_sparse_npz(path).map(SystemMatrix::Sparse);
+    }
+
+    let dense: Array2<T> =
+        read_float_npy(path).map_err(|e| anyhow::anyhow!("Failed to read system matrix: {}", e))?;
+    Ok(SystemMatrix::Dense(dense))
+}
+
+/// Read a CSR matrix saved by `scipy.sparse.save_npz` (or any NPZ with
+/// `indptr`, `indices`, `data` and `shape` arrays), or a COO one (`row`,
+/// `col`, `data`, `shape`) converted to CSR with duplicates summed.
+fn load_sparse_npz<T: CliFloat>(path: &Path) -> Result<SparseSystemMatrix<T>> {
+    let file = File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open system matrix NPZ {:?}: {}", path, e))?;
+    let mut npz = NpzReader::new(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read system matrix NPZ {:?}: {}", path, e))?;
+    let names = npz
+        .names()
+        .map_err(|e| anyhow::anyhow!("Failed to list NPZ entries in {:?}: {}", path, e))?;
+
+    // numpy stores entries as "<key>.npy"; accept bare keys too
+    let find = |key: &str| {
+        names
+            .iter()
+            .find(|name| name.as_str() == key || name.strip_suffix(".npy") == Some(key))
+            .cloned()
+    };
+    let coo = find("indptr").is_none() && find("row").is_some();
+    let entry = |key: &str| -> Result<String> {
+        find(key).ok_or_else(|| {
+            anyhow::anyhow!(
+                "System matrix NPZ {:?} has no '{}' array (expected CSR or COO layout)",
+                path,
+                key
+            )
+        })
+    };
+
+    let (first, second) = if coo {
+        ("row", "col")
+    } else {
+        ("indptr", "indices")
+    };
+    let first = read_index_array(&mut npz, &entry(first)?)?;
+    let second = read_index_array(&mut npz, &entry(second)?)?;
+    let data_name = entry("data")?;
+    let data: Array1<T> = load_float_array(
+        NpzEntry {
+            npz: &mut npz,
+            name: &data_name,
+        },
+        &format!("'data' in {:?}", path),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to read 'data' from {:?}: {}", path, e))?;
+    let shape = read_index_array(&mut npz, &entry("shape")?)?;
+
+    if shape.len() != 2 {
+        bail!(
+            "System matrix NPZ {:?} has shape of length {}, expected 2",
+            path,
+            shape.len()
+        );
+    }
+    let shape = (shape[0], shape[1]);
+    if coo {
+        let (rows, cols) = (&first, &second);
+        if rows.len() != data.len() || cols.len() != data.len() {
+            bail!(
+                "System matrix NPZ {:?} has {} rows, {} cols and {} data entries; COO needs equal \
+                lengths",
+                path,
+                rows.len(),
+                cols.len(),
+                data.len()
+            );
+        }
+        if let Some(k) = (0..data.len()).find(|&k| rows[k] >= shape.0 || cols[k] >= shape.1) {
+            bail!(
+                "System matrix NPZ {:?} has entry ({}, {}) outside shape {:?}",
+                path,
+                rows[k],
+                cols[k],
+                shape
+            );
+        }
+        info!(
+            "Converting COO system matrix ({} entries) to CSR",
+            data.len()
+        );
+        return Ok(SparseSystemMatrix::from_coo(
+            shape,
+            rows,
+            cols,
+            data.as_slice().unwrap(),
+        ));
+    }
+
+    Ok(SparseSystemMatrix::new(shape, first, second, data.to_vec()))
+}
+
+/// Read a 1D integer array (int64 or int32) as `usize` indices.
+fn read_index_array<R: std::io::Read + std::io::Seek>(
+    npz: &mut NpzReader<R>,
+    name: &str,
+) -> Result<Vec<usize>> {
+    let wide: Result<Array1<i64>, _> = npz.by_name(name);
+    let values: Vec<i64> = match wide {
+        Ok(a) => a.to_vec(),
+        Err(_) => {
+            let narrow: Array1<i32> = npz
+                .by_name(name)
+                .map_err(|e| anyhow::anyhow!("Failed to read integer array '{}': {}", name, e))?;
+            narrow.iter().map(|&v| v as i64).collect()
+        }
+    };
+
+    values
+        .into_iter()
+        .map(|v| {
+            usize::try_from(v).map_err(|_| anyhow::anyhow!("Negative index {} in '{}'", v, name))
+        })
+        .collect()
+}
+
+/// Check that the system matrix loaded from `matrix_path` has one row per
+/// ray and one column per voxel of the geometry in `geometry_path`, and,
+/// if it was saved by --save-matrix, that it was built from that geometry.
+fn check_matrix_geometry(
+    geometry: &Geometry,
+    shape: (usize, usize),
+    matrix_path: &Path,
+    geometry_path: &Path,
+) -> Result<()> {
+    geometry.check_system_matrix(shape).map_err(|e| {
+        anyhow::anyhow!(
+            "{:?} does not match {:?}: {}",
+            matrix_path,
+            geometry_path,
+            e
+        )
+    })?;
+    let stored = stored_geometry_hash(matrix_path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read the geometry hash of {:?}: {}",
+            matrix_path,
+            e
+        )
+    })?;
+    match stored {
+        Some(stored) if stored != geometry.fingerprint() => bail!(
+            "System matrix {:?} is stale: it was built from a geometry with hash {:016x}, but \
+            {:?} hashes to {:016x}; rebuild it with --save-matrix",
+            matrix_path,
+            stored,
+            geometry_path,
+            geometry.fingerprint()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// NPZ entry holding `Geometry::fingerprint` of the geometry a saved matrix
+/// was built from.
+const GEOMETRY_HASH_ENTRY: &str = "geometry_hash.npy";
+
+/// Save a CSR matrix in the layout `load_sparse_npz` reads (int64 indices),
+/// plus the fingerprint of `geometry`.
+fn save_matrix_npz<T: CliFloat>(
+    path: &Path,
+    matrix: &SparseSystemMatrix<T>,
+    geometry: &Geometry,
+) -> Result<()> {
+    let as_i64 = |values: &[usize]| values.iter().map(|&v| v as i64).collect::<Array1<i64>>();
+    let (m, n) = matrix.dim();
+    let mut npz = NpzWriter::new(File::create(path)?);
+    npz.add_array("indptr.npy", &as_i64(matrix.indptr()))?;
+    npz.add_array("indices.npy", &as_i64(matrix.indices()))?;
+    npz.add_array("data.npy", &Array1::from(matrix.data().to_vec()))?;
+    npz.add_array("shape.npy", &as_i64(&[m, n]))?;
+    npz.add_array(
+        GEOMETRY_HASH_ENTRY,
+        &Array1::from_elem(1, geometry.fingerprint()),
+    )?;
+    npz.finish()?;
+    Ok(())
+}
+
+/// The geometry fingerprint recorded in a matrix `.npz` by --save-matrix;
+/// `None` for other files (e.g. from scipy, or dense `.npy`).
+fn stored_geometry_hash(matrix_path: &Path) -> Result<Option<u64>> {
+    if matrix_path.extension().is_none_or(|ext| ext != "npz") {
+        return Ok(None);
+    }
+    let mut npz = NpzReader::new(File::open(matrix_path)?)?;
+    if !npz.names()?.iter().any(|name| name == GEOMETRY_HASH_ENTRY) {
+        return Ok(None);
+    }
+    let hash: Array1<u64> = npz.by_name(GEOMETRY_HASH_ENTRY)?;
+    Ok(hash.first().copied())
+}
+
+/// Read and validate a geometry JSON file.
+fn load_geometry(path: &Path) -> Result<Geometry> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read geometry JSON {:?}: {}", path, e))?;
+    Geometry::from_json(&json).map_err(|e| anyhow::anyhow!("{:?}: {}", path, e))
+}
+
+/// `load_geometry` for a precomputed --system-matrix, which only needs the
+/// geometry to check the matrix against it: a file without angle/detector
+/// metadata (such as a placeholder) skips that check with a warning and
+/// gives `None`.
+fn load_matrix_geometry(path: &Path) -> Result<Option<Geometry>> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read geometry JSON {:?}: {}", path, e))?;
+    let value: serde_json::Value = serde_json::from_str(&json)
+        .map_err(|e| anyhow::anyhow!("{:?}: invalid geometry JSON: {}", path, e))?;
+    let has_angles = value.get("num_angles").is_some() || value.get("angles_deg").is_some();
+    if !has_angles || value.get("num_detectors").is_none() {
+        warn!(
+            "{:?} has no angle/detector metadata; not checking the system matrix against it",
+            path
+        );
+        return Ok(None);
+    }
+    Geometry::from_json(&json)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("{:?}: {}", path, e))
+}
+
+/// `load_geometry` for the simulation subcommands, which always work on the
+/// full grid: a ROI is dropped.
+fn load_full_geometry(path: &Path) -> Result<Geometry> {
+    let mut geometry = load_geometry(path)?;
+    if geometry.roi.take().is_some() {
+        info!(
+            "Ignoring the ROI in {:?}: simulating the full {:?} grid",
+            path, geometry.volume_shape
+        );
+    }
+    Ok(geometry)
+}
+
+/// Subtract the projections of the fixed background outside a ROI (see
+/// `Geometry::background_projections`), clamping at 0.
+fn subtract_roi_background<T: CliFloat>(projections: &mut Array1<T>, background: &Array1<T>) {
+    let mut clamped = 0;
+    Zip::from(projections).and(background).for_each(|y, &b| {
+        *y = *y - b;
+        if *y < T::zero() {
+            *y = T::zero();
+            clamped += 1;
+        }
+    });
+    if clamped > 0 {
+        warn!(
+            "{} rays have less signal than the ROI background predicts and were clamped to 0",
+            clamped
+        );
+    }
+}
+
+/// Log the ROI a geometry restricts the reconstruction to, if any.
+fn log_roi(geometry: &Geometry) {
+    if let Some(roi) = &geometry.roi {
+        info!(
+            "Reconstructing the {}x{} ROI at column {}, row {} (background {})",
+            roi.nx, roi.ny, roi.x0, roi.y0, roi.background
+        );
+    }
+}
+
+fn main() -> Result<()> {
+    // progress goes to stderr through `log` (RUST_LOG=debug adds every
+    // iteration, RUST_LOG=warn keeps only problems); results stay on stdout
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Slice(args)) => run_slice(&args),
+        Some(Command::Montage(args)) => run_montage(&args),
+        Some(Command::Compare(args)) => run_compare(&args),
+        Some(Command::Phantom(args)) => run_phantom(&args),
+        Some(Command::Batch(args)) => match args.dtype.map_or_else(|| batch_dtype(&args), Ok)? {
+            Dtype::F32 => run_batch::<f32>(&args),
+            Dtype::F64 => run_batch::<f64>(&args),
+        },
+        Some(Command::Forward(args)) => {
+            match args.dtype.unwrap_or_else(|| stored_dtype(&args.volume)) {
+                Dtype::F32 => run_forward::<f32>(&args),
+                Dtype::F64 => run_forward::<f64>(&args),
+            }
+        }
+        // without a subcommand clap requires the reconstruction flags
+        None => {
+            let args = cli.args.expect("reconstruction flags without a subcommand");
+            match detect_dtype(&args) {
+                Dtype::F32 => run::<f32>(&args),
+                Dtype::F64 => run::<f64>(&args),
+            }
+        }
+    }
+}
+
+/// Read a volume for `slice`, `montage` and `compare` as a
+/// `[slices, rows, cols]` stack, taking its shape from `volume_shape` or the
+/// .npy itself.
+fn read_volume_stack(path: &Path, volume_shape: Option<&VolumeShape>) -> Result<Array3<f64>> {
+    let volume: ArrayD<f64> =
+        read_float_npy(path).map_err(|e| anyhow::anyhow!("Failed to read volume: {}", e))?;
+    let shape = match volume_shape {
+        Some(shape) => shape.clone(),
+        // stored as (Y, X) or (Z, Y, X); VolumeShape lists X first
+        None if matches!(volume.ndim(), 2 | 3) => {
+            VolumeShape(volume.shape().iter().rev().copied().collect())
+        }
+        None => bail!("Volume {:?} is flat; pass --volume-shape", path),
+    };
+    if shape.num_voxels() != volume.len() {
+        bail!(
+            "Volume shape {} has {} voxels but the volume has {}",
+            shape,
+            shape.num_voxels(),
+            volume.len()
+        );
+    }
+    Ok(Array3::from_shape_vec(
+        shape.stack_3d()?,
+        volume.iter().copied().collect(),
+    )?)
+}
+
+impl SliceAxis {
+    /// Index of the axis in a `[slices, rows, cols]` stack.
+    fn stack_axis(self) -> Axis {
+        match self {
+            SliceAxis::Z => Axis(0),
+            SliceAxis::Y => Axis(1),
+            SliceAxis::X => Axis(2),
+        }
+    }
+}
+
+/// Write one slice of a volume as a min/max-normalized grayscale PNG.
+fn run_slice(args: &SliceArgs) -> Result<()> {
+    let volume = read_volume_stack(&args.volume, args.volume_shape.as_ref())?;
+    let axis = args.axis.stack_axis();
+    let len = volume.len_of(axis);
+    let index = args.index.unwrap_or(len / 2);
+    if index >= len {
+        bail!(
+            "Slice index {} is out of range for {} slices along {:?}",
+            index,
+            len,
+            args.axis
+        );
+    }
+    let slice = volume.index_axis(axis, index);
+
+    let min = slice.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = slice.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    let (rows, cols) = slice.dim();
+    let pixels: Vec<u8> = slice
+        .iter()
+        .map(|&v| {
+            if range > 0.0 {
+                ((v - min) / range * 255.0).round() as u8
+            } else {
+                0
+            }
+        })
+        .collect();
+    GrayImage::from_raw(u32::try_from(cols)?, u32::try_from(rows)?, pixels)
+        .expect("pixel buffer matches the slice size")
+        .save_with_format(&args.output, ImageFormat::Png)
+        .map_err(|e| anyhow::anyhow!("Failed to write PNG {:?}: {}", args.output, e))?;
+    println!(
+        "Slice {} along {:?} ({}x{}, values {}..{}) written to {:?}",
+        index, args.axis, cols, rows, min, max, args.output
+    );
+    Ok(())
+}
+
+/// 3x5 bitmap digits for the montage labels: one byte per row, the top bit
+/// of the three the leftmost pixel.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Draw `number` in white on a black box at the top-left corner `(x, y)`
+/// of a `width x height` tile, `scale` pixels per font pixel, clipped to
+/// the tile.
+fn draw_label(
+    image: &mut GrayImage,
+    (x, y): (u32, u32),
+    (width, height): (u32, u32),
+    number: usize,
+    scale: u32,
+) {
+    let digits: Vec<usize> = number
+        .to_string()
+        .bytes()
+        .map(|b| usize::from(b - b'0'))
+        .collect();
+    // one font pixel of padding around the digits and between them
+    let box_width = (4 * digits.len() as u32 + 1) * scale;
+    let box_height = 7 * scale;
+    for dy in 0..box_height.min(height) {
+        for dx in 0..box_width.min(width) {
+            let (fx, fy) = (dx / scale, dy / scale);
+            let lit = (1..6).contains(&fy) && fx % 4 != 0 && {
+                let glyph = DIGITS[digits[fx as usize / 4]];
+                glyph[fy as usize - 1] & (0b100 >> (fx % 4 - 1)) != 0
+            };
+            image.put_pixel(x + dx, y + dy, Luma([if lit { 255 } else { 0 }]));
+        }
+    }
+}
+
+/// Tile `rows x cols` evenly spaced slices of a volume into one PNG with a
+/// shared min/max normalization, each tile labeled with its slice index.
+fn run_montage(args: &MontageArgs) -> Result<()> {
+    if args.rows == 0 || args.cols == 0 {
+        bail!("--rows and --cols must be at least 1");
+    }
+    let volume = read_volume_stack(&args.volume, args.volume_shape.as_ref())?;
+    let axis = args.axis.stack_axis();
+    let len = volume.len_of(axis);
+    // the centers of `count` equal bins over the slices; with more tiles
+    // than slices every slice is shown once and the rest stay blank
+    let count = (args.rows * args.cols).min(len);
+    let indices: Vec<usize> = (0..count)
+        .map(|t| (2 * t + 1) * len / (2 * count))
+        .collect();
+
+    let slices: Vec<_> = indices
+        .iter()
+        .map(|&index| volume.index_axis(axis, index))
+        .collect();
+    let values = || slices.iter().flat_map(|slice| slice.iter().copied());
+    let min = values().fold(f64::INFINITY, f64::min);
+    let max = values().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    // tiles are separated by a gray gap so dark edges stay distinguishable
+    const GAP: u32 = 2;
+    let (tile_rows, tile_cols) = slices[0].dim();
+    let (tile_height, tile_width) = (u32::try_from(tile_rows)?, u32::try_from(tile_cols)?);
+    let width = u32::try_from(args.cols)? * (tile_width + GAP) - GAP;
+    let height = u32::try_from(args.rows)? * (tile_height + GAP) - GAP;
+    let mut image = GrayImage::from_pixel(width, height, Luma([128]));
+    let scale = (tile_width.min(tile_height) / 64).max(1);
+    for (t, (slice, &index)) in slices.iter().zip(&indices).enumerate() {
+        let x0 = (t % args.cols) as u32 * (tile_width + GAP);
+        let y0 = (t / args.cols) as u32 * (tile_height + GAP);
+        for ((r, c), &v) in slice.indexed_iter() {
+            let level = if range > 0.0 {
+                ((v - min) / range * 255.0).round() as u8
+            } else {
+                0
+            };
+            image.put_pixel(x0 + c as u32, y0 + r as u32, Luma([level]));
+        }
+        draw_label(
+            &mut image,
+            (x0, y0),
+            (tile_width, tile_height),
+            index,
+            scale,
+        );
+    }
+    image
+        .save_with_format(&args.output, ImageFormat::Png)
+        .map_err(|e| anyhow::anyhow!("Failed to write PNG {:?}: {}", args.output, e))?;
+    println!(
+        "Montage of {} slices along {:?} ({}x{} tiles, values {}..{}) written to {:?}",
+        count, args.axis, args.cols, args.rows, min, max, args.output
+    );
+    Ok(())
+}
+
+/// Print RMSE, PSNR and the SSIM of every Z slice of a reconstruction
+/// against its ground truth.
+fn run_compare(args: &CompareArgs) -> Result<()> {
+    let volume = read_volume_stack(&args.volume, args.volume_shape.as_ref())?;
+    let reference = read_volume_stack(&args.reference, args.volume_shape.as_ref())?;
+    if volume.dim() != reference.dim() {
+        bail!(
+            "{:?} has shape {:?} but the reference {:?} has {:?} (as [slices, rows, cols])",
+            args.volume,
+            volume.dim(),
+            args.reference,
+            reference.dim()
+        );
+    }
+    let (slices, rows, cols) = volume.dim();
+    let flat = |stack: Array3<f64>| Array1::from(stack.into_raw_vec());
+    let (volume, reference) = (flat(volume), flat(reference));
+
+    println!("RMSE: {}", metrics::rmse(&volume, &reference));
+    println!("PSNR: {:.2} dB", metrics::psnr(&volume, &reference));
+    let ssim = metrics::ssim_slices(&volume, &reference, [slices, rows, cols]);
+    if let [only] = ssim[..] {
+        println!("SSIM: {:.4}", only);
+    } else {
+        let mean = ssim.iter().sum::<f64>() / ssim.len() as f64;
+        let (worst, min) = ssim
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        println!(
+            "SSIM: {:.4} (mean of {} slices, lowest {:.4} at slice {})",
+            mean, slices, min, worst
+        );
+        for (z, value) in ssim.iter().enumerate() {
+            info!("SSIM slice {}: {:.4}", z, value);
+        }
+    }
+    Ok(())
+}
+
+/// Draw a phantom, forward-project it and write the whole test problem as
+/// one NPZ.
+fn run_phantom(args: &PhantomArgs) -> Result<()> {
+    if !(args.noise_level >= 0.0 && args.noise_level.is_finite()) {
+        bail!(
+            "--noise-level must be a non-negative number, got {}",
+            args.noise_level
+        );
+    }
+    if args.size == Some(0) {
+        bail!("--size must be at least 1");
+    }
+    let mut geometry = load_full_geometry(&args.geometry)?;
+    if let Some(size) = args.size {
+        geometry.volume_shape = [size, size];
+    }
+    let kind = match args.kind {
+        PhantomChoice::SheppLogan => PhantomKind::SheppLogan,
+        PhantomChoice::Shapes => PhantomKind::Shapes,
+    };
+    let volume: Array1<f64> = phantom(kind, geometry.volume_shape);
+
+    let sparse = geometry.build_system_matrix::<f64>();
+    let mut projections = forward_project_sparse(&sparse, &volume);
+    if args.noise_level > 0.0 {
+        let model = match args.noise {
+            NoiseChoice::Gaussian => NoiseModel::Gaussian,
+            NoiseChoice::Poisson => NoiseModel::Poisson,
+        };
+        projections = add_noise(&projections, model, args.noise_level, args.seed);
+    }
+    let mut system_matrix = Array2::zeros(sparse.dim());
+    for i in 0..sparse.dim().0 {
+        let (cols, vals) = sparse.row(i);
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            system_matrix[[i, j]] = a_ij;
+        }
+    }
+
+    let write = || -> Result<()> {
+        let mut npz = NpzWriter::new(File::create(&args.output)?);
+        npz.add_array("projections.npy", &projections)?;
+        npz.add_array("system_matrix.npy", &system_matrix)?;
+        npz.add_array("volume.npy", &volume)?;
+        npz.finish()?;
+        Ok(())
+    };
+    write().map_err(|e| anyhow::anyhow!("Failed to write phantom NPZ {:?}: {}", args.output, e))?;
+    println!(
+        "{:?} phantom {:?} ({} rays x {} voxels) written to {:?}",
+        args.kind,
+        geometry.volume_shape,
+        sparse.dim().0,
+        sparse.dim().1,
+        args.output
+    );
+    Ok(())
+}
+
+/// Forward-project a volume through the geometry's system matrix.
+fn run_forward<T: CliFloat>(args: &ForwardArgs) -> Result<()> {
+    let volume: Array1<T> = read_float_npy(&args.volume)
+        .map_err(|e| anyhow::anyhow!("Failed to read volume: {}", e))?;
+    let geometry = load_full_geometry(&args.geometry)?;
+    if volume.len() != geometry.num_voxels() {
+        bail!(
+            "Volume has length {} but the geometry's volume_shape {:?} has {} voxels",
+            volume.len(),
+            geometry.volume_shape,
+            geometry.num_voxels()
+        );
+    }
+
+    let system_matrix = geometry.build_system_matrix::<T>();
+    let projections = forward_project_sparse(&system_matrix, &volume);
+
+    write_npy(&args.output, &projections)
+        .map_err(|e| anyhow::anyhow!("Failed to write projections NPY {:?}: {}", args.output, e))?;
+    println!(
+        "{} projections written to {:?}",
+        projections.len(),
+        args.output
+    );
+    Ok(())
+}
+
+/// Projection files of `mart_cli batch --projection-dir`, sorted by name.
+fn batch_scan_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("Failed to read projection directory {:?}: {}", dir, e))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "npy") {
+            files.push(path);
+        }
+    }
+    if files.is_empty() {
+        bail!("No .npy files in projection directory {:?}", dir);
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Precision of a batch without --dtype: that of the stack, or of the first
+/// scan file.
+fn batch_dtype(args: &BatchArgs) -> Result<Dtype> {
+    Ok(match (&args.projection_stack, &args.projection_dir) {
+        (Some(stack), _) => stored_dtype(stack),
+        (None, Some(dir)) => stored_dtype(&batch_scan_files(dir)?[0]),
+        (None, None) => unreachable!("clap requires --projection-dir or --projection-stack"),
+    })
+}
+
+/// Reconstruct every scan of a batch with one shared system matrix.
+///
+/// In rayon builds the scans run in parallel, each on one thread, so every
+/// output matches `mart_cli --threads 1` on that scan. A scan that cannot be
+/// reconstructed (unreadable, wrong length, NaN/Inf) is reported and
+/// skipped, and the command fails once the others are done.
+fn run_batch<T: CliFloat>(args: &BatchArgs) -> Result<()> {
+    // --- Collect the scans and their output paths ---
+    let files = match &args.projection_dir {
+        Some(dir) => batch_scan_files(dir)?,
+        None => Vec::new(),
+    };
+    let stack: Option<Array2<T>> = match &args.projection_stack {
+        Some(path) => Some(
+            read_float_npy(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read projection stack: {}", e))?,
+        ),
+        None => None,
+    };
+    let (names, outputs): (Vec<String>, Vec<PathBuf>) = match (&stack, &args.projection_stack) {
+        (Some(stack), Some(path)) => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let width = stack.nrows().to_string().len();
+            (0..stack.nrows())
+                .map(|k| {
+                    (
+                        format!("row {}", k),
+                        args.output_dir.join(format!("{}_{:0width$}.npy", stem, k)),
+                    )
+                })
+                .unzip()
+        }
+        _ => files
+            .iter()
+            .map(|file| {
+                let name = file.file_name().expect("read_dir entries have a file name");
+                (
+                    name.to_string_lossy().into_owned(),
+                    args.output_dir.join(name),
+                )
+            })
+            .unzip(),
+    };
+    if names.is_empty() {
+        bail!("The projection stack has no scans");
+    }
+    for output in &outputs {
+        check_clobber(output, args.no_clobber, args.force)?;
+    }
+
+    std::fs::create_dir_all(&args.output_dir).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to create output directory {:?}: {}",
+            args.output_dir,
+            e
+        )
+    })?;
+    if let Some(dir) = &args.projection_dir {
+        if dir.canonicalize()? == args.output_dir.canonicalize()? {
+            bail!(
+                "--output-dir must differ from --projection-dir; the outputs reuse the scan \
+                file names"
+            );
+        }
+    }
+
+    // --- Load and validate the shared system matrix once ---
+    let (system_matrix, geometry, built) = match &args.system_matrix {
+        Some(path) => {
+            let geometry = load_matrix_geometry(&args.geometry)?;
+            let system_matrix = load_system_matrix::<T>(path, false)?;
+            if let Some(geometry) = &geometry {
+                check_matrix_geometry(geometry, system_matrix.dim(), path, &args.geometry)?;
+            }
+            (system_matrix, geometry, false)
+        }
+        None => {
+            let geometry = load_geometry(&args.geometry)?;
+            info!(
+                "Building system matrix from geometry ({} angles x {} detectors, volume {:?})",
+                geometry.num_angles, geometry.num_detectors, geometry.volume_shape
+            );
+            (
+                SystemMatrix::Sparse(geometry.build_system_matrix()),
+                Some(geometry),
+                true,
+            )
+        }
+    };
+    geometry.iter().for_each(log_roi);
+    let background = geometry
+        .as_ref()
+        .and_then(Geometry::background_projections::<T>);
+    let built_from = geometry.as_ref().filter(|_| built);
+    if !args.skip_validation {
+        match &system_matrix {
+            SystemMatrix::Dense(a) => validate_system_matrix(a),
+            SystemMatrix::Sparse(a) => validate_system_matrix_sparse(a),
+        }
+        .map_err(|e| anyhow::anyhow!("{} (pass --skip-validation to run anyway)", e))?;
+    }
+    let volume_shape =
+        built_from.map(|geometry| VolumeShape::from_grid(geometry.reconstruction_shape()));
+
+    let (m, n) = system_matrix.dim();
+    info!(
+        "Running MART ({:?}) on {} scans with M = {}, N = {}, n_iters = {}, relaxation = {}",
+        T::DTYPE,
+        names.len(),
+        m,
+        n,
+        args.n_iters,
+        args.relaxation
+    );
+    let bound = relaxation_bound(Algorithm::Mart, &system_matrix, None);
+    check_relaxation(
+        Algorithm::Mart,
+        args.relaxation,
+        bound,
+        args.allow_unsafe_relax,
+    )?;
+    let mart = MartBuilder::new()
+        .iterations(args.n_iters)
+        .relaxation(args.relaxation)
+        .threads(1);
+    let output_dtype = args.output_dtype.unwrap_or(T::DTYPE);
+    // hashed once: the geometry, matrix and stack every scan shares
+    let shared_inputs = if args.no_metadata {
+        None
+    } else {
+        let optional = [
+            ("system_matrix", args.system_matrix.as_deref()),
+            ("projection_stack", args.projection_stack.as_deref()),
+        ];
+        let mut inputs = vec![("geometry", args.geometry.as_path())];
+        inputs.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, path)| Some((name, path?))),
+        );
+        Some(hash_inputs(&inputs)?)
+    };
+
+    let reconstruct = |k: usize| -> Result<()> {
+        let mut projections: Array1<T> = match &stack {
+            Some(stack) => stack.row(k).to_owned(),
+            None => read_float_npy(&files[k])?,
+        };
+        if projections.len() != m {
+            bail!(
+                "projections have length {} but the system matrix has {} rows",
+                projections.len(),
+                m
+            );
+        }
+        if let Some(background) = &background {
+            subtract_roi_background(&mut projections, background);
+        }
+        let report = match &system_matrix {
+            SystemMatrix::Dense(a) => mart.run(&projections, a)?,
+            SystemMatrix::Sparse(a) => mart.run(&projections, a)?,
+        };
+
+        let output = &outputs[k];
+        let shape = volume_shape
+            .as_ref()
+            .map_or_else(|| vec![n], VolumeShape::array_shape);
+        write_volume_npy(output, &report.volume, &shape, output_dtype)
+            .map_err(|e| anyhow::anyhow!("Failed to write output NPY {:?}: {}", output, e))?;
+        let provenance = match (&shared_inputs, files.get(k)) {
+            (None, _) => None,
+            (Some(inputs), file) => {
+                let mut inputs = inputs.clone();
+                if let Some(file) = file {
+                    inputs.extend(hash_inputs(&[("projections", file)])?);
+                }
+                let mut record = provenance(
+                    inputs,
+                    "MART",
+                    report.iterations_run,
+                    report.final_residual.to_f64().unwrap(),
+                );
+                if stack.is_some() {
+                    record["stack_row"] = json!(k);
+                }
+                Some(record)
+            }
+        };
+        if volume_shape.is_some() || provenance.is_some() {
+            let sidecar = sidecar_path(output);
+            write_sidecar(
+                &sidecar,
+                volume_shape.as_ref(),
+                output_dtype,
+                None,
+                None,
+                provenance.as_ref(),
+            )
+            .map_err(|e| anyhow::anyhow!("Failed to write volume metadata {:?}: {}", sidecar, e))?;
+        }
+        println!(
+            "{}: relative residual {:?} written to {:?}",
+            names[k], report.final_residual, output
+        );
+        Ok(())
+    };
+
+    let started = Instant::now();
+    #[cfg(feature = "rayon")]
+    let results: Vec<Result<()>> = {
+        use rayon::prelude::*;
+        (0..names.len()).into_par_iter().map(reconstruct).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let results: Vec<Result<()>> = (0..names.len()).map(reconstruct).collect();
+
+    let mut failed = 0;
+    for (name, result) in names.iter().zip(&results) {
+        if let Err(e) = result {
+            error!("Scan {} failed: {}", name, e);
+            failed += 1;
+        }
+    }
+    println!(
+        "Reconstructed {} of {} scans in {:.2?}",
+        names.len() - failed,
+        names.len(),
+        started.elapsed()
+    );
+    if failed > 0 {
+        bail!("{} of {} scans failed", failed, names.len());
+    }
+    Ok(())
+}
+
+/// `--diagnose`: singular-value estimates are converged enough after this
+/// many power / inverse iteration steps to separate usable from hopeless
+/// geometries.
+const DIAGNOSE_ITERS: usize = 20;
+
+/// Condition numbers above this get a warning from `--diagnose`.
+const ILL_CONDITIONED: f64 = 1e6;
+
+/// Power iteration steps behind `--relaxation auto`; the estimate only has
+/// to be good to a few percent, which the 10% safety margin absorbs.
+const SPECTRAL_RADIUS_ITERS: usize = 30;
+
+/// Estimated spectral radius `rho` of the SIRT/SART or, for Landweber, the
+/// Landweber iteration; relaxations in `(0, 2 / rho)` converge.
+fn spectral_radius<T: ReconFloat>(
+    algorithm: Algorithm,
+    system_matrix: &SystemMatrix<T>,
+    mask: Option<&Array1<bool>>,
+) -> f64 {
+    match (system_matrix, algorithm) {
+        (SystemMatrix::Dense(a), Algorithm::Landweber) => {
+            estimate_landweber_spectral_radius(a, SPECTRAL_RADIUS_ITERS)
+        }
+        (SystemMatrix::Sparse(a), Algorithm::Landweber) => {
+            estimate_landweber_spectral_radius(a, SPECTRAL_RADIUS_ITERS)
+        }
+        (SystemMatrix::Dense(a), _) => {
+            estimate_sirt_spectral_radius(a, mask, SPECTRAL_RADIUS_ITERS)
+        }
+        (SystemMatrix::Sparse(a), _) => {
+            estimate_sirt_spectral_radius(a, mask, SPECTRAL_RADIUS_ITERS)
+        }
+    }
+}
+
+/// Relaxation at and above which `algorithm` is unstable.
+///
+/// For ART (Kaczmarz) it is 2, the point where a ray's update overshoots
+/// its residual by as much as it started with. MART is the multiplicative
+/// analogue: at 2 one ray's update reflects `y_hat` to `y^2 / y_hat`, so
+/// the bound for MART and SMART is 2 too, although SMART only provably
+/// converges up to 1. SIRT and SART converge below `2 / rho`, where `rho`
+/// is exactly 1 for a nonnegative matrix and estimated otherwise, and
+/// Landweber below `2 / sigma_max(A)^2`, which is always estimated. MLEM
+/// has no relaxation and no bound.
+fn relaxation_bound<T: ReconFloat>(
+    algorithm: Algorithm,
+    system_matrix: &SystemMatrix<T>,
+    mask: Option<&Array1<bool>>,
+) -> f64 {
+    match algorithm {
+        Algorithm::Mlem => f64::INFINITY,
+        Algorithm::Mart | Algorithm::Smart | Algorithm::Art => 2.0,
+        Algorithm::Sirt | Algorithm::Sart if !system_matrix.has_negative_entries() => 2.0,
+        Algorithm::Sirt | Algorithm::Sart | Algorithm::Landweber => {
+            2.0 / spectral_radius(algorithm, system_matrix, mask)
+        }
+    }
+}
+
+/// Warn when a multiplicative method is over-relaxed (`relaxation > 1`),
+/// and refuse a relaxation at or above `bound` unless `allow_unsafe`.
+fn check_relaxation(
+    algorithm: Algorithm,
+    relaxation: f64,
+    bound: f64,
+    allow_unsafe: bool,
+) -> Result<()> {
+    if relaxation >= bound {
+        if !allow_unsafe {
+            bail!(
+                "Relaxation {} is at or above the stability bound {:.6} of {}; lower it, or pass \
+                --allow-unsafe-relax to run anyway",
+                relaxation,
+                bound,
+                algorithm.label()
+            );
+        }
+        warn!(
+            "Relaxation {} is at or above the stability bound {:.6} of {}; expect the \
+            reconstruction to oscillate or diverge",
+            relaxation,
+            bound,
+            algorithm.label()
+        );
+    } else if relaxation > 1.0 && algorithm.is_multiplicative() {
+        warn!(
+            "Relaxation {} > 1 over-relaxes the multiplicative {} update: early iterations may \
+            converge faster, but it can oscillate (stability bound {:.6})",
+            relaxation,
+            algorithm.label(),
+            bound
+        );
+    }
+    Ok(())
+}
+
+/// Print the estimated extreme singular values and condition number.
+fn diagnose<T: ReconFloat>(system_matrix: &SystemMatrix<T>) -> Result<()> {
+    let estimate = match system_matrix {
+        SystemMatrix::Dense(a) => estimate_condition(a, DIAGNOSE_ITERS),
+        SystemMatrix::Sparse(a) => estimate_condition_sparse(a, DIAGNOSE_ITERS),
+    };
+    let (m, n) = system_matrix.dim();
+    println!("System matrix: M = {}, N = {}", m, n);
+    println!("Largest singular value:  ~{:.6e}", estimate.sigma_max);
+    println!("Smallest singular value: ~{:.6e}", estimate.sigma_min);
+    println!(
+        "Condition number:        ~{:.3e} (estimates are lower bounds)",
+        estimate.condition_number()
+    );
+    if estimate.condition_number() > ILL_CONDITIONED {
+        warn!(
+            "The system matrix is badly conditioned; add angles or detectors, or regularize \
+             (--reg-l2, --tv-weight)"
+        );
+    }
+    if m < n {
+        println!(
+            "Note: {} rays for {} voxels; the remaining {} voxel combinations are unmeasured",
+            m,
+            n,
+            n - m
+        );
+    }
+    Ok(())
+}
+
+/// `--dry-run`: report the problem size and a peak memory estimate.
+///
+/// The estimate counts the system matrix, the projections and volume, and
+/// the length-M and length-N work vectors the solver keeps (row and column
+/// sums, the estimated projections, subset copies of the volume, ...). It
+/// leaves out allocator overhead and the output conversion, so treat it as
+/// a lower bound.
+fn dry_run<T: CliFloat>(system_matrix: &SystemMatrix<T>, args: &Args) {
+    let (m, n) = system_matrix.dim();
+    let value = std::mem::size_of::<T>();
+    let index = std::mem::size_of::<usize>();
+    println!("System matrix: M = {}, N = {}", m, n);
+    let matrix_bytes = match system_matrix {
+        SystemMatrix::Dense(a) => {
+            let nnz = a.iter().filter(|&&a_ij| a_ij != T::zero()).count();
+            let bytes = m * n * value;
+            println!(
+                "Dense storage: {} ({} nonzeros, density {:.3}%)",
+                format_bytes(bytes),
+                nnz,
+                100.0 * nnz as f64 / (m * n).max(1) as f64
+            );
+            bytes
+        }
+        SystemMatrix::Sparse(a) => {
+            let bytes = a.nnz() * (value + index) + (m + 1) * index;
+            println!(
+                "Sparse CSR storage: {} (nnz = {}, density {:.3}%)",
+                format_bytes(bytes),
+                a.nnz(),
+                100.0 * a.nnz() as f64 / (m * n).max(1) as f64
+            );
+            bytes
+        }
+    };
+
+    // work vectors on top of the projections, the volume and the residual's
+    // y_hat, as (length-M, length-N) counts
+    let (rays, voxels) = match args.algorithm {
+        Algorithm::Mart if args.os_parallel => (0, 2 * args.n_subsets),
+        Algorithm::Mart if args.blocks > 1 => (2, 0),
+        Algorithm::Mart => (
+            0,
+            usize::from(args.weighted || args.voxel_weights.is_some()),
+        ),
+        Algorithm::Art | Algorithm::Landweber => (2, 1),
+        Algorithm::Sirt | Algorithm::Sart | Algorithm::Mlem => (4, 2),
+        Algorithm::Smart => (3, 2),
+    };
+    let auto_relax = if args.auto_relax { 1 } else { 0 };
+    let vector_bytes = ((2 + rays) * m + (1 + voxels + auto_relax) * n) * value;
+    println!(
+        "Algorithm: {} ({:?}), n_iters = {}",
+        args.algorithm.label(),
+        T::DTYPE,
+        args.n_iters
+    );
+    println!(
+        "Estimated peak memory: ~{} (matrix {}, vectors {})",
+        format_bytes(matrix_bytes + vector_bytes),
+        format_bytes(matrix_bytes),
+        format_bytes(vector_bytes)
+    );
+}
+
+/// `bytes` in the largest binary unit that keeps the value at least 1.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", value, UNITS[unit])
+}
+
+/// Print the `--report-quality` proxies of `volume` and return them for the
+/// provenance record.
+///
+/// `bounds` are the clamp bounds the solver applied, if any; voxels outside
+/// `mask` are left out of the fractions at them.
+fn report_quality<T: ReconFloat>(
+    residual: f64,
+    volume: &Array1<T>,
+    volume_shape: Option<&VolumeShape>,
+    bounds: Option<(f64, f64)>,
+    mask: Option<&Array1<bool>>,
+) -> Result<serde_json::Value> {
+    println!("Quality (no reference):");
+    println!("  data-fit residual: {:.6e}", residual);
+    let tv = match volume_shape {
+        Some(shape) => {
+            let tv = total_variation(volume, shape.stack_3d()?);
+            println!(
+                "  total variation:   {:.6e} ({:.6e} per voxel)",
+                tv,
+                tv / volume.len() as f64
+            );
+            Some(tv)
+        }
+        None => {
+            println!(
+                "  total variation:   n/a (needs --volume-shape or a matrix built from --geometry)"
+            );
+            None
+        }
+    };
+    let at_bounds = bounds.map(|bounds| metrics::fraction_at_bounds(volume, bounds, mask));
+    match (bounds, at_bounds) {
+        (Some((lo, hi)), Some((below, above))) => println!(
+            "  at bounds:         {:.2}% at the lower bound {}, {:.2}% at the upper bound {}",
+            100.0 * below,
+            lo,
+            100.0 * above,
+            hi
+        ),
+        _ => println!("  at bounds:         n/a (no --nonneg, --min-val or --max-val)"),
+    }
+    Ok(json!({
+        "residual": residual,
+        "total_variation": tv,
+        "fraction_at_lower_bound": at_bounds.map(|(below, _)| below),
+        "fraction_at_upper_bound": at_bounds.map(|(_, above)| above),
+    }))
+}
+
+/// Load inputs, reconstruct and write the output in precision `T`.
+fn run<T: CliFloat>(args: &Args) -> Result<()> {
+    let mut timing = Timing::new(args.timing);
+    // --continue asks to update the existing --output in place
+    if let Some(output) = args.output.as_ref().filter(|_| !args.continue_run) {
+        check_clobber(output, args.no_clobber, args.force)?;
+    }
+
+    // --- Load projections + system matrix from .npy/.npz (or .h5) files ---
+    check_input_flags(args)?;
+    let projections = load_projections::<T>(args)?;
+    let mut problem = load_problem(args, projections, &mut timing)?;
+    timing.phase("validation");
+
+    if args.diagnose {
+        diagnose(&problem.system_matrix)?;
+        timing.phase("diagnose");
+        timing.print();
+        return Ok(());
+    }
+    if args.dry_run {
+        dry_run(&problem.system_matrix, args);
+        timing.print();
+        return Ok(());
+    }
+    let Some(output) = &args.output else {
+        bail!("--output is required unless --diagnose or --dry-run is given");
+    };
+
+    let row_norms = args.normalize_rows.then(|| problem.normalize_rows());
+    if let Some(norms) = &row_norms {
+        let (lo, hi) = norms
+            .iter()
+            .fold((T::infinity(), T::zero()), |(lo, hi), &n| {
+                (lo.min(n), hi.max(n))
+            });
+        info!(
+            "Normalized the system matrix rows (L2 norms {:?} to {:?})",
+            lo, hi
+        );
+    }
+
+    info!(
+        "Running {} ({:?}) with M = {}, N = {}, n_iters = {}, relaxation = {}",
+        args.algorithm.label(),
+        T::DTYPE,
+        problem.system_matrix.dim().0,
+        problem.system_matrix.dim().1,
+        args.n_iters,
+        args.relaxation
+    );
+    if let Some(schedule) = &args.relax_schedule {
+        info!("Relaxation schedule {:?} overrides --relaxation", schedule);
+    }
+    if args.auto_relax {
+        info!(
+            "Tuning relaxation every {} iterations over {:?}",
+            args.auto_relax_every, args.auto_relax_candidates
+        );
+    }
+
+    check_solver_flags(args, &problem)?;
+
+    let resume = Resume::load(args, output, problem.system_matrix.dim().1)?;
+    let start_iteration = resume.start_iteration();
+    // --n-iters counts all iterations for --resume but the added ones for
+    // --continue
+    let total_iters = if args.continue_run {
+        start_iteration + args.n_iters
+    } else {
+        args.n_iters
+    };
+    let n_iters = total_iters.saturating_sub(start_iteration);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let mart = mart_builder(args, &problem, &resume, &stop)?.iterations(n_iters);
+    let base_relaxation = choose_relaxation(
+        args,
+        &problem.system_matrix,
+        mart.options(),
+        total_iters,
+        &mut timing,
+    )?;
+    let mart = mart.relaxation(base_relaxation);
+    let options = mart.options();
+
+    // --- Run reconstruction ---
+    let checkpoint_path = args
+        .checkpoint_path
+        .clone()
+        .unwrap_or_else(|| output.with_extension("ckpt.npz"));
+    let mut checkpoints = Checkpointer::new(Some(checkpoint_path), args.checkpoint_every);
+
+    if let (Some(every), Some(dir)) = (args.snapshot_every, &args.snapshot_dir) {
+        let count = total_iters / every - start_iteration / every;
+        if count > args.snapshot_max {
+            warn!(
+                "--snapshot-every {} writes up to {} files to {:?} (more than --snapshot-max {})",
+                every, count, dir, args.snapshot_max
+            );
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create snapshot directory {:?}: {}", dir, e))?;
+    }
+    let mut snapshots = Snapshots::new(
+        args.snapshot_dir.clone(),
+        args.snapshot_every,
+        problem.volume_shape.as_ref().map_or_else(
+            || vec![problem.system_matrix.dim().1],
+            VolumeShape::array_shape,
+        ),
+        args.output_dtype.unwrap_or(T::DTYPE),
+    );
+    let mut voxel_stats = args.voxel_stats.clone().map(|path| {
+        let start = problem.start_volume(options);
+        let multiplicative = matches!(
+            args.algorithm,
+            Algorithm::Mart | Algorithm::Smart | Algorithm::Mlem
+        );
+        VoxelStats::new(path, &start, multiplicative)
+    });
+
+    #[cfg(feature = "mlflow")]
+    let mut mlflow = args
+        .mlflow_uri
+        .as_deref()
+        .zip(args.run_id.as_deref())
+        .map(|(uri, run_id)| MlflowLogger::new(uri, run_id));
+
+    let mut log = ResidualLog::create(args.residual_log.as_deref(), args.residual_csv.as_deref())?;
+    // relaxation of each pass as the solvers pick it; --auto-relax tunes it
+    // internally and MLEM has none
+    let relaxation_at = |iter: usize| {
+        if options.auto_relaxation.is_some() || args.algorithm == Algorithm::Mlem {
+            return None;
+        }
+        Some(
+            options
+                .relaxation_schedule
+                .as_ref()
+                .map_or(base_relaxation, |schedule| schedule.at(iter, total_iters)),
+        )
+    };
+    #[cfg(feature = "mlflow")]
+    let solve_start = Instant::now();
+    timing.phase("setup");
+    let callback = |iter: usize, volume: &Array1<T>, residual: T| {
+        timing.iteration();
+        debug!("Iteration {}: relative residual {:?}", iter, residual);
+        log.record(iter, residual, relaxation_at(iter));
+        checkpoints.record(iter, volume);
+        snapshots.record(iter, volume);
+        if let Some(stats) = voxel_stats.as_mut() {
+            stats.record(volume);
+        }
+        #[cfg(feature = "mlflow")]
+        if let Some(mlflow) = mlflow.as_mut() {
+            mlflow.log_metric("residual", residual.to_f64().unwrap(), iter);
+        }
+    };
+
+    install_interrupt_handler(stop)?;
+    let report = solve(args, &problem, &mart, n_iters, base_relaxation, callback)?;
+    timing.phase("reconstruction");
+    log.finish()?;
+    checkpoints.finish()?;
+    snapshots.finish()?;
+    if let Some(stats) = voxel_stats {
+        stats.finish()?;
+    }
+    let mut report = report.check_diverged()?;
+    if report.interrupted {
+        warn!(
+            "Interrupted after iteration {} of {}; writing the current volume",
+            start_iteration + report.iterations_run,
+            total_iters
+        );
+    }
+    if let Some(norms) = &row_norms {
+        problem.restore_rows(norms);
+        report.final_residual = problem
+            .system_matrix
+            .relative_residual(&problem.projections, &report.volume);
+    }
+
+    #[cfg(feature = "mlflow")]
+    if let Some(mut mlflow) = mlflow {
+        let step = start_iteration + report.iterations_run;
+        mlflow.log_metric(
+            "final_residual",
+            report.final_residual.to_f64().unwrap(),
+            step,
+        );
+        mlflow.log_metric("iterations_run", report.iterations_run as f64, step);
+        mlflow.log_metric("converged", if report.converged { 1.0 } else { 0.0 }, step);
+        mlflow.log_metric("solve_seconds", solve_start.elapsed().as_secs_f64(), step);
+        if mlflow.failures() > 0 {
+            warn!(
+                "{} metric(s) could not be sent to MLflow",
+                mlflow.failures()
+            );
+        }
+    }
+
+    match report.stopped_by {
+        Some(StopCriterion::Residual) => {
+            info!(
+                "Converged after {} iterations (tol = {})",
+                report.iterations_run,
+                args.tol.unwrap()
+            );
+        }
+        Some(StopCriterion::VolumeChange) => {
+            info!(
+                "Converged after {} iterations (tol-x = {})",
+                report.iterations_run,
+                args.tol_x.unwrap()
+            );
+        }
+        None if args.tol.is_some() || args.tol_x.is_some() => {
+            info!("Did not converge within {} iterations", total_iters);
+        }
+        None => {}
+    }
+    println!("Final relative residual: {:?}", report.final_residual);
+
+    write_outputs(
+        args,
+        output,
+        &problem,
+        report,
+        resume,
+        options.mask.as_ref(),
+        &mut timing,
+    )?;
+    timing.phase("output");
+    timing.print();
+
+    Ok(())
+}
+
+/// Check the numeric flags that need none of the inputs, before loading them.
+fn check_input_flags(args: &Args) -> Result<()> {
+    if !(args.scale.is_finite() && args.scale > 0.0) {
+        bail!("--scale must be a positive number, got {}", args.scale);
+    }
+    if let Some(tol_x) = args.tol_x.filter(|t| !(t.is_finite() && *t > 0.0)) {
+        bail!("--tol-x must be a positive number, got {}", tol_x);
+    }
+    if let Some(voxel_size) = args.voxel_size.filter(|v| !(v.is_finite() && *v > 0.0)) {
+        bail!(
+            "--voxel-size must be a positive number of mm, got {}",
+            voxel_size
+        );
+    }
+    if !(args.unit_scale.is_finite() && args.unit_scale > 0.0) {
+        bail!(
+            "--unit-scale must be a positive number, got {}",
+            args.unit_scale
+        );
+    }
+    Ok(())
+}
+
+/// Read --projections (.npy or .h5) in solver units, with the --flat/--dark
+/// correction and the --log-transform applied.
+fn load_projections<T: CliFloat>(args: &Args) -> Result<Array1<T>> {
+    let scale = T::from(args.scale).unwrap();
+    let projections: Array1<T> = if is_hdf5(&args.projections) {
+        read_hdf5_projections::<T>(args)?.mapv(|v| v * scale)
+    } else {
+        read_counts_npy(&args.projections, scale)
+            .map_err(|e| anyhow::anyhow!("Failed to read projections: {}", e))?
+    };
+    if args.log_transform && args.i0.is_none() && args.flat.is_none() {
+        bail!("--log-transform needs the flat-field intensity from --i0 or --flat");
+    }
+    let projections = match &args.flat {
+        Some(flat_path) => {
+            let read_field = |path: &Path, what: &str| -> Result<Array1<T>> {
+                read_counts_npy(path, scale)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {} {:?}: {}", what, path, e))
+            };
+            let flat = read_field(flat_path, "flat field")?;
+            let dark = match &args.dark {
+                Some(path) => read_field(path, "dark field")?,
+                None => Array1::zeros(1),
+            };
+            info!("Applying flat/dark field correction (I - dark) / (flat - dark)");
+            let (corrected, clamped) = flat_dark_correct(&projections, &dark, &flat)?;
+            if !clamped.is_empty() {
+                warn!(
+                    "{} rays have flat <= dark (first at ray {}); their denominator was clamped \
+                    to {}",
+                    clamped.len(),
+                    clamped[0],
+                    MIN_FLAT_DARK_GAP
+                );
+            }
+            corrected
+        }
+        None => projections,
+    };
+    Ok(match (&args.i0, args.log_transform) {
+        (None, true) => {
+            info!(
+                "Converting {} flat-corrected transmissions to line integrals -ln(T)",
+                projections.len()
+            );
+            log_transform(&projections, &Array1::ones(1))?
+        }
+        (Some(i0), true) => {
+            let flat_field: Array1<T> = match i0 {
+                FlatField::Value(value) => Array1::from_elem(1, T::from(*value).unwrap() * scale),
+                FlatField::Npy(path) => read_counts_npy(path, scale)
+                    .map_err(|e| anyhow::anyhow!("Failed to read flat field {:?}: {}", path, e))?,
+            };
+            info!(
+                "Converting {} intensities to line integrals -ln(I / I0)",
+                projections.len()
+            );
+            log_transform(&projections, &flat_field)?
+        }
+        _ => projections,
+    })
+}
+
+/// The inputs of a reconstruction, loaded and checked against each other.
+struct Problem<T> {
+    projections: Array1<T>,
+    system_matrix: SystemMatrix<T>,
+    /// Geometry of a matrix built from --geometry, after --use-angles
+    built_from: Option<Geometry>,
+    volume_shape: Option<VolumeShape>,
+}
+
+impl<T: ReconFloat> Problem<T> {
+    /// --normalize-rows: scale every ray to a unit-norm matrix row, returning
+    /// the row norms for `restore_rows`.
+    fn normalize_rows(&mut self) -> Array1<T> {
+        match &mut self.system_matrix {
+            SystemMatrix::Dense(a) => normalize_rows(&mut self.projections, a),
+            SystemMatrix::Sparse(a) => normalize_rows_sparse(&mut self.projections, a),
+        }
+    }
+
+    /// Undo `normalize_rows`.
+    fn restore_rows(&mut self, norms: &Array1<T>) {
+        match &mut self.system_matrix {
+            SystemMatrix::Dense(a) => restore_rows(&mut self.projections, a, norms),
+            SystemMatrix::Sparse(a) => restore_rows_sparse(&mut self.projections, a, norms),
+        }
+    }
+
+    /// The starting volume exactly as the solvers build it from `options`.
+    fn start_volume(&self, options: &ReconOptions) -> Array1<T> {
+        let n = self.system_matrix.dim().1;
+        let mut start = match &options.initial_guess {
+            InitialGuess::Uniform(value) => Array1::from_elem(n, T::from(*value).unwrap()),
+            InitialGuess::FromArray(volume) => volume.mapv(|v| T::from(v).unwrap()),
+            InitialGuess::Backprojection => self.system_matrix.backprojection(&self.projections),
+        };
+        if let Some(mask) = &options.mask {
+            Zip::from(&mut start).and(mask).for_each(|v, &keep| {
+                if !keep {
+                    *v = T::zero();
+                }
+            });
+        }
+        start
+    }
+}
+
+/// Load or build the system matrix for `projections`, apply the ROI background
+/// and angle selection, and validate (and with --sanitize, clean) both.
+fn load_problem<T: CliFloat>(
+    args: &Args,
+    mut projections: Array1<T>,
+    timing: &mut Timing,
+) -> Result<Problem<T>> {
+    let (mut system_matrix, geometry, built) = match &args.system_matrix {
+        Some(path) => {
+            let geometry = load_matrix_geometry(&args.geometry)?;
+            let system_matrix = if is_hdf5(path) {
+                read_hdf5_system_matrix::<T>(path, args)?
+            } else {
+                load_system_matrix::<T>(path, args.mmap)?
+            };
+            timing.phase("load");
+            if let Some(geometry) = &geometry {
+                check_matrix_geometry(geometry, system_matrix.dim(), path, &args.geometry)?;
+            }
+            (system_matrix, geometry, false)
+        }
+        None => {
+            let geometry = load_geometry(&args.geometry)?;
+            geometry
+                .check_num_rays(projections.len())
+                .map_err(|e| anyhow::anyhow!("{:?}: {}", args.geometry, e))?;
+            info!(
+                "Building system matrix from geometry ({} angles x {} detectors, volume {:?})",
+                geometry.num_angles, geometry.num_detectors, geometry.volume_shape
+            );
+            timing.phase("load");
+            let system_matrix = geometry.build_system_matrix();
+            timing.phase("matrix construction");
+            if let Some(path) = &args.save_matrix {
+                save_matrix_npz(path, &system_matrix, &geometry).map_err(|e| {
+                    anyhow::anyhow!("Failed to write system matrix {:?}: {}", path, e)
+                })?;
+                println!(
+                    "System matrix ({} nonzeros) written to {:?}",
+                    system_matrix.nnz(),
+                    path
+                );
+                timing.phase("matrix export");
+            }
+            (SystemMatrix::Sparse(system_matrix), Some(geometry), true)
+        }
+    };
+    geometry.iter().for_each(log_roi);
+    if let Some(geometry) = &geometry {
+        if let Some(background) = geometry.background_projections() {
+            geometry
+                .check_num_rays(projections.len())
+                .map_err(|e| anyhow::anyhow!("{:?}: {}", args.geometry, e))?;
+            subtract_roi_background(&mut projections, &background);
+        }
+    }
+
+    // --- Limited-view subset of the angles ---
+    let geometry = if args.use_angles.is_none() && args.use_angle_indices.is_none() {
+        geometry
+    } else {
+        let Some(geometry) = geometry else {
+            bail!(
+                "--use-angles and --use-angle-indices need angle/detector metadata in {:?}",
+                args.geometry
+            );
+        };
+        geometry
+            .check_num_rays(projections.len())
+            .map_err(|e| anyhow::anyhow!("{:?}: {}", args.geometry, e))?;
+        let angles = match (&args.use_angle_indices, &args.use_angles) {
+            (Some(indices), _) => indices.clone(),
+            (None, Some(range)) => range.indices(geometry.num_angles)?,
+            (None, None) => unreachable!(),
+        };
+        let subset = geometry
+            .select_angles(&angles)
+            .map_err(|e| anyhow::anyhow!("Angle selection: {}", e))?;
+        let rows = geometry.angle_rows(&angles);
+        projections = projections.select(Axis(0), &rows);
+        system_matrix = system_matrix.select_rows(&rows);
+        info!(
+            "Using {} of {} angles ({} rays)",
+            subset.num_angles,
+            geometry.num_angles,
+            rows.len()
+        );
+        Some(subset)
+    };
+    // only a matrix built from the geometry takes its shapes from it
+    let built_from = geometry.filter(|_| built);
+
+    if !args.skip_validation {
+        match &system_matrix {
+            SystemMatrix::Dense(a) => validate_system_matrix(a),
+            SystemMatrix::Sparse(a) => validate_system_matrix_sparse(a),
+        }
+        .or_else(|e| match e {
+            // --sanitize deals with NaN/Inf below
+            ReconError::InvalidSystemMatrix(mut issues) if args.sanitize => {
+                issues.non_finite.clear();
+                if issues.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ReconError::InvalidSystemMatrix(issues))
+                }
+            }
+            e => Err(e),
+        })
+        .map_err(|e| anyhow::anyhow!("{} (pass --skip-validation to run anyway)", e))?;
+    }
+
+    let geometry_shape = built_from.as_ref().map(Geometry::reconstruction_shape);
+    if let (Some(shape), Some(grid)) = (&args.volume_shape, geometry_shape) {
+        // a transposed WxH has the same voxel count but would silently swap
+        // the axes of every reshaped output
+        if shape.grid_2d().ok() != Some(grid) {
+            bail!(
+                "--volume-shape {} does not match the geometry's grid {} (WxH, from volume_shape \
+                [rows, cols] = {:?})",
+                shape,
+                VolumeShape::from_grid(grid),
+                grid
+            );
+        }
+    }
+    let volume_shape = args
+        .volume_shape
+        .clone()
+        .or(geometry_shape.map(VolumeShape::from_grid));
+    if let Some(shape) = &volume_shape {
+        if shape.num_voxels() != system_matrix.dim().1 {
+            bail!(
+                "Volume shape {} has {} voxels but the system matrix has {} columns",
+                shape,
+                shape.num_voxels(),
+                system_matrix.dim().1
+            );
+        }
+    }
+
+    if projections.len() != system_matrix.dim().0 {
+        bail!(
+            "Projections have length {} but the system matrix has {} rows",
+            projections.len(),
+            system_matrix.dim().0
+        );
+    }
+
+    // --- Check for NaN/Inf (detector dropouts) ---
+    let sanitized = match &mut system_matrix {
+        SystemMatrix::Dense(a) => sanitize_inputs(&mut projections, a),
+        SystemMatrix::Sparse(a) => sanitize_inputs_sparse(&mut projections, a),
+    };
+    if !sanitized.is_clean() {
+        if !args.sanitize {
+            bail!(
+                "{} projection(s) and {} system matrix entries are NaN or infinite (first \
+                affected ray {}); pass --sanitize to zero them and skip the affected rays",
+                sanitized.projections,
+                sanitized.matrix_entries,
+                sanitized.disabled_rays[0]
+            );
+        }
+        warn!(
+            "Sanitized {} non-finite projection(s) and {} non-finite matrix entries; disabled {} \
+            of {} rays",
+            sanitized.projections,
+            sanitized.matrix_entries,
+            sanitized.disabled_rays.len(),
+            projections.len()
+        );
+    }
+    Ok(Problem {
+        projections,
+        system_matrix,
+        built_from,
+        volume_shape,
+    })
+}
+
+/// Check the solver flags and their combinations with the algorithm and the
+/// loaded problem.
+fn check_solver_flags<T>(args: &Args, problem: &Problem<T>) -> Result<()> {
+    if let (Some(lo), Some(hi)) = (args.min_val, args.max_val) {
+        if lo > hi {
+            bail!("--min-val ({}) must not exceed --max-val ({})", lo, hi);
+        }
+    }
+    if args.tv_every == 0 {
+        bail!("--tv-every must be at least 1");
+    }
+    match args.threads {
+        Some(0) => bail!("--threads must be at least 1"),
+        Some(n) if n > 1 && !cfg!(feature = "rayon") => {
+            warn!(
+                "Built without the rayon feature; --threads {} runs on one thread",
+                n
+            )
+        }
+        _ => {}
+    }
+    if args.n_subsets == 0 {
+        bail!("--n-subsets must be at least 1");
+    }
+    if args.n_subsets > 1 && args.algorithm != Algorithm::Mart {
+        bail!(
+            "--n-subsets only applies to MART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if args.os_parallel && args.n_subsets < 2 {
+        bail!("--os-parallel needs --n-subsets of at least 2");
+    }
+    if args.blocks == 0 {
+        bail!("--blocks must be at least 1");
+    }
+    if args.blocks > 1 {
+        if args.algorithm != Algorithm::Mart || args.n_subsets > 1 || args.auto_relax {
+            bail!("--blocks only applies to plain MART (no --n-subsets or --auto-relax)");
+        }
+        if matches!(problem.system_matrix, SystemMatrix::Dense(_)) {
+            bail!("--blocks needs a sparse system matrix (CSR .npz or --geometry)");
+        }
+    }
+    if args.weighted && args.algorithm != Algorithm::Mart {
+        bail!(
+            "--weighted only applies to MART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if args.weights.is_some() && !args.algorithm.is_multiplicative() {
+        bail!(
+            "--weights only applies to MART and SMART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if args.voxel_weights.is_some() && !args.algorithm.is_multiplicative() {
+        bail!(
+            "--voxel-weights only applies to MART and SMART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if args.relax_schedule.is_some() && args.algorithm == Algorithm::Mlem {
+        bail!("--relax-schedule does not apply to MLEM, which has no relaxation");
+    }
+    if args.auto_relax {
+        if args.algorithm != Algorithm::Mart || args.n_subsets > 1 {
+            bail!("--auto-relax only applies to plain MART (--n-subsets 1)");
+        }
+        if args.auto_relax_every == 0 {
+            bail!("--auto-relax-every must be at least 1");
+        }
+        if args
+            .auto_relax_candidates
+            .iter()
+            .any(|&c| !(c.is_finite() && c > 0.0))
+        {
+            bail!(
+                "--auto-relax-candidates must be positive, got {:?}",
+                args.auto_relax_candidates
+            );
+        }
+    }
+    if (args.floor_eps != 0.0 || args.ratio_clamp.is_some()) && !args.algorithm.is_multiplicative()
+    {
+        bail!(
+            "--floor-eps and --ratio-clamp only apply to MART and SMART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if !(args.skip_eps >= 0.0 && args.skip_eps.is_finite()) {
+        bail!(
+            "--skip-eps must be a non-negative number, got {}",
+            args.skip_eps
+        );
+    }
+    if args.skip_eps != 0.0 && args.algorithm != Algorithm::Mart {
+        bail!(
+            "--skip-eps only applies to MART, not {}",
+            args.algorithm.label()
+        );
+    }
+    if args.quantize.is_some() && args.output_format != OutputFormat::Npy {
+        bail!("--quantize only applies to --output-format npy");
+    }
+    if args.output_dtype.is_some() && args.output_format != OutputFormat::Npy {
+        bail!("--output-dtype only applies to --output-format npy");
+    }
+    if !(args.smooth_sigma >= 0.0 && args.smooth_sigma.is_finite()) {
+        bail!(
+            "--smooth-sigma must be a non-negative number, got {}",
+            args.smooth_sigma
+        );
+    }
+    if args.smooth_sigma > 0.0 && problem.volume_shape.is_none() {
+        bail!("--smooth-sigma needs --volume-shape (or a matrix built from --geometry)");
+    }
+
+    if args.checkpoint_every == Some(0) {
+        bail!("--checkpoint-every must be at least 1");
+    }
+    if args.checkpoint_path.is_some() && args.checkpoint_every.is_none() {
+        bail!("--checkpoint-path needs --checkpoint-every");
+    }
+
+    if args.snapshot_every == Some(0) {
+        bail!("--snapshot-every must be at least 1");
+    }
+    Ok(())
+}
+
+/// Where a run starts: from scratch, a --resume checkpoint or the --continue
+/// output.
+struct Resume {
+    /// Starting volume and the iterations already run
+    start: Option<(Array1<f64>, usize)>,
+    /// Hash of the --continue output, an input of the one replacing it
+    previous_output: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Resume {
+    /// Read the --resume checkpoint or the --continue `output` for a system
+    /// matrix with `n` columns.
+    fn load(args: &Args, output: &Path, n: usize) -> Result<Self> {
+        // hashed before it is overwritten, for the provenance of the new output
+        let mut previous_output = serde_json::Map::new();
+        let start = match &args.resume {
+            Some(path) => {
+                let (volume, iteration) = read_checkpoint(path)?;
+                if volume.len() != n {
+                    bail!(
+                        "Checkpoint volume has length {} but the system matrix has {} columns",
+                        volume.len(),
+                        n
+                    );
+                }
+                info!(
+                    "Resuming from {:?} after iteration {} of {}",
+                    path, iteration, args.n_iters
+                );
+                Some((volume, iteration))
+            }
+            None if args.continue_run => {
+                if args.output_format != OutputFormat::Npy {
+                    bail!(
+                        "--continue reads the previous output back, which needs --output-format \
+                        npy"
+                    );
+                }
+                let (volume, iteration) = read_previous_output(output, args.algorithm)?;
+                previous_output = hash_inputs(&[("previous_output", output)])?;
+                if volume.len() != n {
+                    bail!(
+                        "Previous output volume has length {} but the system matrix has {} columns",
+                        volume.len(),
+                        n
+                    );
+                }
+                // the column sums and weights are rebuilt from the matrix as in
+                // any run; only the volume and the count carry over
+                info!(
+                    "Continuing {:?} from iteration {} for {} more",
+                    output, iteration, args.n_iters
+                );
+                Some((volume, iteration))
+            }
+            None => None,
+        };
+        Ok(Self {
+            start,
+            previous_output,
+        })
+    }
+
+    fn start_iteration(&self) -> usize {
+        self.start.as_ref().map_or(0, |&(_, iteration)| iteration)
+    }
+}
+
+/// Map the reconstruction flags to a `MartBuilder`, reading the masks, weights
+/// and initial volume they name. Every MART variant runs through the builder;
+/// the other solvers take its options.
+fn mart_builder<T: CliFloat>(
+    args: &Args,
+    problem: &Problem<T>,
+    resume: &Resume,
+    stop: &Arc<AtomicBool>,
+) -> Result<MartBuilder> {
+    let Problem {
+        system_matrix,
+        volume_shape,
+        ..
+    } = problem;
+    let read_voxel_mask = |path: &Path, what: &str| -> Result<Array1<bool>> {
+        let mask = read_mask(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {} {:?}: {}", what, path, e))?;
+        if mask.len() != system_matrix.dim().1 {
+            bail!(
+                "{} {:?} has {} elements but the system matrix has {} columns",
+                what,
+                path,
+                mask.len(),
+                system_matrix.dim().1
+            );
+        }
+        info!(
+            "{} keeps {} of {} voxels",
+            what,
+            mask.iter().filter(|&&keep| keep).count(),
+            mask.len()
+        );
+        Ok(mask)
+    };
+    let mask = args
+        .mask
+        .as_deref()
+        .map(|path| read_voxel_mask(path, "Mask"))
+        .transpose()?;
+    let support = args
+        .support_mask
+        .as_deref()
+        .map(|path| read_voxel_mask(path, "Support mask"))
+        .transpose()?;
+    let mask = match (mask, support) {
+        (Some(mask), Some(support)) => {
+            let both = Zip::from(&mask)
+                .and(&support)
+                .map_collect(|&keep, &inside| keep && inside);
+            let kept = both.iter().filter(|&&keep| keep).count();
+            info!(
+                "Reconstructing the {} voxels inside both the mask and the support",
+                kept
+            );
+            Some(both)
+        }
+        (mask, support) => mask.or(support),
+    };
+
+    let ray_weights = match &args.weights {
+        Some(path) => {
+            let weights: Array1<f64> = read_float_npy(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read ray weights {:?}: {}", path, e))?;
+            if weights.len() != system_matrix.dim().0 {
+                bail!(
+                    "Ray weights have length {} but the system matrix has {} rows",
+                    weights.len(),
+                    system_matrix.dim().0
+                );
+            }
+            if let Some((i, w)) = weights
+                .iter()
+                .enumerate()
+                .find(|(_, w)| !(w.is_finite() && **w >= 0.0))
+            {
+                bail!(
+                    "Ray weight {} is {}; weights must be finite and non-negative",
+                    i,
+                    w
+                );
+            }
+            info!(
+                "Ray weights drop {} of {} rays",
+                weights.iter().filter(|&&w| w == 0.0).count(),
+                weights.len()
+            );
+            Some(weights)
+        }
+        None => None,
+    };
+
+    let voxel_weights = match &args.voxel_weights {
+        Some(path) => {
+            let weights: ArrayD<f64> = read_float_npy(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read voxel weights {:?}: {}", path, e))?;
+            let weights: Array1<f64> = weights.iter().copied().collect();
+            if weights.len() != system_matrix.dim().1 {
+                bail!(
+                    "Voxel weights have {} elements but the system matrix has {} columns",
+                    weights.len(),
+                    system_matrix.dim().1
+                );
+            }
+            if let Some((j, w)) = weights
+                .iter()
+                .enumerate()
+                .find(|(_, w)| !(w.is_finite() && **w >= 0.0))
+            {
+                bail!(
+                    "Voxel weight {} is {}; weights must be finite and non-negative",
+                    j,
+                    w
+                );
+            }
+            Some(weights)
+        }
+        None => None,
+    };
+
+    let l2_regularization = match args.reg_l2 {
+        Some(weight) => Some(L2Regularization {
+            weight,
+            volume_shape: require_grid(volume_shape.as_ref(), "--reg-l2")?,
+        }),
+        None => None,
+    };
+    let tv_regularization = match args.tv_weight {
+        Some(weight) => Some(TvRegularization {
+            weight,
+            every: args.tv_every,
+            volume_shape: require_grid(volume_shape.as_ref(), "--tv-weight")?,
+        }),
+        None => None,
+    };
+    let huber_regularization = match args.huber {
+        Some((delta, weight)) => Some(HuberRegularization {
+            delta,
+            weight,
+            volume_shape: require_stack(volume_shape.as_ref(), "--huber")?,
+        }),
+        None => None,
+    };
+    let initial_guess = match (&resume.start, &args.init, &args.init_from) {
+        (Some((volume, _)), _, _) => InitialGuess::FromArray(volume.clone()),
+        (None, _, Some(path)) => {
+            let coarse_shape = args
+                .init_from_shape
+                .as_ref()
+                .expect("clap requires --init-from-shape");
+            InitialGuess::FromArray(upsampled_init(path, coarse_shape, volume_shape.as_ref())?)
+        }
+        (None, InitSpec::Uniform(value), None) => InitialGuess::Uniform(*value),
+        (None, InitSpec::Backprojection, None) => InitialGuess::Backprojection,
+        (None, InitSpec::Npy(path), None) => {
+            // any shape, e.g. a reshaped output of an earlier run
+            let volume: ArrayD<f64> = read_float_npy(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read initial volume {:?}: {}", path, e))?;
+            let volume: Array1<f64> = volume.iter().copied().collect();
+            if volume.len() != system_matrix.dim().1 {
+                bail!(
+                    "Initial volume has length {} but the system matrix has {} columns",
+                    volume.len(),
+                    system_matrix.dim().1
+                );
+            }
+            InitialGuess::FromArray(volume)
+        }
+    };
+    Ok(MartBuilder::new()
+        .nonnegative(args.nonneg)
+        .optional(args.tol, MartBuilder::tolerance)
+        .optional(args.tol_x, MartBuilder::volume_tolerance)
+        .row_order(match args.shuffle_seed {
+            Some(seed) => RowOrder::Shuffled { seed },
+            None => RowOrder::Sequential,
+        })
+        .optional(l2_regularization, MartBuilder::l2_regularization)
+        .optional(tv_regularization, MartBuilder::tv_regularization)
+        .optional(huber_regularization, MartBuilder::huber_regularization)
+        .initial_guess(initial_guess)
+        .optional(
+            (args.min_val.is_some() || args.max_val.is_some()).then(|| {
+                (
+                    args.min_val.unwrap_or(f64::NEG_INFINITY),
+                    args.max_val.unwrap_or(f64::INFINITY),
+                )
+            }),
+            |mart, (lo, hi)| mart.bounds(lo, hi),
+        )
+        .column_weighted(args.weighted)
+        .optional(args.relax_schedule, MartBuilder::relaxation_schedule)
+        .start_iteration(resume.start_iteration())
+        .optional(mask, MartBuilder::mask)
+        .optional(
+            (args.patience > 0).then_some(args.patience),
+            MartBuilder::patience,
+        )
+        .floor_eps(args.floor_eps)
+        .optional(args.ratio_clamp, |mart, (lo, hi)| mart.ratio_clamp(lo, hi))
+        .skip_eps(args.skip_eps)
+        .optional(args.threads, MartBuilder::threads)
+        .deterministic(args.deterministic)
+        .parallel_subsets(args.os_parallel)
+        .stop(Arc::clone(stop))
+        .optional(ray_weights, MartBuilder::ray_weights)
+        .optional(voxel_weights, MartBuilder::voxel_weights)
+        .optional(
+            args.auto_relax.then(|| AutoRelaxation {
+                candidates: args.auto_relax_candidates.clone(),
+                every: args.auto_relax_every,
+                seed: args.auto_relax_seed,
+                ..AutoRelaxation::default()
+            }),
+            MartBuilder::auto_relaxation,
+        )
+        .subsets(args.n_subsets)
+        .blocks(args.blocks))
+}
+
+/// The --relaxation to run with: the given value, checked against the
+/// convergence bound of the algorithm, or the estimated safe one for `auto`.
+fn choose_relaxation<T: ReconFloat>(
+    args: &Args,
+    system_matrix: &SystemMatrix<T>,
+    options: &ReconOptions,
+    total_iters: usize,
+    timing: &mut Timing,
+) -> Result<f64> {
+    let base_relaxation = match args.relaxation {
+        Relaxation::Value(value) => value,
+        Relaxation::Auto => {
+            if !matches!(
+                args.algorithm,
+                Algorithm::Sirt | Algorithm::Sart | Algorithm::Landweber
+            ) {
+                bail!(
+                    "--relaxation auto only applies to SIRT, SART and Landweber, not {} (see \
+                    --auto-relax for MART)",
+                    args.algorithm.label()
+                );
+            }
+            if args.relax_schedule.is_some() {
+                bail!("--relaxation auto conflicts with --relax-schedule");
+            }
+            let spectral_radius =
+                spectral_radius(args.algorithm, system_matrix, options.mask.as_ref());
+            let relaxation = safe_relaxation(spectral_radius);
+            info!(
+                "Estimated spectral radius {:.6} of the {} iteration; using relaxation {:.6} \
+                (bound {:.6})",
+                spectral_radius,
+                args.algorithm.label(),
+                relaxation,
+                2.0 / spectral_radius
+            );
+            timing.phase("relaxation");
+            relaxation
+        }
+    };
+    // the auto relaxation is safe by construction; otherwise check the
+    // largest value any pass (or --auto-relax candidate) will use
+    if args.relaxation != Relaxation::Auto && args.algorithm != Algorithm::Mlem {
+        let mut largest = match &options.relaxation_schedule {
+            Some(schedule) => (0..total_iters)
+                .map(|iter| schedule.at(iter, total_iters))
+                .fold(f64::MIN, f64::max),
+            None => base_relaxation,
+        };
+        if let Some(auto) = &options.auto_relaxation {
+            largest = auto.candidates.iter().copied().fold(largest, f64::max);
+        }
+        let bound = relaxation_bound(args.algorithm, system_matrix, options.mask.as_ref());
+        check_relaxation(args.algorithm, largest, bound, args.allow_unsafe_relax)?;
+    }
+    Ok(base_relaxation)
+}
+
+/// Run `args.algorithm` on `problem`; MART in all its variants through `mart`.
+fn solve<T: CliFloat>(
+    args: &Args,
+    problem: &Problem<T>,
+    mart: &MartBuilder,
+    n_iters: usize,
+    base_relaxation: f64,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>> {
+    let options = mart.options();
+    let relaxation = T::from(base_relaxation).unwrap();
+    Ok(match (&problem.system_matrix, args.algorithm) {
+        (SystemMatrix::Dense(a), Algorithm::Mart) => {
+            if args.n_subsets > 1 {
+                info!("Using ordered-subset MART with {} subsets", args.n_subsets);
+            }
+            mart.run_with_callback(&problem.projections, a, callback)?
+        }
+        (SystemMatrix::Dense(a), Algorithm::Art) => art_reconstruct_with_callback(
+            &problem.projections,
+            a,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        )?,
+        (SystemMatrix::Dense(a), Algorithm::Sirt) => sirt_reconstruct_with_callback(
+            &problem.projections,
+            a,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        )?,
+        (SystemMatrix::Dense(a), Algorithm::Landweber) => landweber_reconstruct_with_callback(
+            &problem.projections,
+            a,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        )?,
+        (SystemMatrix::Dense(a), Algorithm::Sart) => sart_reconstruct_with_callback(
+            &problem.projections,
+            a,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        )?,
+        (SystemMatrix::Dense(a), Algorithm::Mlem) => {
+            mlem_reconstruct_with_callback(&problem.projections, a, n_iters, options, callback)?
+        }
+        (SystemMatrix::Dense(a), Algorithm::Smart) => smart_reconstruct_with_callback(
+            &problem.projections,
+            a,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        )?,
+        (SystemMatrix::Sparse(a), Algorithm::Mart) => {
+            if args.blocks > 1 {
+                info!(
+                    "Using sparse CSR system matrix ({} nonzeros) in {} voxel blocks",
+                    a.nnz(),
+                    args.blocks
+                );
+            } else {
+                info!("Using sparse CSR system matrix ({} nonzeros)", a.nnz());
+            }
+            mart.run_with_callback(&problem.projections, a, callback)?
+        }
+        (SystemMatrix::Sparse(a), Algorithm::Sirt) => {
+            info!("Using sparse CSR system matrix ({} nonzeros)", a.nnz());
+            sirt_reconstruct_with_callback(
+                &problem.projections,
+                a,
+                n_iters,
+                relaxation,
+                options,
+                callback,
+            )?
+        }
+        (SystemMatrix::Sparse(a), Algorithm::Sart) => {
+            info!("Using sparse CSR system matrix ({} nonzeros)", a.nnz());
+            sart_reconstruct_with_callback(
+                &problem.projections,
+                a,
+                n_iters,
+                relaxation,
+                options,
+                callback,
+            )?
+        }
+        (SystemMatrix::Sparse(a), Algorithm::Landweber) => {
+            info!("Using sparse CSR system matrix ({} nonzeros)", a.nnz());
+            landweber_reconstruct_with_callback(
+                &problem.projections,
+                a,
+                n_iters,
+                relaxation,
+                options,
+                callback,
+            )?
+        }
+        (SystemMatrix::Sparse(_), algorithm) => {
+            bail!(
+                "Sparse system matrices currently only support MART, SIRT, SART and Landweber, \
+                not {}",
+                algorithm.label()
+            )
+        }
+    })
+}
+
+/// Smooth, scale and write the reconstructed volume to `output`, with its
+/// sidecar and the optional --residual-output.
+fn write_outputs<T: CliFloat>(
+    args: &Args,
+    output: &Path,
+    problem: &Problem<T>,
+    report: ReconReport<T>,
+    resume: Resume,
+    mask: Option<&Array1<bool>>,
+    timing: &mut Timing,
+) -> Result<()> {
+    let Problem {
+        projections,
+        system_matrix,
+        built_from,
+        volume_shape,
+    } = problem;
+    let start_iteration = resume.start_iteration();
+    let volume = match &volume_shape {
+        Some(shape) if args.smooth_sigma > 0.0 => {
+            info!(
+                "Smoothing the volume with a Gaussian (sigma = {} voxels)",
+                args.smooth_sigma
+            );
+            let smoothed = gaussian_blur(&report.volume, &shape.array_shape(), args.smooth_sigma);
+            timing.phase("smoothing");
+            smoothed
+        }
+        _ => report.volume,
+    };
+    let quality = if args.report_quality {
+        let residual = system_matrix
+            .relative_residual(projections, &volume)
+            .to_f64()
+            .unwrap();
+        // the solver's clamps: --nonneg raises the lower bound to 0
+        let lower = args
+            .nonneg
+            .then_some(0.0)
+            .into_iter()
+            .chain(args.min_val)
+            .reduce(f64::max);
+        let bounds = match (lower, args.max_val) {
+            (None, None) => None,
+            (lo, hi) => Some((lo.unwrap_or(f64::NEG_INFINITY), hi.unwrap_or(f64::INFINITY))),
+        };
+        Some(report_quality(
+            residual,
+            &volume,
+            volume_shape.as_ref(),
+            bounds,
+            mask,
+        )?)
+    } else {
+        None
+    };
+
+    // --- Save volume ---
+    // every output format writes `written`; `volume` stays in solver units
+    // for the residual below
+    let units = (args.voxel_size.is_some() || args.unit_scale != 1.0).then(|| PhysicalUnits {
+        voxel_size_mm: args.voxel_size,
+        pixel_size: built_from
+            .as_ref()
+            .map_or(1.0, |geometry| geometry.pixel_size),
+        unit_scale: args.unit_scale,
+    });
+    let scaled;
+    let written = match &units {
+        Some(units) => {
+            info!(
+                "Scaling the volume by {:e} to {} units",
+                units.factor(),
+                units.units()
+            );
+            let factor = T::from(units.factor()).unwrap();
+            scaled = volume.mapv(|v| v * factor);
+            &scaled
+        }
+        None => &volume,
+    };
+    let output_dtype = args.output_dtype.unwrap_or(T::DTYPE);
+    let quantization = args
+        .quantize
+        .map(|Quantize::U16| match args.intensity_range {
+            Some((lo, hi)) => Quantization::from_range(lo, hi),
+            None => Quantization::from_volume(written),
+        });
+    match args.output_format {
+        OutputFormat::Npy => {
+            let shape = volume_shape
+                .as_ref()
+                .map_or_else(|| vec![volume.len()], VolumeShape::array_shape);
+            match &quantization {
+                Some(quantization) => {
+                    info!(
+                        "Quantizing to uint16 (step {:e}, offset {})",
+                        quantization.scale, quantization.offset
+                    );
+                    write_npy(
+                        output,
+                        &quantization.quantize(written).into_shape(IxDyn(&shape))?,
+                    )
+                    .map_err(anyhow::Error::from)
+                }
+                None => write_volume_npy(output, written, &shape, output_dtype),
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to write output NPY {:?}: {}", output, e))?
+        }
+        OutputFormat::Tiff => {
+            let Some(shape) = &volume_shape else {
+                bail!(
+                    "--output-format tiff needs --volume-shape (or a matrix built from --geometry)"
+                );
+            };
+            write_tiff_stack(output, written, shape.stack_3d()?, args.raw_intensity)
+                .map_err(|e| anyhow::anyhow!("Failed to write output TIFF {:?}: {}", output, e))?
+        }
+    }
+
+    println!("Reconstruction written to {:?}", output);
+
+    let provenance = if args.no_metadata {
+        None
+    } else {
+        let mut inputs = hash_inputs(&args.input_files())?;
+        inputs.extend(resume.previous_output);
+        let mut record = provenance(
+            inputs,
+            args.algorithm.label(),
+            start_iteration + report.iterations_run,
+            report.final_residual.to_f64().unwrap(),
+        );
+        record["converged"] = json!(report.converged);
+        record["stopped_by"] = json!(report.stopped_by.map(|criterion| match criterion {
+            StopCriterion::Residual => "residual",
+            StopCriterion::VolumeChange => "volume_change",
+        }));
+        record["interrupted"] = json!(report.interrupted);
+        if args.continue_run {
+            record["continued_from_iteration"] = json!(start_iteration);
+        }
+        if let Some(quality) = quality {
+            record["quality"] = quality;
+        }
+        Some(record)
+    };
+    if volume_shape.is_some() || quantization.is_some() || units.is_some() || provenance.is_some() {
+        let sidecar = sidecar_path(output);
+        let (quantization, units) = (quantization.as_ref(), units.as_ref());
+        write_sidecar(
+            &sidecar,
+            volume_shape.as_ref(),
+            output_dtype,
+            quantization,
+            units,
+            provenance.as_ref(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to write volume metadata {:?}: {}", sidecar, e))?;
+        println!("Volume metadata written to {:?}", sidecar);
+    }
+
+    if let Some(path) = &args.residual_output {
+        let residual = system_matrix.residual(projections, &volume);
+        let ray_shape = built_from.as_ref().map(|g| [g.num_angles, g.num_detectors]);
+        write_residual(path, &residual, ray_shape)
+            .map_err(|e| anyhow::anyhow!("Failed to write residual {:?}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Handle Ctrl-C during the solve: the first one sets `stop`, so the solver
+/// finishes its current iteration and the volume so far is written as
+/// usual; a second one aborts immediately.
+fn install_interrupt_handler(stop: Arc<AtomicBool>) -> Result<()> {
+    ctrlc::set_handler(move || {
+        if stop.swap(true, Ordering::SeqCst) {
+            error!("Interrupted again; aborting without writing the volume");
+            std::process::exit(130);
+        }
+        warn!("Interrupted; stopping after the current iteration (press Ctrl-C again to abort)");
+    })
+    .map_err(|e| anyhow::anyhow!("Failed to install the Ctrl-C handler: {}", e))
+}
+
+/// Save the per-ray residual, as a sinogram `[angles, detectors]` when
+/// `ray_shape` is known, and report the worst-fitting ray.
+fn write_residual<T: CliFloat>(
+    path: &Path,
+    residual: &Array1<T>,
+    ray_shape: Option<[usize; 2]>,
+) -> Result<()> {
+    match ray_shape {
+        Some(shape) => write_npy(path, &residual.clone().into_shape(shape)?)?,
+        None => write_npy(path, residual)?,
+    }
+    let worst = residual
+        .iter()
+        .enumerate()
+        .fold((0, T::zero()), |(i, max), (k, &r)| {
+            if r.abs() > max {
+                (k, r.abs())
+            } else {
+                (i, max)
+            }
+        });
+    match ray_shape {
+        Some([_, detectors]) => println!(
+            "Residual A*x - y written to {:?} (largest |residual| {:?} at angle {}, detector {})",
+            path,
+            worst.1,
+            worst.0 / detectors,
+            worst.0 % detectors
+        ),
+        None => println!(
+            "Residual A*x - y written to {:?} (largest |residual| {:?} at ray {})",
+            path, worst.1, worst.0
+        ),
+    }
+    Ok(())
+}
+
+/// Fail if `path` exists and --no-clobber is given without --force.
+fn check_clobber(path: &Path, no_clobber: bool, force: bool) -> Result<()> {
+    if no_clobber && !force && path.exists() {
+        bail!(
+            "{:?} already exists; pass --force to overwrite it despite --no-clobber",
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Write `volume` as a `dtype` NPY of shape `shape`.
+fn write_volume_npy<T: CliFloat>(
+    path: &Path,
+    volume: &Array1<T>,
+    shape: &[usize],
+    dtype: Dtype,
+) -> Result<()> {
+    match dtype {
+        Dtype::F32 => write_npy(
+            path,
+            &volume
+                .mapv(|v| v.to_f32().unwrap())
+                .into_shape(IxDyn(shape))?,
+        ),
+        Dtype::F64 => write_npy(
+            path,
+            &volume
+                .mapv(|v| v.to_f64().unwrap())
+                .into_shape(IxDyn(shape))?,
+        ),
+    }?;
+    Ok(())
+}
+
+/// Conversion of the reconstructed volume to physical units.
+///
+/// The system matrix holds path lengths in the geometry's length unit, in
+/// which one voxel is `pixel_size` long (1 for a matrix not built from
+/// --geometry, i.e. lengths in voxels). The solvers therefore return
+/// attenuation per length unit `x`, and with a voxel edge of
+/// `voxel_size_mm` the written volume is
+///
+///   mu [cm^-1] = unit_scale * x * pixel_size * 10 / voxel_size_mm
+///
+/// Without --voxel-size only `unit_scale` is applied and the units stay
+/// arbitrary.
+struct PhysicalUnits {
+    voxel_size_mm: Option<f64>,
+    pixel_size: f64,
+    unit_scale: f64,
+}
+
+impl PhysicalUnits {
+    fn factor(&self) -> f64 {
+        match self.voxel_size_mm {
+            Some(voxel_size_mm) => self.unit_scale * self.pixel_size * 10.0 / voxel_size_mm,
+            None => self.unit_scale,
+        }
+    }
+
+    fn units(&self) -> &'static str {
+        if self.voxel_size_mm.is_some() {
+            "cm^-1"
+        } else {
+            "arbitrary"
+        }
+    }
+}
+
+impl Args {
+    /// Every input file the reconstruction reads, by role, for the
+    /// provenance hashes.
+    fn input_files(&self) -> Vec<(&'static str, &Path)> {
+        let mut inputs = vec![
+            ("projections", self.projections.as_path()),
+            ("geometry", self.geometry.as_path()),
+        ];
+        let optional = [
+            ("system_matrix", self.system_matrix.as_deref()),
+            ("flat", self.flat.as_deref()),
+            ("dark", self.dark.as_deref()),
+            ("weights", self.weights.as_deref()),
+            ("voxel_weights", self.voxel_weights.as_deref()),
+            ("init_from", self.init_from.as_deref()),
+            ("mask", self.mask.as_deref()),
+            ("support_mask", self.support_mask.as_deref()),
+            ("resume", self.resume.as_deref()),
+        ];
+        inputs.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, path)| Some((name, path?))),
+        );
+        if let Some(FlatField::Npy(path)) = &self.i0 {
+            inputs.push(("i0", path));
+        }
+        if let InitSpec::Npy(path) = &self.init {
+            inputs.push(("init", path));
+        }
+        inputs
+    }
+}
+
+/// xxh3-64 of the bytes of `path`, as 16 hex digits.
+fn file_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(format!("{:016x}", hasher.digest())),
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+}
+
+/// `{"<role>": {"path": ..., "xxh3_64": ...}}` for the provenance record.
+fn hash_inputs(inputs: &[(&str, &Path)]) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut hashes = serde_json::Map::new();
+    for &(name, path) in inputs {
+        let hash = file_hash(path)
+            .map_err(|e| anyhow::anyhow!("Failed to hash {} {:?}: {}", name, path, e))?;
+        hashes.insert(
+            name.to_string(),
+            json!({ "path": path.display().to_string(), "xxh3_64": hash }),
+        );
+    }
+    Ok(hashes)
+}
+
+/// Provenance block of the sidecar JSON: enough to tell which build, which
+/// command and which exact inputs produced an output, and how the solve
+/// ended.
+fn provenance(
+    inputs: serde_json::Map<String, serde_json::Value>,
+    algorithm: &str,
+    iterations_run: usize,
+    final_residual: f64,
+) -> serde_json::Value {
+    json!({
+        "crate": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "command_line": std::env::args().collect::<Vec<_>>(),
+        "inputs": inputs,
+        "algorithm": algorithm,
+        "iterations_run": iterations_run,
+        "final_residual": final_residual,
+        "timestamp": format!("{:.0}", jiff::Timestamp::now()),
+    })
+}
+
+/// Sidecar of `output`: `<output>.meta.json`, e.g. `volume.npy.meta.json`.
+fn sidecar_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".meta.json");
+    PathBuf::from(path)
+}
+
+/// Record the volume layout next to the output (see `sidecar_path`):
+/// `{"volume_shape": "64x64x16", "shape": [16, 64, 64], "axes": ["z", "y", "x"],
+/// "order": "C", "dtype": "float32"}`. A quantized volume stores `"dtype": "uint16"`
+/// plus `"quantization": {"scale", "offset", "dtype"}`, where values are
+/// recovered as `offset + scale * code` in the reconstruction dtype. Scaled
+/// output adds `"units"`, `"unit_scale"`, the applied `"unit_factor"` and
+/// `"voxel_size_mm"` (see `PhysicalUnits`), and unless --no-metadata the
+/// record from `provenance` is stored under `"provenance"`.
+fn write_sidecar(
+    path: &Path,
+    shape: Option<&VolumeShape>,
+    dtype: Dtype,
+    quantization: Option<&Quantization>,
+    units: Option<&PhysicalUnits>,
+    provenance: Option<&serde_json::Value>,
+) -> Result<()> {
+    let mut meta = json!({ "order": "C", "dtype": dtype.numpy_name() });
+    if let Some(shape) = shape {
+        meta["volume_shape"] = json!(shape.to_string());
+        meta["shape"] = json!(shape.array_shape());
+        meta["axes"] = json!(shape.axes());
+    }
+    if let Some(quantization) = quantization {
+        meta["dtype"] = json!("uint16");
+        meta["quantization"] = json!({
+            "scale": quantization.scale,
+            "offset": quantization.offset,
+            "dtype": dtype.numpy_name(),
+        });
+    }
+    if let Some(units) = units {
+        meta["units"] = json!(units.units());
+        meta["unit_scale"] = json!(units.unit_scale);
+        meta["unit_factor"] = json!(units.factor());
+        if let Some(voxel_size_mm) = units.voxel_size_mm {
+            meta["voxel_size_mm"] = json!(voxel_size_mm);
+        }
+    }
+    if let Some(provenance) = provenance {
+        meta["provenance"] = provenance.clone();
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&meta)? + "\n")?;
+    Ok(())
+}
@@ -0,0 +1,372 @@
+//! Fluent configuration of a MART reconstruction.
+//!
+//! `MartBuilder` collects the iteration count, the relaxation and the
+//! `ReconOptions` behind named setters and runs the solver on a dense or
+//! sparse system matrix, e.g.
+//! `MartBuilder::new().iterations(100).relaxation(0.5).bounds(0.0, 10.0).run(&y, &a)`.
+//!
+//! The builder is a thin layer over the free functions
+//! (`mart_reconstruct_with_callback`, `os_mart_reconstruct_with_callback`
+//! and the sparse plain and blocked solvers), which stay the primary API and
+//! check the inputs; `run` gives exactly their results. `mart_cli` runs
+//! every MART variant through it.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+
+use crate::{
+    mart_reconstruct_sparse_blocked_with_callback, mart_reconstruct_sparse_with_callback,
+    mart_reconstruct_with_callback, os_mart_reconstruct_with_callback, AutoRelaxation,
+    HuberRegularization, InitialGuess, L2Regularization, ReconError, ReconFloat, ReconOptions,
+    ReconReport, RelaxationSchedule, RowOrder, SparseSystemMatrix, TvRegularization,
+};
+
+/// A system matrix MART can run on: the dense and the CSR matrices.
+pub trait MartSystemMatrix<T: ReconFloat> {
+    /// Run the MART variant `mart` selects on `self`.
+    fn mart_reconstruct(
+        &self,
+        projections: &Array1<T>,
+        mart: &MartBuilder,
+        callback: impl FnMut(usize, &Array1<T>, T),
+    ) -> Result<ReconReport<T>, ReconError>;
+}
+
+/// Plain MART, or OS-MART with more than one subset; voxel blocks are
+/// `ReconError::Unsupported`.
+impl<T: ReconFloat, S: Data<Elem = T> + Sync> MartSystemMatrix<T> for ArrayBase<S, Ix2> {
+    fn mart_reconstruct(
+        &self,
+        projections: &Array1<T>,
+        mart: &MartBuilder,
+        callback: impl FnMut(usize, &Array1<T>, T),
+    ) -> Result<ReconReport<T>, ReconError> {
+        if mart.n_blocks > 1 {
+            return Err(ReconError::Unsupported {
+                what: "voxel-blocked MART",
+                matrix: "dense",
+            });
+        }
+        let relaxation = mart.relaxation_as();
+        if mart.n_subsets > 1 {
+            return os_mart_reconstruct_with_callback(
+                projections,
+                self,
+                mart.n_iters,
+                mart.n_subsets,
+                relaxation,
+                &mart.options,
+                callback,
+            );
+        }
+        mart_reconstruct_with_callback(
+            projections,
+            self,
+            mart.n_iters,
+            relaxation,
+            &mart.options,
+            callback,
+        )
+    }
+}
+
+/// Plain MART, or the voxel-blocked solver with more than one block; ordered
+/// subsets are `ReconError::Unsupported`. Divergence is not checked (see
+/// `mart_reconstruct_sparse_with_callback`).
+impl<T: ReconFloat> MartSystemMatrix<T> for SparseSystemMatrix<T> {
+    fn mart_reconstruct(
+        &self,
+        projections: &Array1<T>,
+        mart: &MartBuilder,
+        callback: impl FnMut(usize, &Array1<T>, T),
+    ) -> Result<ReconReport<T>, ReconError> {
+        if mart.n_subsets > 1 {
+            return Err(ReconError::Unsupported {
+                what: "ordered-subset MART",
+                matrix: "sparse",
+            });
+        }
+        let relaxation = mart.relaxation_as();
+        if mart.n_blocks != 1 {
+            return mart_reconstruct_sparse_blocked_with_callback(
+                projections,
+                self,
+                mart.n_iters,
+                relaxation,
+                mart.n_blocks,
+                &mart.options,
+                callback,
+            );
+        }
+        mart_reconstruct_sparse_with_callback(
+            projections,
+            self,
+            mart.n_iters,
+            relaxation,
+            &mart.options,
+            callback,
+        )
+    }
+}
+
+/// Builder for a MART run; see the module docs.
+///
+/// Starts from 50 iterations at relaxation 1.0 with `ReconOptions::default()`,
+/// the defaults of `mart_cli`, on one subset and one voxel block. Every
+/// option has a setter; `configure` edits them in place.
+#[derive(Debug, Clone)]
+pub struct MartBuilder {
+    n_iters: usize,
+    relaxation: f64,
+    n_subsets: usize,
+    n_blocks: usize,
+    options: ReconOptions,
+}
+
+impl Default for MartBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MartBuilder {
+    /// Builder with the defaults above.
+    pub fn new() -> Self {
+        Self {
+            n_iters: 50,
+            relaxation: 1.0,
+            n_subsets: 1,
+            n_blocks: 1,
+            options: ReconOptions::default(),
+        }
+    }
+
+    /// Start from existing `options` instead of the defaults.
+    pub fn with_options(options: ReconOptions) -> Self {
+        Self {
+            options,
+            ..Self::new()
+        }
+    }
+
+    /// Number of iterations (passes over all rays).
+    pub fn iterations(mut self, n_iters: usize) -> Self {
+        self.n_iters = n_iters;
+        self
+    }
+
+    /// Relaxation applied to every pass; see `relaxation_schedule` for a
+    /// varying one.
+    pub fn relaxation(mut self, relaxation: f64) -> Self {
+        self.relaxation = relaxation;
+        self
+    }
+
+    /// Ordered subsets of rays for OS-MART (see
+    /// `os_mart_reconstruct_with_callback`); dense matrices only. 0 and 1
+    /// run plain MART.
+    pub fn subsets(mut self, n_subsets: usize) -> Self {
+        self.n_subsets = n_subsets;
+        self
+    }
+
+    /// Voxel blocks for the blocked solver (see
+    /// `mart_reconstruct_sparse_blocked_with_callback`); sparse matrices
+    /// only, which also reject 0.
+    pub fn blocks(mut self, n_blocks: usize) -> Self {
+        self.n_blocks = n_blocks;
+        self
+    }
+
+    /// `ReconOptions::relaxation_schedule`.
+    pub fn relaxation_schedule(mut self, schedule: RelaxationSchedule) -> Self {
+        self.options.relaxation_schedule = Some(schedule);
+        self
+    }
+
+    /// `ReconOptions::tolerance`: stop on a stalled residual.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.options.tolerance = Some(tolerance);
+        self
+    }
+
+    /// `ReconOptions::volume_tolerance`: stop on a stalled volume.
+    pub fn volume_tolerance(mut self, tolerance: f64) -> Self {
+        self.options.volume_tolerance = Some(tolerance);
+        self
+    }
+
+    /// `ReconOptions::bounds`: clamp every voxel into `[lo, hi]`.
+    pub fn bounds(mut self, lo: f64, hi: f64) -> Self {
+        self.options.bounds = Some((lo, hi));
+        self
+    }
+
+    /// `ReconOptions::clamp_nonnegative`.
+    pub fn nonnegative(mut self, clamp: bool) -> Self {
+        self.options.clamp_nonnegative = clamp;
+        self
+    }
+
+    /// `ReconOptions::mask` (length N).
+    pub fn mask(mut self, mask: Array1<bool>) -> Self {
+        self.options.mask = Some(mask);
+        self
+    }
+
+    /// `ReconOptions::ray_weights` (length M).
+    pub fn ray_weights(mut self, weights: Array1<f64>) -> Self {
+        self.options.ray_weights = Some(weights);
+        self
+    }
+
+    /// `ReconOptions::voxel_weights` (length N).
+    pub fn voxel_weights(mut self, weights: Array1<f64>) -> Self {
+        self.options.voxel_weights = Some(weights);
+        self
+    }
+
+    /// `ReconOptions::column_weighted`.
+    pub fn column_weighted(mut self, weighted: bool) -> Self {
+        self.options.column_weighted = weighted;
+        self
+    }
+
+    /// `ReconOptions::initial_guess`.
+    pub fn initial_guess(mut self, guess: InitialGuess) -> Self {
+        self.options.initial_guess = guess;
+        self
+    }
+
+    /// `ReconOptions::row_order`.
+    pub fn row_order(mut self, order: RowOrder) -> Self {
+        self.options.row_order = order;
+        self
+    }
+
+    /// `ReconOptions::l2_regularization`.
+    pub fn l2_regularization(mut self, regularization: L2Regularization) -> Self {
+        self.options.l2_regularization = Some(regularization);
+        self
+    }
+
+    /// `ReconOptions::tv_regularization`.
+    pub fn tv_regularization(mut self, regularization: TvRegularization) -> Self {
+        self.options.tv_regularization = Some(regularization);
+        self
+    }
+
+    /// `ReconOptions::huber_regularization`.
+    pub fn huber_regularization(mut self, regularization: HuberRegularization) -> Self {
+        self.options.huber_regularization = Some(regularization);
+        self
+    }
+
+    /// `ReconOptions::auto_relaxation`.
+    pub fn auto_relaxation(mut self, auto: AutoRelaxation) -> Self {
+        self.options.auto_relaxation = Some(auto);
+        self
+    }
+
+    /// `ReconOptions::start_iteration`, e.g. when resuming a run.
+    pub fn start_iteration(mut self, iteration: usize) -> Self {
+        self.options.start_iteration = iteration;
+        self
+    }
+
+    /// `ReconOptions::patience`: stop as diverged after this many rising
+    /// residuals.
+    pub fn patience(mut self, patience: usize) -> Self {
+        self.options.patience = Some(patience);
+        self
+    }
+
+    /// `ReconOptions::threads`.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = Some(threads);
+        self
+    }
+
+    /// `ReconOptions::stop`: end the run early once `stop` is set.
+    pub fn stop(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.options.stop = Some(stop);
+        self
+    }
+
+    /// `ReconOptions::floor_eps`.
+    pub fn floor_eps(mut self, eps: f64) -> Self {
+        self.options.floor_eps = eps;
+        self
+    }
+
+    /// `ReconOptions::ratio_clamp`.
+    pub fn ratio_clamp(mut self, lo: f64, hi: f64) -> Self {
+        self.options.ratio_clamp = Some((lo, hi));
+        self
+    }
+
+    /// `ReconOptions::skip_eps`.
+    pub fn skip_eps(mut self, eps: f64) -> Self {
+        self.options.skip_eps = eps;
+        self
+    }
+
+    /// `ReconOptions::deterministic`.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.options.deterministic = deterministic;
+        self
+    }
+
+    /// `ReconOptions::parallel_subsets`.
+    pub fn parallel_subsets(mut self, parallel: bool) -> Self {
+        self.options.parallel_subsets = parallel;
+        self
+    }
+
+    /// Apply `set` when `value` is given, e.g. for an optional command-line
+    /// flag: `.optional(tol, MartBuilder::tolerance)`.
+    pub fn optional<V>(self, value: Option<V>, set: impl FnOnce(Self, V) -> Self) -> Self {
+        match value {
+            Some(value) => set(self, value),
+            None => self,
+        }
+    }
+
+    /// Edit any option in place.
+    pub fn configure(mut self, edit: impl FnOnce(&mut ReconOptions)) -> Self {
+        edit(&mut self.options);
+        self
+    }
+
+    /// The options collected so far, e.g. to hand to another solver.
+    pub fn options(&self) -> &ReconOptions {
+        &self.options
+    }
+
+    /// Run MART on `system_matrix` (dense or `SparseSystemMatrix`), with
+    /// the subsets or blocks set above.
+    pub fn run<T: ReconFloat, M: MartSystemMatrix<T> + ?Sized>(
+        &self,
+        projections: &Array1<T>,
+        system_matrix: &M,
+    ) -> Result<ReconReport<T>, ReconError> {
+        self.run_with_callback(projections, system_matrix, |_, _, _| {})
+    }
+
+    /// `run` with a per-iteration callback; see
+    /// `mart_reconstruct_with_callback` for its contract.
+    pub fn run_with_callback<T: ReconFloat, M: MartSystemMatrix<T> + ?Sized>(
+        &self,
+        projections: &Array1<T>,
+        system_matrix: &M,
+        callback: impl FnMut(usize, &Array1<T>, T),
+    ) -> Result<ReconReport<T>, ReconError> {
+        system_matrix.mart_reconstruct(projections, self, callback)
+    }
+
+    fn relaxation_as<T: ReconFloat>(&self) -> T {
+        T::from(self.relaxation).unwrap()
+    }
+}
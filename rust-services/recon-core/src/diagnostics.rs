@@ -0,0 +1,262 @@
+//! Pre-flight estimates of how well a system matrix can be inverted.
+//!
+//! The condition number `sigma_max / sigma_min` of `A` bounds how much
+//! measurement noise is amplified in a least-squares reconstruction. Large
+//! values (say above 1e6) mean the geometry leaves some voxel combinations
+//! essentially unmeasured, and no solver will recover them without
+//! regularization.
+//!
+//! The spectral radius of the SIRT/SART and Landweber iteration matrices
+//! likewise bounds the relaxation those solvers converge with; see
+//! `estimate_sirt_spectral_radius` and `estimate_landweber_spectral_radius`.
+
+use ndarray::{Array1, Array2};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::operator::masked_sums;
+use crate::sparse::{back_project_sparse, forward_project_sparse};
+use crate::{back_project, forward_project, LinearOperator, ReconFloat, SparseSystemMatrix};
+
+/// Fraction of the convergence bound `2 / rho` picked by `safe_relaxation`.
+pub const SAFE_RELAXATION_FRACTION: f64 = 0.9;
+
+/// Extreme singular values of a system matrix, as estimated by
+/// `estimate_condition`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConditionEstimate {
+    /// Largest singular value.
+    pub sigma_max: f64,
+    /// Smallest of the `min(M, N)` singular values (0 if the matrix is rank
+    /// deficient).
+    pub sigma_min: f64,
+}
+
+impl ConditionEstimate {
+    /// `sigma_max / sigma_min`, or infinity when `sigma_min` is 0.
+    pub fn condition_number(&self) -> f64 {
+        if self.sigma_min > 0.0 {
+            self.sigma_max / self.sigma_min
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Estimate the largest and smallest singular values of a dense system
+/// matrix (shape (M, N)) with `n_iters` steps each.
+///
+/// Works on the smaller of the Gram matrices `A^T A` and `A A^T`, so an
+/// underdetermined system is judged by its `M` singular values rather than
+/// by the `N - M` zeros of its null space. `sigma_max` comes from power
+/// iteration; `sigma_min` from inverse iteration, where each solve is
+/// `n_iters` conjugate-gradient steps. Every step costs one
+/// `forward_project` and one `back_project`.
+///
+/// Both are approached from inside the spectrum: `sigma_max` from below and
+/// `sigma_min` from above, so the condition number is an underestimate that
+/// tightens with more iterations. A handful is enough to tell a
+/// well-posed geometry (condition in the tens to thousands) from a hopeless
+/// one.
+pub fn estimate_condition<T: ReconFloat>(
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+) -> ConditionEstimate {
+    condition(
+        system_matrix.dim(),
+        n_iters,
+        |x| forward_project(system_matrix, x),
+        |y| back_project(system_matrix, y),
+    )
+}
+
+/// `estimate_condition` for a CSR system matrix.
+pub fn estimate_condition_sparse<T: ReconFloat>(
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+) -> ConditionEstimate {
+    condition(
+        system_matrix.dim(),
+        n_iters,
+        |x| forward_project_sparse(system_matrix, x),
+        |y| back_project_sparse(system_matrix, y),
+    )
+}
+
+/// Power and inverse iteration on the smaller Gram matrix, in f64.
+fn condition<T: ReconFloat>(
+    (m, n): (usize, usize),
+    n_iters: usize,
+    forward: impl Fn(&Array1<T>) -> Array1<T>,
+    back: impl Fn(&Array1<T>) -> Array1<T>,
+) -> ConditionEstimate {
+    let to_t = |v: &Array1<f64>| v.mapv(|x| T::from(x).unwrap());
+    let to_f64 = |v: Array1<T>| v.mapv(|x| x.to_f64().unwrap());
+    let dim = m.min(n);
+    let gram = |v: &Array1<f64>| {
+        if n <= m {
+            to_f64(back(&forward(&to_t(v))))
+        } else {
+            to_f64(forward(&back(&to_t(v))))
+        }
+    };
+    if dim == 0 {
+        return ConditionEstimate {
+            sigma_max: 0.0,
+            sigma_min: 0.0,
+        };
+    }
+
+    // a fixed random start overlaps every eigenvector (almost surely)
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let start = normalized(Array1::from_shape_fn(dim, |_| rng.gen_range(-1.0..1.0)));
+
+    let mut v = start.clone();
+    let mut lambda_max = 0.0;
+    for _ in 0..n_iters.max(1) {
+        let gv = gram(&v);
+        lambda_max = v.dot(&gv);
+        if gv.iter().all(|&x| x == 0.0) {
+            break;
+        }
+        v = normalized(gv);
+    }
+
+    let mut v = start;
+    let mut lambda_min = lambda_max;
+    for _ in 0..n_iters.max(1) {
+        let w = conjugate_gradient(gram, &v, n_iters.max(1));
+        if w.iter().all(|&x| x == 0.0) || w.iter().any(|x| !x.is_finite()) {
+            break;
+        }
+        v = normalized(w);
+        lambda_min = v.dot(&gram(&v)).min(lambda_min);
+    }
+
+    ConditionEstimate {
+        sigma_max: lambda_max.max(0.0).sqrt(),
+        sigma_min: lambda_min.max(0.0).sqrt(),
+    }
+}
+
+/// Estimate the spectral radius `rho` of the SIRT/SART iteration matrix
+/// `C A^T R A` with `n_iters` power-iteration steps.
+///
+/// `R` and `C` hold the inverse row and column sums (over the voxels kept
+/// by `mask`, as the solvers use them), so the update
+/// `x += relaxation * C A^T R (y - A x)` converges for every relaxation in
+/// `(0, 2 / rho)`; this plays the role of `2 / sigma_max(A^T A)` for plain
+/// Landweber iteration. For a nonnegative matrix `rho` is exactly 1 (the
+/// all-ones volume is an eigenvector), so the estimate matters for system
+/// matrices with negative entries, e.g. from interpolating or filtered
+/// projectors.
+///
+/// The iteration runs on the symmetric `C^1/2 A^T R A C^1/2`, which has the
+/// same eigenvalues, and approaches `rho` from below.
+pub fn estimate_sirt_spectral_radius<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    system_matrix: &A,
+    mask: Option<&Array1<bool>>,
+    n_iters: usize,
+) -> f64 {
+    let (row_sums, col_sums) = masked_sums(system_matrix, mask);
+    let inverse = |sums: Array1<T>| {
+        sums.mapv(|s| {
+            if s > T::zero() {
+                1.0 / s.to_f64().unwrap()
+            } else {
+                0.0
+            }
+        })
+    };
+    let (r, c_sqrt) = (inverse(row_sums), inverse(col_sums).mapv(f64::sqrt));
+    let to_t = |v: &Array1<f64>| v.mapv(|x| T::from(x).unwrap());
+    let to_f64 = |v: Array1<T>| v.mapv(|x| x.to_f64().unwrap());
+    power_iteration(system_matrix.dim().1, n_iters, |v| {
+        let weighted = to_f64(system_matrix.forward(&to_t(&(v * &c_sqrt)))) * &r;
+        to_f64(system_matrix.adjoint(&to_t(&weighted))) * &c_sqrt
+    })
+}
+
+/// Estimate the spectral radius `rho = sigma_max(A)^2` of `A^T A`, which
+/// bounds the Landweber relaxation to `(0, 2 / rho)`, with `n_iters`
+/// power-iteration steps (approaching `rho` from below).
+pub fn estimate_landweber_spectral_radius<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    system_matrix: &A,
+    n_iters: usize,
+) -> f64 {
+    let to_t = |v: &Array1<f64>| v.mapv(|x| T::from(x).unwrap());
+    power_iteration(system_matrix.dim().1, n_iters, |v| {
+        system_matrix
+            .adjoint(&system_matrix.forward(&to_t(v)))
+            .mapv(|x| x.to_f64().unwrap())
+    })
+}
+
+/// Largest eigenvalue of the symmetric positive semi-definite `operator`
+/// on vectors of length `n`, by power iteration from a fixed random start.
+fn power_iteration(
+    n: usize,
+    n_iters: usize,
+    operator: impl Fn(&Array1<f64>) -> Array1<f64>,
+) -> f64 {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut v = normalized(Array1::from_shape_fn(n, |_| rng.gen_range(-1.0..1.0)));
+    let mut rho = 0.0;
+    for _ in 0..n_iters.max(1) {
+        let sv = operator(&v);
+        rho = v.dot(&sv);
+        if sv.iter().all(|&x| x == 0.0) {
+            break;
+        }
+        v = normalized(sv);
+    }
+    rho.max(0.0)
+}
+
+/// Relaxation `SAFE_RELAXATION_FRACTION * 2 / rho` for a spectral radius
+/// from `estimate_sirt_spectral_radius` or
+/// `estimate_landweber_spectral_radius` (1 if `rho` is 0, i.e. the matrix
+/// is empty).
+pub fn safe_relaxation(spectral_radius: f64) -> f64 {
+    if spectral_radius > 0.0 {
+        SAFE_RELAXATION_FRACTION * 2.0 / spectral_radius
+    } else {
+        1.0
+    }
+}
+
+fn normalized(v: Array1<f64>) -> Array1<f64> {
+    let norm = v.dot(&v).sqrt();
+    v / norm
+}
+
+/// Approximate `G^-1 b` for the symmetric positive semi-definite `G` with
+/// up to `n_iters` conjugate-gradient steps from zero.
+fn conjugate_gradient(
+    gram: impl Fn(&Array1<f64>) -> Array1<f64>,
+    b: &Array1<f64>,
+    n_iters: usize,
+) -> Array1<f64> {
+    let mut x = Array1::zeros(b.len());
+    let mut r = b.clone();
+    let mut p = r.clone();
+    let mut rs = r.dot(&r);
+    let tol = 1e-28 * rs;
+    for _ in 0..n_iters {
+        let gp = gram(&p);
+        let pgp = p.dot(&gp);
+        if pgp.is_nan() || pgp <= 0.0 {
+            break;
+        }
+        let alpha = rs / pgp;
+        x.scaled_add(alpha, &p);
+        r.scaled_add(-alpha, &gp);
+        let rs_new = r.dot(&r);
+        if rs_new <= tol {
+            break;
+        }
+        p = &r + &(&p * (rs_new / rs));
+        rs = rs_new;
+    }
+    x
+}
@@ -0,0 +1,77 @@
+//! Error type for the reconstruction library.
+
+use thiserror::Error;
+
+use crate::validation::SystemMatrixIssues;
+
+/// Errors returned by the reconstruction routines.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ReconError {
+    /// An input's length does not match the system matrix.
+    #[error("{what} has length {actual}, expected {expected}")]
+    DimensionMismatch {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+
+    /// The system has no rays (M = 0) or no voxels (N = 0).
+    #[error("system has {rays} rays and {voxels} voxels; both must be at least 1")]
+    Empty { rays: usize, voxels: usize },
+
+    /// An input contains NaN or an infinity.
+    #[error("{what} has a non-finite value at index {index}")]
+    NonFinite { what: &'static str, index: usize },
+
+    /// MART's multiplicative update needs `y_i >= 0`.
+    #[error("projection {index} is negative ({value}); MART needs non-negative projections")]
+    NegativeProjection { index: usize, value: f64 },
+
+    /// A ray weight (`ReconOptions::ray_weights`) is below zero.
+    #[error("ray weight {index} is negative ({value})")]
+    NegativeWeight { index: usize, value: f64 },
+
+    /// A voxel weight (`ReconOptions::voxel_weights`) is below zero.
+    #[error("voxel weight {index} is negative ({value})")]
+    NegativeVoxelWeight { index: usize, value: f64 },
+
+    /// `mart_reconstruct_sparse_blocked` needs at least one voxel block.
+    #[error("cannot split {voxels} voxels into {blocks} blocks")]
+    InvalidBlocks { blocks: usize, voxels: usize },
+
+    /// A `MartBuilder` variant has no solver for this matrix type.
+    #[error("{what} does not support {matrix} system matrices")]
+    Unsupported {
+        what: &'static str,
+        matrix: &'static str,
+    },
+
+    /// A ray index passed to `mart_step_rows` is not a row of the matrix.
+    #[error("row index {index} is out of range for {rows} rays")]
+    RowOutOfRange { index: usize, rows: usize },
+
+    /// The system matrix has rows/columns or entries that would make the
+    /// reconstruction stall or produce garbage.
+    #[error("invalid system matrix: {0}")]
+    InvalidSystemMatrix(SystemMatrixIssues),
+
+    /// The volume became non-finite, or the residual kept rising for
+    /// `ReconOptions::patience` iterations; lowering the relaxation usually
+    /// helps.
+    #[error(
+        "reconstruction diverged at iteration {iteration} (residual {residual}); try a lower \
+        relaxation"
+    )]
+    Diverged { iteration: usize, residual: f64 },
+
+    /// A flat-field intensity passed to `log_transform` is zero, negative
+    /// or non-finite.
+    #[error("flat field {index} is {value}; I0 must be positive and finite")]
+    InvalidFlatField { index: usize, value: f64 },
+
+    /// The calibration coefficients of a two-material basis decomposition
+    /// are (nearly) proportional across the energies, so the materials
+    /// cannot be told apart.
+    #[error("basis coefficients for {energies} energies cannot separate the two materials")]
+    SingularBasis { energies: usize },
+}
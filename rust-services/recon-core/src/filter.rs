@@ -0,0 +1,160 @@
+//! Separable filters and resampling on flattened volumes.
+//!
+//! A volume of shape `[d0, d1, ...]` is stored in C order (the last axis
+//! varies fastest), so a 2D `[rows, cols]` grid uses the same layout as
+//! `Geometry::volume_shape` and a 3D stack is `[slices, rows, cols]`.
+
+use ndarray::Array1;
+
+use crate::ReconFloat;
+
+/// How `correlate_axis` reads voxels past either end of an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Border {
+    /// Repeat the edge voxel, so a constant volume stays constant.
+    Replicate,
+    /// Treat voxels outside the grid as zero.
+    Zero,
+}
+
+/// Correlate `volume` with a 1D `kernel` along `axis`:
+///
+///   out[.., i, ..] = sum_t kernel[t] * volume[.., i + t - center, ..]
+///
+/// so `kernel[center]` weighs the voxel itself.
+pub fn correlate_axis<T: ReconFloat>(
+    volume: &Array1<T>,
+    shape: &[usize],
+    axis: usize,
+    kernel: &[T],
+    center: usize,
+    border: Border,
+) -> Array1<T> {
+    assert_eq!(
+        volume.len(),
+        shape.iter().product::<usize>(),
+        "volume length must match the shape"
+    );
+    assert!(
+        center < kernel.len(),
+        "kernel center must be one of its taps"
+    );
+    let len = shape[axis] as isize;
+    let stride: usize = shape[axis + 1..].iter().product();
+
+    Array1::from_shape_fn(volume.len(), |j| {
+        let i = ((j / stride) % shape[axis]) as isize;
+        let mut acc = T::zero();
+        for (t, &k) in kernel.iter().enumerate() {
+            let pos = i + t as isize - center as isize;
+            let pos = match border {
+                Border::Replicate => pos.clamp(0, len - 1),
+                Border::Zero if pos < 0 || pos >= len => continue,
+                Border::Zero => pos,
+            };
+            let offset = (pos - i) * stride as isize;
+            acc = acc + k * volume[(j as isize + offset) as usize];
+        }
+        acc
+    })
+}
+
+/// Separable Gaussian blur with standard deviation `sigma` (in voxels) along
+/// every axis, replicating the edge voxels at the borders.
+///
+/// The kernel is truncated at `3 * sigma` and normalized to sum to one.
+/// Axes of length 1 are left alone, so a 2D volume passed as a single-slice
+/// 3D stack is only blurred in-plane. A `sigma` of 0 returns the volume
+/// unchanged.
+pub fn gaussian_blur<T: ReconFloat>(volume: &Array1<T>, shape: &[usize], sigma: f64) -> Array1<T> {
+    assert!(
+        sigma >= 0.0 && sigma.is_finite(),
+        "sigma must be non-negative, got {sigma}"
+    );
+    if sigma == 0.0 {
+        return volume.clone();
+    }
+    let kernel = gaussian_kernel::<T>(sigma);
+    let center = kernel.len() / 2;
+    let mut blurred = volume.clone();
+    for axis in (0..shape.len()).filter(|&axis| shape[axis] > 1) {
+        blurred = correlate_axis(&blurred, shape, axis, &kernel, center, Border::Replicate);
+    }
+    blurred
+}
+
+/// Sampled, normalized Gaussian of radius `ceil(3 * sigma)`.
+fn gaussian_kernel<T: ReconFloat>(sigma: f64) -> Vec<T> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    let weights: Vec<f64> = (-radius..=radius)
+        .map(|k| (-((k * k) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights
+        .iter()
+        .map(|w| T::from(w / total).unwrap())
+        .collect()
+}
+
+/// Resample `volume` from `shape` to `new_shape` (same number of axes) by
+/// linear interpolation along every axis: bilinear for 2D, trilinear for 3D.
+///
+/// Grids are aligned on their outer edges, so voxel centers sit at
+/// `(i + 0.5) * len / new_len - 0.5` in source voxel units and samples past
+/// the outermost centers repeat the edge voxel. Upsampling a constant
+/// volume keeps it constant. There is no anti-aliasing, so shrinking by
+/// more than 2x skips voxels; blur first if that matters.
+pub fn resample_linear<T: ReconFloat>(
+    volume: &Array1<T>,
+    shape: &[usize],
+    new_shape: &[usize],
+) -> Array1<T> {
+    assert_eq!(
+        volume.len(),
+        shape.iter().product::<usize>(),
+        "volume length must match the shape"
+    );
+    assert_eq!(
+        shape.len(),
+        new_shape.len(),
+        "shapes must have the same number of axes"
+    );
+    let mut resampled = volume.clone();
+    let mut current = shape.to_vec();
+    for axis in 0..shape.len() {
+        if current[axis] != new_shape[axis] {
+            resampled = resample_axis(&resampled, &current, axis, new_shape[axis]);
+            current[axis] = new_shape[axis];
+        }
+    }
+    resampled
+}
+
+/// Linear resampling of one axis of a C-order volume to `new_len` voxels.
+fn resample_axis<T: ReconFloat>(
+    volume: &Array1<T>,
+    shape: &[usize],
+    axis: usize,
+    new_len: usize,
+) -> Array1<T> {
+    let len = shape[axis];
+    let stride: usize = shape[axis + 1..].iter().product();
+    let outer: usize = shape[..axis].iter().product();
+    let scale = len as f64 / new_len as f64;
+
+    let mut resampled = Array1::zeros(outer * new_len * stride);
+    for k in 0..new_len {
+        let pos = ((k as f64 + 0.5) * scale - 0.5).clamp(0.0, (len - 1) as f64);
+        let lo = pos.floor() as usize;
+        let hi = (lo + 1).min(len - 1);
+        let frac = T::from(pos - lo as f64).unwrap();
+        for o in 0..outer {
+            for s in 0..stride {
+                let a = volume[(o * len + lo) * stride + s];
+                let b = volume[(o * len + hi) * stride + s];
+                resampled[(o * new_len + k) * stride + s] = a + (b - a) * frac;
+            }
+        }
+    }
+    resampled
+}
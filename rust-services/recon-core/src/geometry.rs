@@ -0,0 +1,603 @@
+//! Scanner geometry parsing and system-matrix synthesis.
+//!
+//! A geometry JSON describes the acquisition (angles, detector) and the
+//! reconstruction grid. From it we trace every ray through the pixel grid
+//! (Siddon's method) and store the intersection lengths as a CSR matrix, so
+//! the CLI can run from `--geometry` and raw projections alone.
+//!
+//! Example (2D parallel beam; for a fan beam use `"kind": "fan_beam"` and add
+//! `source_to_detector`, `source_to_center` and `fan_angle`):
+//!
+//! ```json
+//! {
+//!   "kind": "parallel_beam",
+//!   "num_angles": 180,
+//!   "num_detectors": 128,
+//!   "detector_spacing": 1.0,
+//!   "volume_shape": [128, 128],
+//!   "pixel_size": 1.0
+//! }
+//! ```
+//!
+//! Conventions:
+//! - `volume_shape` is `[rows, cols]` like a NumPy image; voxel `(r, c)` is
+//!   flattened to `j = r * cols + c` (C order). Columns run along +x, rows
+//!   along +y, and the grid is centered on the rotation axis.
+//! - Rays are ordered angle-major: row `i = angle_index * num_detectors +
+//!   detector_index`.
+//! - Angles start at `angle_start_deg` and cover `angle_range_deg` (default
+//!   180) without repeating the endpoint. For non-uniform or limited-angle
+//!   scans, list them instead as `"angles_deg": [0.0, 1.5, 4.0, ...]`;
+//!   `num_angles` may then be omitted, and the start/range keys are ignored.
+//! - At angle `theta` the central ray travels along `(-sin, cos)` and the
+//!   detector axis points along `(cos, sin)`. Fan-beam sources sit
+//!   `source_to_center` behind the rotation axis on the central ray.
+//!
+//! Region of interest: adding
+//! `"roi": {"x0": 32, "y0": 40, "nx": 64, "ny": 48, "background": 0.0}`
+//! reconstructs only the `ny x nx` block of `volume_shape` whose first
+//! column is `x0` and first row `y0` (`z0` and `nz`, if given, must be 0 and
+//! 1 for these 2D grids). Rays are still traced through the full grid, but
+//! the system matrix only has columns for the ROI voxels, in C order within
+//! the ROI; every voxel outside is held at the constant `background`, whose
+//! contribution `background_projections` the caller subtracts from the
+//! data. Caveat: the model is only exact if the object outside the ROI
+//! really is that constant. Any other structure on rays through the ROI
+//! (interior/truncated tomography) is forced into the ROI voxels, which
+//! typically shows up as a bright or dark cupping ring along the ROI
+//! border and a low-frequency intensity offset; pad the ROI or estimate the
+//! background from a coarse full-field reconstruction to contain it.
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{ReconFloat, SparseSystemMatrix};
+
+/// Errors raised while parsing or validating a geometry description.
+#[derive(Debug, Error)]
+pub enum GeometryError {
+    #[error("invalid geometry JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("invalid geometry: {0}")]
+    Invalid(String),
+}
+
+/// Beam shape of the acquisition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GeometryKind {
+    /// Parallel rays; the detector offset is perpendicular to the beam.
+    ParallelBeam,
+    /// Rays diverge from a point source onto a flat detector.
+    ///
+    /// The `num_detectors` pixels are evenly spaced across the detector
+    /// width that subtends `fan_angle` (degrees) at the source, so
+    /// `detector_spacing` is ignored. Ray directions depend only on
+    /// `fan_angle` and the source position; `source_to_detector` places the
+    /// detector plane and must exceed `source_to_center`.
+    FanBeam {
+        source_to_detector: f64,
+        source_to_center: f64,
+        fan_angle: f64,
+    },
+}
+
+/// 2D scanner geometry plus reconstruction grid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    /// Beam shape, selected by the `"kind"` key.
+    #[serde(flatten)]
+    pub kind: GeometryKind,
+    /// Number of projection angles (defaults to the length of `angles_deg`
+    /// when that is given).
+    #[serde(default)]
+    pub num_angles: usize,
+    /// Explicit projection angles in degrees, in acquisition order; overrides
+    /// `angle_start_deg` and `angle_range_deg`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angles_deg: Option<Vec<f64>>,
+    /// First projection angle in degrees.
+    #[serde(default)]
+    pub angle_start_deg: f64,
+    /// Angular range covered by the `num_angles` angles, in degrees.
+    #[serde(default = "default_angle_range_deg")]
+    pub angle_range_deg: f64,
+    /// Number of detector pixels per angle.
+    pub num_detectors: usize,
+    /// Distance between detector pixel centers (same units as `pixel_size`;
+    /// parallel beam only).
+    #[serde(default = "default_unit")]
+    pub detector_spacing: f64,
+    /// Reconstruction grid as `[rows, cols]`.
+    pub volume_shape: [usize; 2],
+    /// Edge length of one (square) pixel.
+    #[serde(default = "default_unit")]
+    pub pixel_size: f64,
+    /// Reconstruct only this block of the grid (see the module docs).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roi: Option<Roi>,
+}
+
+/// A rectangular region of interest within `Geometry::volume_shape`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Roi {
+    /// First column of the ROI.
+    pub x0: usize,
+    /// First row of the ROI.
+    pub y0: usize,
+    /// First slice; must be 0 for a 2D grid.
+    #[serde(default)]
+    pub z0: usize,
+    /// Number of columns.
+    pub nx: usize,
+    /// Number of rows.
+    pub ny: usize,
+    /// Number of slices; must be 1 for a 2D grid.
+    #[serde(default = "default_slices")]
+    pub nz: usize,
+    /// Fixed value of every voxel outside the ROI.
+    #[serde(default)]
+    pub background: f64,
+}
+
+fn default_slices() -> usize {
+    1
+}
+
+fn default_angle_range_deg() -> f64 {
+    180.0
+}
+
+fn default_unit() -> f64 {
+    1.0
+}
+
+/// A ray `origin + t * direction` with unit `direction`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: [f64; 2],
+    pub direction: [f64; 2],
+}
+
+impl Geometry {
+    /// Parse and validate a geometry JSON document.
+    pub fn from_json(json: &str) -> Result<Self, GeometryError> {
+        let mut geometry: Geometry = serde_json::from_str(json)?;
+        if let Some(angles) = &geometry.angles_deg {
+            if geometry.num_angles == 0 {
+                geometry.num_angles = angles.len();
+            }
+        }
+        geometry.validate()?;
+        Ok(geometry)
+    }
+
+    /// Check that all sizes are positive and all lengths are finite and > 0.
+    pub fn validate(&self) -> Result<(), GeometryError> {
+        if self.num_angles == 0 || self.num_detectors == 0 {
+            return Err(GeometryError::Invalid(
+                "num_angles (or angles_deg) and num_detectors must be at least 1".into(),
+            ));
+        }
+        if let Some(angles) = &self.angles_deg {
+            if angles.len() != self.num_angles {
+                return Err(GeometryError::Invalid(format!(
+                    "angles_deg lists {} angles but num_angles is {}",
+                    angles.len(),
+                    self.num_angles
+                )));
+            }
+            if let Some(index) = angles.iter().position(|a| !a.is_finite()) {
+                return Err(GeometryError::Invalid(format!(
+                    "angles_deg[{index}] is not finite ({})",
+                    angles[index]
+                )));
+            }
+        }
+        if self.volume_shape.contains(&0) {
+            return Err(GeometryError::Invalid(format!(
+                "volume_shape {:?} must not contain zeros",
+                self.volume_shape
+            )));
+        }
+        if let Some(roi) = &self.roi {
+            let [rows, cols] = self.volume_shape;
+            if roi.nx == 0 || roi.ny == 0 || roi.x0 + roi.nx > cols || roi.y0 + roi.ny > rows {
+                return Err(GeometryError::Invalid(format!(
+                    "roi columns {}..{} and rows {}..{} must be non-empty and inside the \
+                    {rows}x{cols} grid",
+                    roi.x0,
+                    roi.x0 + roi.nx,
+                    roi.y0,
+                    roi.y0 + roi.ny
+                )));
+            }
+            if roi.z0 != 0 || roi.nz != 1 {
+                return Err(GeometryError::Invalid(format!(
+                    "roi z0 = {} and nz = {} must be 0 and 1 for a 2D grid",
+                    roi.z0, roi.nz
+                )));
+            }
+            if !roi.background.is_finite() {
+                return Err(GeometryError::Invalid(format!(
+                    "roi background must be finite, got {}",
+                    roi.background
+                )));
+            }
+        }
+        for (name, value) in [
+            ("detector_spacing", self.detector_spacing),
+            ("pixel_size", self.pixel_size),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(GeometryError::Invalid(format!(
+                    "{name} must be positive, got {value}"
+                )));
+            }
+        }
+
+        if let GeometryKind::FanBeam {
+            source_to_detector,
+            source_to_center,
+            fan_angle,
+        } = self.kind
+        {
+            if !(source_to_center.is_finite() && source_to_center > 0.0) {
+                return Err(GeometryError::Invalid(format!(
+                    "source_to_center must be positive, got {source_to_center}"
+                )));
+            }
+            if !(source_to_detector.is_finite() && source_to_detector > source_to_center) {
+                return Err(GeometryError::Invalid(format!(
+                    "source_to_detector ({source_to_detector}) must exceed source_to_center \
+                    ({source_to_center})"
+                )));
+            }
+            if !(fan_angle > 0.0 && fan_angle < 180.0) {
+                return Err(GeometryError::Invalid(format!(
+                    "fan_angle must be in (0, 180) degrees, got {fan_angle}"
+                )));
+            }
+
+            // the source must stay outside the grid for every angle
+            let half_diagonal = 0.5
+                * self.pixel_size
+                * ((self.volume_shape[0].pow(2) + self.volume_shape[1].pow(2)) as f64).sqrt();
+            if source_to_center <= half_diagonal {
+                return Err(GeometryError::Invalid(format!(
+                    "source_to_center ({source_to_center}) must exceed the grid half-diagonal \
+                    ({half_diagonal})"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of rays M (rows of the system matrix).
+    pub fn num_rays(&self) -> usize {
+        self.num_angles * self.num_detectors
+    }
+
+    /// Number of voxels N (columns of the system matrix): the ROI's if
+    /// there is one.
+    pub fn num_voxels(&self) -> usize {
+        let [rows, cols] = self.reconstruction_shape();
+        rows * cols
+    }
+
+    /// Shape `[rows, cols]` of the reconstructed volume: the ROI's
+    /// `[ny, nx]`, otherwise `volume_shape`.
+    pub fn reconstruction_shape(&self) -> [usize; 2] {
+        match &self.roi {
+            Some(roi) => [roi.ny, roi.nx],
+            None => self.volume_shape,
+        }
+    }
+
+    /// Column of the system matrix for voxel `j` of the full grid, or
+    /// `None` if it lies outside the ROI.
+    fn roi_column(&self, j: usize) -> Option<usize> {
+        let Some(roi) = &self.roi else {
+            return Some(j);
+        };
+        let cols = self.volume_shape[1];
+        let (r, c) = (j / cols, j % cols);
+        let inside =
+            (roi.y0..roi.y0 + roi.ny).contains(&r) && (roi.x0..roi.x0 + roi.nx).contains(&c);
+        inside.then(|| (r - roi.y0) * roi.nx + (c - roi.x0))
+    }
+
+    /// Check that a projection vector of length `m` matches this geometry,
+    /// i.e. `m == num_angles * num_detectors`.
+    pub fn check_num_rays(&self, m: usize) -> Result<(), GeometryError> {
+        if m == self.num_rays() {
+            return Ok(());
+        }
+        Err(GeometryError::Invalid(format!(
+            "projections have length {m}, but the geometry has {} angles x {} detectors = {} rays",
+            self.num_angles,
+            self.num_detectors,
+            self.num_rays()
+        )))
+    }
+
+    /// Check that a precomputed system matrix of shape `(m, n)` was built
+    /// for this geometry: one row per ray (`num_angles * num_detectors`) and
+    /// one column per voxel of `volume_shape`.
+    pub fn check_system_matrix(&self, (m, n): (usize, usize)) -> Result<(), GeometryError> {
+        let mut problems = Vec::new();
+        if m != self.num_rays() {
+            problems.push(format!(
+                "{m} rows, but the geometry has {} angles x {} detectors = {} rays",
+                self.num_angles,
+                self.num_detectors,
+                self.num_rays()
+            ));
+        }
+        if n != self.num_voxels() {
+            let [rows, cols] = self.reconstruction_shape();
+            let what = if self.roi.is_some() { "ROI" } else { "volume" };
+            problems.push(format!(
+                "{n} columns, but the geometry has a {rows}x{cols} {what} = {} voxels",
+                self.num_voxels()
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(GeometryError::Invalid(format!(
+            "system matrix has {}",
+            problems.join(" and ")
+        )))
+    }
+
+    /// A 64-bit FNV-1a hash of the geometry, for telling whether a cached
+    /// system matrix was built from it.
+    ///
+    /// The hash covers every field as the geometry serializes to JSON, so it
+    /// does not depend on key order, whitespace or defaulted keys in the
+    /// source file, and is the same on every platform; any change to the
+    /// scanner or the grid changes it.
+    pub fn fingerprint(&self) -> u64 {
+        let json = serde_json::to_string(self).expect("geometry serializes to JSON");
+        json.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// Projection angles in degrees: `angles_deg` if given, otherwise
+    /// `num_angles` evenly spaced steps over `angle_range_deg`.
+    pub fn angles_degrees(&self) -> Vec<f64> {
+        if let Some(angles) = &self.angles_deg {
+            return angles.clone();
+        }
+        let step = self.angle_range_deg / self.num_angles as f64;
+        (0..self.num_angles)
+            .map(|k| self.angle_start_deg + k as f64 * step)
+            .collect()
+    }
+
+    /// Projection angles in radians (see `angles_degrees`).
+    pub fn angles_rad(&self) -> Vec<f64> {
+        self.angles_degrees()
+            .into_iter()
+            .map(f64::to_radians)
+            .collect()
+    }
+
+    /// Rows of the system matrix (and entries of the projections) measured
+    /// at the angles `angles` (0-based indices), in the given order.
+    ///
+    /// Panics if an index is not below `num_angles`.
+    pub fn angle_rows(&self, angles: &[usize]) -> Vec<usize> {
+        angles
+            .iter()
+            .flat_map(|&a| {
+                assert!(
+                    a < self.num_angles,
+                    "angle index {a} out of range for {} angles",
+                    self.num_angles
+                );
+                a * self.num_detectors..(a + 1) * self.num_detectors
+            })
+            .collect()
+    }
+
+    /// The same scan restricted to the angles `angles` (0-based, in the
+    /// given order), e.g. for limited-view studies.
+    ///
+    /// The result lists its angles in `angles_deg`, so its system matrix
+    /// is exactly the rows `angle_rows(angles)` of this geometry's matrix.
+    /// Fails on an empty selection, out-of-range or repeated indices.
+    pub fn select_angles(&self, angles: &[usize]) -> Result<Geometry, GeometryError> {
+        if angles.is_empty() {
+            return Err(GeometryError::Invalid("angle selection is empty".into()));
+        }
+        let mut seen = vec![false; self.num_angles];
+        for &a in angles {
+            if a >= self.num_angles {
+                return Err(GeometryError::Invalid(format!(
+                    "angle index {a} out of range for {} angles",
+                    self.num_angles
+                )));
+            }
+            if std::mem::replace(&mut seen[a], true) {
+                return Err(GeometryError::Invalid(format!(
+                    "angle index {a} selected twice"
+                )));
+            }
+        }
+        let degrees = self.angles_degrees();
+        Ok(Geometry {
+            num_angles: angles.len(),
+            angles_deg: Some(angles.iter().map(|&a| degrees[a]).collect()),
+            ..self.clone()
+        })
+    }
+
+    /// The ray hitting detector pixel `detector` at angle `theta` (radians).
+    pub fn ray(&self, theta: f64, detector: usize) -> Ray {
+        // detector axis n = (cos, sin), central beam direction u = (-sin, cos)
+        let (sin, cos) = theta.sin_cos();
+        let n = [cos, sin];
+        let u = [-sin, cos];
+        let centered = detector as f64 + 0.5 - self.num_detectors as f64 / 2.0;
+
+        match self.kind {
+            GeometryKind::ParallelBeam => {
+                let offset = centered * self.detector_spacing;
+                Ray {
+                    origin: [offset * n[0], offset * n[1]],
+                    direction: u,
+                }
+            }
+            GeometryKind::FanBeam {
+                source_to_center,
+                fan_angle,
+                ..
+            } => {
+                // flat detector: pixel offsets are linear in tan(gamma)
+                let half_width = (0.5 * fan_angle.to_radians()).tan();
+                let tan_gamma = centered * 2.0 * half_width / self.num_detectors as f64;
+                let norm = (1.0 + tan_gamma * tan_gamma).sqrt();
+                Ray {
+                    origin: [-source_to_center * u[0], -source_to_center * u[1]],
+                    direction: [
+                        (u[0] + tan_gamma * n[0]) / norm,
+                        (u[1] + tan_gamma * n[1]) / norm,
+                    ],
+                }
+            }
+        }
+    }
+
+    /// Trace every ray through the grid and assemble the CSR system matrix.
+    ///
+    /// Entry `A_ij` is the length of ray `i` inside voxel `j`. With a ROI
+    /// only its voxels get columns; see `background_projections` for the
+    /// rest.
+    pub fn build_system_matrix<T: ReconFloat>(&self) -> SparseSystemMatrix<T> {
+        let mut indptr = Vec::with_capacity(self.num_rays() + 1);
+        let mut indices = Vec::new();
+        let mut data = Vec::new();
+
+        indptr.push(0);
+        for theta in self.angles_rad() {
+            for detector in 0..self.num_detectors {
+                let mut hits: Vec<(usize, f64)> = siddon(
+                    self.ray(theta, detector),
+                    self.volume_shape,
+                    self.pixel_size,
+                )
+                .into_iter()
+                .filter_map(|(j, length)| self.roi_column(j).map(|column| (column, length)))
+                .collect();
+                hits.sort_unstable_by_key(|&(j, _)| j);
+                for (j, length) in hits {
+                    indices.push(j);
+                    data.push(T::from(length).unwrap());
+                }
+                indptr.push(data.len());
+            }
+        }
+
+        SparseSystemMatrix::new((self.num_rays(), self.num_voxels()), indptr, indices, data)
+    }
+
+    /// Projections of the fixed background outside the ROI: per ray,
+    /// `background` times its path length outside the ROI. `None` without a
+    /// ROI or with a zero background.
+    ///
+    /// The ROI volume `x` then models the data as `y = A x + y_background`.
+    pub fn background_projections<T: ReconFloat>(&self) -> Option<Array1<T>> {
+        let roi = self.roi.filter(|roi| roi.background != 0.0)?;
+        let mut background = Vec::with_capacity(self.num_rays());
+        for theta in self.angles_rad() {
+            for detector in 0..self.num_detectors {
+                let outside: f64 = siddon(
+                    self.ray(theta, detector),
+                    self.volume_shape,
+                    self.pixel_size,
+                )
+                .into_iter()
+                .filter(|&(j, _)| self.roi_column(j).is_none())
+                .map(|(_, length)| length)
+                .sum();
+                background.push(T::from(roi.background * outside).unwrap());
+            }
+        }
+        Some(Array1::from(background))
+    }
+}
+
+/// Intersection lengths of `ray` with the pixels of a centered grid.
+///
+/// Collects the parametric positions where the ray crosses the grid's x and
+/// y planes, sorts them, and assigns each segment to the pixel containing
+/// its midpoint. Returns `(voxel_index, length)` pairs.
+pub fn siddon(ray: Ray, shape: [usize; 2], pixel_size: f64) -> Vec<(usize, f64)> {
+    let [rows, cols] = shape;
+    let half = [
+        cols as f64 * pixel_size / 2.0,
+        rows as f64 * pixel_size / 2.0,
+    ];
+    let counts = [cols, rows];
+
+    // clip the ray to the grid's bounding box
+    let mut t_enter = f64::NEG_INFINITY;
+    let mut t_exit = f64::INFINITY;
+    for ((&o, &d), &h) in ray.origin.iter().zip(&ray.direction).zip(&half) {
+        if d.abs() < f64::EPSILON {
+            if o <= -h || o >= h {
+                return Vec::new();
+            }
+            continue;
+        }
+        let t1 = (-h - o) / d;
+        let t2 = (h - o) / d;
+        t_enter = t_enter.max(t1.min(t2));
+        t_exit = t_exit.min(t1.max(t2));
+    }
+    if t_enter >= t_exit {
+        return Vec::new();
+    }
+
+    // plane crossings strictly inside the clipped segment
+    let mut ts = vec![t_enter, t_exit];
+    for (((&o, &d), &h), &count) in ray
+        .origin
+        .iter()
+        .zip(&ray.direction)
+        .zip(&half)
+        .zip(&counts)
+    {
+        if d.abs() < f64::EPSILON {
+            continue;
+        }
+        for k in 0..=count {
+            let plane = -h + k as f64 * pixel_size;
+            let t = (plane - o) / d;
+            if t > t_enter && t < t_exit {
+                ts.push(t);
+            }
+        }
+    }
+    ts.sort_by(f64::total_cmp);
+
+    let mut hits = Vec::new();
+    for pair in ts.windows(2) {
+        let length = pair[1] - pair[0];
+        if length <= 1e-12 * pixel_size {
+            continue;
+        }
+        let t_mid = 0.5 * (pair[0] + pair[1]);
+        let x = ray.origin[0] + t_mid * ray.direction[0];
+        let y = ray.origin[1] + t_mid * ray.direction[1];
+        let col = (((x + half[0]) / pixel_size).floor() as usize).min(cols - 1);
+        let row = (((y + half[1]) / pixel_size).floor() as usize).min(rows - 1);
+        hits.push((row * cols + col, length));
+    }
+    hits
+}
@@ -0,0 +1,105 @@
+//! Reading projections and system matrices from HDF5 files (`hdf5`
+//! feature).
+//!
+//! Datasets are addressed by their path inside the file, e.g.
+//! `/entry/data/projections`. A system matrix is either a 2D dataset
+//! (dense) or a group holding the CSR arrays `data`, `indices`, `indptr`
+//! and `shape`, the same keys a scipy `.npz` uses. HDF5 converts the stored
+//! element types on read, so any float type works for values and any
+//! integer type for indices.
+
+use std::path::Path;
+
+use ::hdf5::{File, LocationType};
+use ndarray::{Array1, Array2};
+
+use crate::io::{check_csr, LoadError};
+use crate::{ReconFloat, SparseSystemMatrix};
+
+/// System matrix as stored in an HDF5 file.
+#[derive(Debug, Clone)]
+pub enum H5SystemMatrix<T> {
+    /// 2D dataset of shape (M, N).
+    Dense(Array2<T>),
+    /// Group with CSR arrays.
+    Sparse(SparseSystemMatrix<T>),
+}
+
+/// Read the 1D dataset `dataset` (e.g. the projections) from `path`.
+pub fn read_array1_h5<T: ReconFloat>(path: &Path, dataset: &str) -> Result<Array1<T>, LoadError> {
+    let file = File::open(path)?;
+    let values = read_floats(&file, dataset, 1)?.1;
+    Ok(Array1::from_vec(values))
+}
+
+/// True if the dataset `dataset` in `path` stores 8-byte (double) values.
+pub fn dataset_is_f64(path: &Path, dataset: &str) -> Result<bool, LoadError> {
+    Ok(File::open(path)?.dataset(dataset)?.dtype()?.size() == 8)
+}
+
+/// Read the system matrix stored at `name` in `path`: a dense 2D dataset, or
+/// a CSR group (see the module docs).
+pub fn read_system_matrix_h5<T: ReconFloat>(
+    path: &Path,
+    name: &str,
+) -> Result<H5SystemMatrix<T>, LoadError> {
+    let file = File::open(path)?;
+    if file.loc_type_by_name(name)? != LocationType::Group {
+        let (shape, values) = read_floats(&file, name, 2)?;
+        let dense = Array2::from_shape_vec((shape[0], shape[1]), values)
+            .map_err(|e| LoadError::Format(format!("dataset {name:?}: {e}")))?;
+        return Ok(H5SystemMatrix::Dense(dense));
+    }
+
+    let group = file.group(name)?;
+    let read_indices = |key: &str| -> Result<Vec<usize>, LoadError> {
+        let raw = group.dataset(key)?.read_raw::<i64>()?;
+        raw.into_iter()
+            .map(|v| {
+                usize::try_from(v)
+                    .map_err(|_| LoadError::Format(format!("negative entry {v} in {name}/{key}")))
+            })
+            .collect()
+    };
+    let shape = match read_indices("shape")?[..] {
+        [rows, cols] => (rows, cols),
+        ref other => {
+            return Err(LoadError::Format(format!(
+                "{name}/shape must have 2 entries, got {other:?}"
+            )))
+        }
+    };
+    let indptr = read_indices("indptr")?;
+    let indices = read_indices("indices")?;
+    let data: Vec<T> = group
+        .dataset("data")?
+        .read_raw::<f64>()?
+        .into_iter()
+        .map(|v| T::from(v).unwrap())
+        .collect();
+    check_csr(shape, &indptr, &indices, data.len())?;
+    Ok(H5SystemMatrix::Sparse(SparseSystemMatrix::new(
+        shape, indptr, indices, data,
+    )))
+}
+
+/// Shape and C-order values of the `ndim`-dimensional float dataset `name`.
+fn read_floats<T: ReconFloat>(
+    file: &File,
+    name: &str,
+    ndim: usize,
+) -> Result<(Vec<usize>, Vec<T>), LoadError> {
+    let dataset = file.dataset(name)?;
+    let shape = dataset.shape();
+    if shape.len() != ndim {
+        return Err(LoadError::Format(format!(
+            "dataset {name:?} has shape {shape:?}, expected {ndim} dimension(s)"
+        )));
+    }
+    let values = dataset
+        .read_raw::<f64>()?
+        .into_iter()
+        .map(|v| T::from(v).unwrap())
+        .collect();
+    Ok((shape, values))
+}
@@ -0,0 +1,447 @@
+//! Low-memory loaders for system matrices stored as NumPy files.
+//!
+//! Reading a CSR `.npz` with `ndarray_npy::NpzReader` materializes every
+//! array as an `ndarray` first and then converts it (`i32`/`i64` indices to
+//! `usize`, `f32` data to `f64`), so peak memory is roughly twice the final
+//! matrix. A dense `.npy` is worse: the whole `M x N` array has to fit in RAM
+//! even when almost every entry is zero.
+//!
+//! The loaders here decode the NumPy data in fixed-size chunks straight
+//! into the final `indptr` / `indices` / `data` vectors:
+//!
+//! - `load_csr_npz` peaks at the size of the resulting `SparseSystemMatrix`
+//!   (`8 * (M + 1) + nnz * (8 + size_of::<T>())` bytes) plus a 64 KiB
+//!   buffer. With `mmap`, uncompressed (`np.savez`) entries are parsed
+//!   directly from a memory map of the archive; compressed entries
+//!   (`np.savez_compressed`, scipy's default) are inflated chunk by chunk.
+//!   COO archives (`row`, `col`, `data`, `shape`, as `scipy.sparse.save_npz`
+//!   writes a `coo_matrix`) are converted to CSR after loading, which
+//!   briefly holds the triplets and the CSR matrix together.
+//! - `load_dense_npy_as_csr` memory-maps a dense `.npy` and keeps only its
+//!   nonzeros, so heap usage is proportional to nnz rather than `M * N`.
+//!   The mapped pages belong to the OS page cache and can be evicted under
+//!   memory pressure.
+//!
+//! Only little-endian `f32`/`f64` data and `i32`/`i64` indices in C order
+//! are supported, which covers what NumPy and scipy write by default.
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use memmap2::Mmap;
+use thiserror::Error;
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::{ReconFloat, SparseSystemMatrix};
+
+/// Errors raised while loading a system matrix from disk.
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid npz archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[cfg(feature = "hdf5")]
+    #[error("HDF5 error: {0}")]
+    Hdf5(#[from] ::hdf5::Error),
+    #[error("{0}")]
+    Format(String),
+}
+
+/// Bytes decoded per chunk.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NpyDtype {
+    F32,
+    F64,
+    I32,
+    I64,
+}
+
+impl NpyDtype {
+    fn size(self) -> usize {
+        match self {
+            NpyDtype::F32 | NpyDtype::I32 => 4,
+            NpyDtype::F64 | NpyDtype::I64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NpyHeader {
+    dtype: NpyDtype,
+    fortran_order: bool,
+    shape: Vec<usize>,
+}
+
+impl NpyHeader {
+    fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+}
+
+/// Read the `.npy` preamble, leaving `reader` at the first data byte.
+fn read_header(reader: &mut (impl Read + ?Sized)) -> Result<NpyHeader, LoadError> {
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..6] != b"\x93NUMPY" {
+        return Err(LoadError::Format("not a .npy file (bad magic)".into()));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        v => return Err(LoadError::Format(format!("unsupported .npy version {v}"))),
+    };
+    let mut header = vec![0u8; header_len];
+    reader.read_exact(&mut header)?;
+    parse_header_dict(&String::from_utf8_lossy(&header))
+}
+
+/// Parse the Python dict literal `{'descr': '<f4', 'fortran_order': False,
+/// 'shape': (3, 4), }` of a `.npy` header.
+fn parse_header_dict(header: &str) -> Result<NpyHeader, LoadError> {
+    let value_after = |key: &str| {
+        header
+            .find(&format!("'{key}':"))
+            .map(|pos| header[pos + key.len() + 3..].trim_start())
+            .ok_or_else(|| LoadError::Format(format!("npy header is missing '{key}': {header}")))
+    };
+
+    let descr = value_after("descr")?;
+    let descr = descr.trim_start_matches(['\'', '"']);
+    let descr = &descr[..descr.find(['\'', '"']).unwrap_or(descr.len())];
+    let dtype = match descr {
+        "<f4" => NpyDtype::F32,
+        "<f8" => NpyDtype::F64,
+        "<i4" => NpyDtype::I32,
+        "<i8" => NpyDtype::I64,
+        other => {
+            return Err(LoadError::Format(format!(
+                "unsupported npy dtype {other:?}"
+            )))
+        }
+    };
+
+    let fortran_order = value_after("fortran_order")?.starts_with("True");
+
+    let shape = value_after("shape")?;
+    let shape = &shape[1..shape.find(')').unwrap_or(shape.len())];
+    let shape = shape
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            d.parse::<usize>()
+                .map_err(|_| LoadError::Format(format!("bad npy shape in {header}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(NpyHeader {
+        dtype,
+        fortran_order,
+        shape,
+    })
+}
+
+/// Decode `header.len()` elements from `reader` in chunks, calling `f` on
+/// each chunk of raw little-endian bytes.
+fn for_each_chunk(
+    reader: &mut (impl Read + ?Sized),
+    header: &NpyHeader,
+    mut f: impl FnMut(&[u8]) -> Result<(), LoadError>,
+) -> Result<(), LoadError> {
+    let size = header.dtype.size();
+    let mut buffer = vec![0u8; CHUNK_BYTES / size * size];
+    let mut remaining = header.len() * size;
+    while remaining > 0 {
+        let chunk = &mut buffer[..remaining.min(CHUNK_BYTES / size * size)];
+        reader.read_exact(chunk)?;
+        f(chunk)?;
+        remaining -= chunk.len();
+    }
+    Ok(())
+}
+
+/// Stream a float array, passing every value to `sink`.
+fn stream_floats(
+    reader: &mut (impl Read + ?Sized),
+    header: &NpyHeader,
+    mut sink: impl FnMut(f64),
+) -> Result<(), LoadError> {
+    let dtype = header.dtype;
+    for_each_chunk(reader, header, |bytes| {
+        match dtype {
+            NpyDtype::F32 => bytes
+                .chunks_exact(4)
+                .for_each(|b| sink(f32::from_le_bytes(b.try_into().unwrap()) as f64)),
+            NpyDtype::F64 => bytes
+                .chunks_exact(8)
+                .for_each(|b| sink(f64::from_le_bytes(b.try_into().unwrap()))),
+            _ => {
+                return Err(LoadError::Format(format!(
+                    "expected float data, got {dtype:?}"
+                )))
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Stream an integer array as `usize`, rejecting negative values.
+fn stream_indices(
+    reader: &mut (impl Read + ?Sized),
+    header: &NpyHeader,
+    mut sink: impl FnMut(usize),
+) -> Result<(), LoadError> {
+    let dtype = header.dtype;
+    for_each_chunk(reader, header, |bytes| {
+        let mut push = |v: i64| {
+            usize::try_from(v)
+                .map(&mut sink)
+                .map_err(|_| LoadError::Format(format!("negative index {v}")))
+        };
+        match dtype {
+            NpyDtype::I32 => bytes
+                .chunks_exact(4)
+                .try_for_each(|b| push(i32::from_le_bytes(b.try_into().unwrap()) as i64)),
+            NpyDtype::I64 => bytes
+                .chunks_exact(8)
+                .try_for_each(|b| push(i64::from_le_bytes(b.try_into().unwrap()))),
+            _ => Err(LoadError::Format(format!(
+                "expected integer indices, got {dtype:?}"
+            ))),
+        }
+    })
+}
+
+/// Name of the entry `key` (or `key.npy`) of an archive, if present.
+fn entry_name<R: Read + Seek>(archive: &ZipArchive<R>, key: &str) -> Option<String> {
+    [key.to_string(), format!("{key}.npy")]
+        .into_iter()
+        .find(|name| archive.file_names().any(|f| f == name))
+}
+
+/// Open the entry `key` (or `key.npy`) of an archive and hand its bytes to
+/// `f`, reading straight from `mapped` when the entry is stored
+/// uncompressed.
+fn with_entry<R: Read + Seek, O>(
+    archive: &mut ZipArchive<R>,
+    mapped: Option<&[u8]>,
+    key: &str,
+    f: impl FnOnce(&mut dyn Read) -> Result<O, LoadError>,
+) -> Result<O, LoadError> {
+    let name = entry_name(archive, key)
+        .ok_or_else(|| LoadError::Format(format!("npz archive has no {key:?} entry")))?;
+
+    let mut entry = archive.by_name(&name)?;
+    match mapped {
+        Some(bytes) if entry.compression() == CompressionMethod::Stored => {
+            let start = entry.data_start() as usize;
+            let end = start + entry.size() as usize;
+            drop(entry);
+            let mut slice = bytes.get(start..end).ok_or_else(|| {
+                LoadError::Format(format!("entry {name:?} extends past the end of the file"))
+            })?;
+            f(&mut slice)
+        }
+        _ => f(&mut entry),
+    }
+}
+
+/// Read an index array entry with a known expected length.
+fn read_index_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    mapped: Option<&[u8]>,
+    key: &str,
+) -> Result<Vec<usize>, LoadError> {
+    with_entry(archive, mapped, key, |reader| {
+        let header = read_header(reader)?;
+        let mut values = Vec::with_capacity(header.len());
+        stream_indices(reader, &header, |v| values.push(v))?;
+        Ok(values)
+    })
+}
+
+/// Load a CSR archive, or a COO one (`row`/`col` instead of
+/// `indptr`/`indices`) converted to CSR.
+fn load_sparse_from_archive<R: Read + Seek, T: ReconFloat>(
+    archive: &mut ZipArchive<R>,
+    mapped: Option<&[u8]>,
+) -> Result<SparseSystemMatrix<T>, LoadError> {
+    let coo = entry_name(archive, "indptr").is_none() && entry_name(archive, "row").is_some();
+    let format = if coo { "COO" } else { "CSR" };
+    let shape = read_index_entry(archive, mapped, "shape")?;
+    let [n_rows, n_cols] = shape[..] else {
+        return Err(LoadError::Format(format!(
+            "{format} shape must have 2 entries, got {shape:?}"
+        )));
+    };
+
+    let (first, second) = if coo {
+        ("row", "col")
+    } else {
+        ("indptr", "indices")
+    };
+    let first = read_index_entry(archive, mapped, first)?;
+    let second = read_index_entry(archive, mapped, second)?;
+    let data = with_entry(archive, mapped, "data", |reader| {
+        let header = read_header(reader)?;
+        let mut values = Vec::with_capacity(header.len());
+        stream_floats(reader, &header, |v| values.push(T::from(v).unwrap()))?;
+        Ok(values)
+    })?;
+
+    if coo {
+        check_coo((n_rows, n_cols), &first, &second, data.len())?;
+        Ok(SparseSystemMatrix::from_coo(
+            (n_rows, n_cols),
+            &first,
+            &second,
+            &data,
+        ))
+    } else {
+        check_csr((n_rows, n_cols), &first, &second, data.len())?;
+        Ok(SparseSystemMatrix::new(
+            (n_rows, n_cols),
+            first,
+            second,
+            data,
+        ))
+    }
+}
+
+/// The checks `SparseSystemMatrix::new` asserts, as errors instead of
+/// panics since the input comes from a file.
+pub(crate) fn check_csr(
+    shape: (usize, usize),
+    indptr: &[usize],
+    indices: &[usize],
+    nnz: usize,
+) -> Result<(), LoadError> {
+    let (n_rows, n_cols) = shape;
+    let consistent = indptr.len() == n_rows + 1
+        && indptr[0] == 0
+        && indptr.windows(2).all(|w| w[0] <= w[1])
+        && indptr[n_rows] == nnz
+        && indices.len() == nnz
+        && indices.iter().all(|&j| j < n_cols);
+    if consistent {
+        Ok(())
+    } else {
+        Err(LoadError::Format(format!(
+            "inconsistent CSR arrays for shape {shape:?} (indptr {}, indices {}, data {})",
+            indptr.len(),
+            indices.len(),
+            nnz
+        )))
+    }
+}
+
+/// The checks `SparseSystemMatrix::from_coo` asserts, as errors.
+fn check_coo(
+    shape: (usize, usize),
+    rows: &[usize],
+    cols: &[usize],
+    nnz: usize,
+) -> Result<(), LoadError> {
+    let (n_rows, n_cols) = shape;
+    let consistent = rows.len() == nnz
+        && cols.len() == nnz
+        && rows.iter().all(|&i| i < n_rows)
+        && cols.iter().all(|&j| j < n_cols);
+    if consistent {
+        Ok(())
+    } else {
+        Err(LoadError::Format(format!(
+            "inconsistent COO arrays for shape {shape:?} (row {}, col {}, data {})",
+            rows.len(),
+            cols.len(),
+            nnz
+        )))
+    }
+}
+
+/// Memory-map `path` read-only.
+fn map_file(path: &Path) -> Result<Mmap, LoadError> {
+    let file = File::open(path)?;
+    // SAFETY: the map is read-only and dropped before returning to the
+    // caller; the file must not be truncated while a load is running.
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+/// Load a scipy-style CSR `.npz` (`indptr`, `indices`, `data`, `shape`)
+/// without intermediate copies of its arrays. A COO `.npz` (`row`, `col`,
+/// `data`, `shape`) is accepted as well and converted to CSR.
+///
+/// With `mmap`, the archive is memory-mapped and uncompressed entries are
+/// decoded in place; otherwise it is read through a buffered file handle.
+pub fn load_csr_npz<T: ReconFloat>(
+    path: &Path,
+    mmap: bool,
+) -> Result<SparseSystemMatrix<T>, LoadError> {
+    if mmap {
+        let map = map_file(path)?;
+        let mut archive = ZipArchive::new(Cursor::new(&map[..]))?;
+        load_sparse_from_archive(&mut archive, Some(&map[..]))
+    } else {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        load_sparse_from_archive(&mut archive, None)
+    }
+}
+
+/// Memory-map a dense 2D `.npy` matrix and convert it to CSR, keeping only
+/// the nonzero entries on the heap.
+pub fn load_dense_npy_as_csr<T: ReconFloat>(
+    path: &Path,
+) -> Result<SparseSystemMatrix<T>, LoadError> {
+    let map = map_file(path)?;
+    let mut reader = &map[..];
+    let header = read_header(&mut reader)?;
+    let [n_rows, n_cols] = header.shape[..] else {
+        return Err(LoadError::Format(format!(
+            "expected a 2D matrix, got shape {:?}",
+            header.shape
+        )));
+    };
+    if header.fortran_order {
+        return Err(LoadError::Format(
+            "Fortran-ordered matrices are not supported".into(),
+        ));
+    }
+    if n_cols == 0 {
+        return Err(LoadError::Format("system matrix has no columns".into()));
+    }
+
+    let mut indptr = Vec::with_capacity(n_rows + 1);
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    indptr.push(0);
+    let mut k = 0;
+    stream_floats(&mut reader, &header, |v| {
+        if v != 0.0 {
+            indices.push(k % n_cols);
+            data.push(T::from(v).unwrap());
+        }
+        k += 1;
+        if k % n_cols == 0 {
+            indptr.push(data.len());
+        }
+    })?;
+
+    Ok(SparseSystemMatrix::new(
+        (n_rows, n_cols),
+        indptr,
+        indices,
+        data,
+    ))
+}
@@ -1,67 +1,2500 @@
-use ndarray::{Array1, Array2, Axis};
+#[cfg(debug_assertions)]
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, Axis, Data, Ix2, Zip};
+use num_traits::Float;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "rayon")]
+use rayon::join;
+
+pub mod builder;
+pub mod complex;
+pub mod diagnostics;
+pub mod error;
+pub mod filter;
+pub mod geometry;
+#[cfg(feature = "hdf5")]
+pub mod h5;
+pub mod io;
+pub mod metrics;
+#[cfg(feature = "mlflow")]
+pub mod mlflow;
+pub mod operator;
+pub mod phantom;
+pub mod precompute;
+pub mod preprocess;
+pub mod quantize;
+pub mod regularization;
+pub mod sparse;
+pub mod spectral;
+pub mod streaming;
+pub mod validation;
+
+pub use builder::{MartBuilder, MartSystemMatrix};
+pub use complex::{mart_reconstruct_complex, mart_reconstruct_sparse_complex, mart_step_complex};
+pub use diagnostics::{
+    estimate_condition, estimate_condition_sparse, estimate_landweber_spectral_radius,
+    estimate_sirt_spectral_radius, safe_relaxation, ConditionEstimate,
+};
+pub use error::ReconError;
+
+pub use geometry::{Geometry, GeometryError, GeometryKind, Roi};
+use operator::masked_sums;
+pub use operator::LinearOperator;
+pub use phantom::{add_noise, phantom, NoiseModel, PhantomKind};
+pub use precompute::Precomputed;
+pub use preprocess::{
+    flat_dark_correct, log_transform, normalize_rows, normalize_rows_sparse, restore_rows,
+    restore_rows_sparse,
+};
+pub use quantize::Quantization;
+pub use regularization::{
+    laplacian, total_variation, HuberRegularization, L2Regularization, TvRegularization,
+};
+pub use sparse::{
+    back_project_sparse, backprojection_sparse, cgls_reconstruct_sparse, forward_project_sparse,
+    mart_reconstruct_sparse, mart_reconstruct_sparse_blocked,
+    mart_reconstruct_sparse_blocked_with_callback, mart_reconstruct_sparse_report,
+    mart_reconstruct_sparse_with_callback, mart_step_sparse, mart_step_sparse_rows,
+    SparseSystemMatrix,
+};
+pub use spectral::{
+    mart_reconstruct_sparse_spectral, mart_reconstruct_spectral, reconstruct_channels,
+    BasisDecomposition,
+};
+pub use streaming::MartState;
+use validation::{
+    check_initial_guess, check_mart_inputs, check_mask, check_projections, check_ray_weights,
+    check_voxel_weights,
+};
+pub use validation::{
+    sanitize_inputs, sanitize_inputs_sparse, validate_system_matrix, validate_system_matrix_sparse,
+    SanitizeReport, SystemMatrixIssues,
+};
+
+/// Floating-point element type accepted by the solvers (`f32` or `f64`).
+///
+/// This is `num_traits::Float` plus the bounds ndarray and rayon need; it is
+/// implemented automatically for every such type.
+pub trait ReconFloat: Float + Debug + Send + Sync + 'static {}
+
+impl<T: Float + Debug + Send + Sync + 'static> ReconFloat for T {}
+
+/// Options shared by the reconstruction loops.
+///
+/// `ReconOptions::default()` reproduces the plain, unconstrained solvers.
+#[derive(Debug, Clone, Default)]
+pub struct ReconOptions {
+    /// After each iteration, set any negative voxel to 0.0.
+    ///
+    /// Additive methods (ART/SIRT) can overshoot below zero, which is
+    /// unphysical for attenuation. MART stays positive on its own, so the
+    /// clamp is a no-op there.
+    pub clamp_nonnegative: bool,
+
+    /// Stop early once the relative L2 residual `||A*x - y|| / ||y||`
+    /// changes by less than this between consecutive iterations.
+    ///
+    /// `None` always runs the full `n_iters`.
+    pub tolerance: Option<f64>,
+
+    /// Stop early once an iteration changes the volume by less than this,
+    /// relative to its size: `||x_new - x_old|| / ||x_old|| < tol`.
+    ///
+    /// Independent of `tolerance`: either, both or neither can be set, and
+    /// the run stops on whichever fires first (`ReconReport::stopped_by`
+    /// says which). Unlike the residual, the volume change keeps shrinking
+    /// on inconsistent data whose residual plateaus early. `None` disables
+    /// the check.
+    pub volume_tolerance: Option<f64>,
+
+    /// Order in which MART visits the rays within each iteration.
+    pub row_order: RowOrder,
+
+    /// Tikhonov smoothing applied after each pass, before the
+    /// non-negativity clamp.
+    pub l2_regularization: Option<L2Regularization>,
+
+    /// Total-variation denoising applied every `every` passes, after the L2
+    /// and Huber smoothing and before the non-negativity clamp.
+    pub tv_regularization: Option<TvRegularization>,
+
+    /// Edge-preserving Huber smoothing applied after each pass, after the L2
+    /// smoothing.
+    pub huber_regularization: Option<HuberRegularization>,
+
+    /// Volume the first iteration starts from.
+    pub initial_guess: InitialGuess,
+
+    /// Clamp every voxel into `[lo, hi]` after each pass (after the
+    /// non-negativity clamp). Use an infinite end for a one-sided bound.
+    ///
+    /// Known attenuation limits of the imaged material keep MART from
+    /// overshooting on under-determined data and speed up convergence.
+    pub bounds: Option<(f64, f64)>,
+
+    /// Column-weighted MART (MART solvers only): voxel `j` is scaled by
+    /// `ratio^(relaxation * A_ij / colsum_j)` instead of `ratio^relaxation`.
+    ///
+    /// With plain MART every ray hitting a voxel applies the full factor, so
+    /// voxels crossed by many or long rays are updated far more strongly
+    /// than sparsely sampled ones. On non-uniform geometries (limited angle,
+    /// fan beam, truncated detectors) that shows up as overshoot and streaks
+    /// along the densely sampled directions. Weighting by the column sum
+    /// bounds each voxel's total exponent per pass by `relaxation`, which
+    /// evens out the update strength and damps noise amplification. Each
+    /// pass moves less, so expect to need more iterations or a relaxation
+    /// closer to 1.
+    pub column_weighted: bool,
+
+    /// Per-iteration relaxation; overrides the solver's `relaxation`
+    /// argument when set.
+    pub relaxation_schedule: Option<RelaxationSchedule>,
+
+    /// Number of iterations already performed, when resuming from a
+    /// checkpoint.
+    ///
+    /// The solver then runs `n_iters` more passes numbered from
+    /// `start_iteration`, so relaxation schedules, the TV cadence, shuffled
+    /// row orders and callback indices continue where the interrupted run
+    /// stopped. Start from the checkpointed volume via `initial_guess`.
+    pub start_iteration: usize,
+
+    /// Voxels to reconstruct (length N); `false` entries, e.g. air outside
+    /// the reconstruction circle, are fixed at zero.
+    ///
+    /// Masked voxels start at zero, are reset to zero after every pass (so
+    /// regularization and bounds cannot revive them), and are left out of
+    /// the ray sums and updates: MART's multiplicative update keeps them at
+    /// zero by itself, and ART and SIRT restrict their row norms and updates
+    /// to the unmasked voxels.
+    pub mask: Option<Array1<bool>>,
+
+    /// Stop as diverged once the residual has risen for this many
+    /// consecutive iterations.
+    ///
+    /// A volume with NaN or infinite voxels always counts as diverged; this
+    /// adds the slower failure mode of an overly aggressive relaxation,
+    /// where the residual grows without ever overflowing. `None` disables
+    /// the check. Regularization can legitimately raise the residual a
+    /// little, so keep the patience generous when it is enabled.
+    pub patience: Option<usize>,
+
+    /// Finish the current iteration and return once this flag is set, e.g.
+    /// from a signal handler.
+    ///
+    /// The flag is checked after every iteration (and its constraints and
+    /// callback); the report of an interrupted run has `interrupted` set
+    /// and holds the volume of the last completed iteration.
+    pub stop: Option<Arc<AtomicBool>>,
+
+    /// MART skips a ray whose estimated projection `y_hat` is at or below
+    /// this value.
+    ///
+    /// The default of 0.0 only guards against division by zero; on data
+    /// with tiny but positive `y_hat` (rays grazing the volume edge) a
+    /// small positive floor keeps `y / y_hat` from exploding.
+    pub floor_eps: f64,
+
+    /// Clamp MART's ratio `y / y_hat` into `[lo, hi]` before it is raised to
+    /// the relaxation, so a single ray cannot scale a voxel by more than
+    /// `hi^relaxation` (or less than `lo^relaxation`) in one update.
+    pub ratio_clamp: Option<(f64, f64)>,
+
+    /// MART leaves the volume alone for a ray whose factor
+    /// `ratio^relaxation` is within this distance of 1.0.
+    ///
+    /// Late in convergence most rays are already explained and their
+    /// update multiplies every touched voxel by almost exactly one; a small
+    /// positive value (e.g. 1e-6) skips that work. `y_hat` is still
+    /// computed for every ray, so the residual and stopping rule are
+    /// unaffected and the saving is at most the voxel-update half of each
+    /// sweep (see the `mart_skip` benchmark). The default of 0.0 never
+    /// skips. SMART ignores it.
+    pub skip_eps: f64,
+
+    /// Worker threads for the `rayon` build; `None` uses rayon's global
+    /// pool (one thread per core unless `RAYON_NUM_THREADS` says otherwise).
+    /// Ignored without the `rayon` feature.
+    ///
+    /// The solver runs in a pool of this size that lives for the duration of
+    /// the call. `Some(1)` takes the serial kernels and reproduces the
+    /// non-rayon build bit for bit. With more threads the per-ray dot
+    /// product is reduced in chunks whose boundaries depend on the thread
+    /// count and on work stealing, and since floating-point addition is not
+    /// associative, results can differ in the last bits between runs and
+    /// thread counts; set `deterministic` to rule that out.
+    pub threads: Option<usize>,
+
+    /// Reduce MART's per-ray dot products with a fixed pairwise tree, so the
+    /// volume is bit-identical for every thread count (and between rayon and
+    /// non-rayon builds).
+    ///
+    /// The tree splits each row at the same points whatever the pool size;
+    /// only which thread sums which half changes. The order differs from
+    /// the default kernel, so deterministic results agree with
+    /// non-deterministic ones to rounding only.
+    pub deterministic: bool,
+
+    /// Run the ordered subsets of `os_mart_reconstruct` concurrently
+    /// (block-Jacobi) instead of one after another.
+    ///
+    /// Every subset sweeps its own copy of the volume from the start of the
+    /// iteration, with rayon in parallel, and the copies are merged into one
+    /// update per voxel: the geometric mean of the subsets' factors
+    /// `x_s,j / x_j`, weighted by each subset's share of the column sum, so
+    /// a voxel only one subset sees takes that subset's update in full.
+    /// Subsets no longer see each other's corrections within an iteration,
+    /// so this trades convergence per iteration for wall-clock time: expect
+    /// a higher residual for the same iteration count (see the
+    /// `os_mart_parallel` benchmark). Ignored by the other solvers.
+    pub parallel_subsets: bool,
+
+    /// Per-ray confidence (length M, non-negative) for the MART solvers:
+    /// ray `i` is applied with relaxation `relaxation * ray_weights[i]`.
+    ///
+    /// Lower the weight of noisy detector pixels and set it to 0 for dead
+    /// ones, which drops those rays. The residual is still measured over
+    /// all rays.
+    pub ray_weights: Option<Array1<f64>>,
+
+    /// Per-voxel scale (length N, non-negative) of the multiplicative
+    /// exponent for the MART solvers and SMART: voxel `j` is updated with
+    /// `ratio^(relaxation * voxel_weights[j])`.
+    ///
+    /// On an anisotropic grid, weighting by the voxel's physical extent
+    /// along the dominant ray direction (or any other per-axis model laid out
+    /// with the volume shape) keeps long voxels from being over-corrected. A
+    /// weight of 0 freezes the voxel at its initial value, unlike `mask`,
+    /// which fixes it at zero. With `column_weighted` the two scales
+    /// multiply, giving the exponent `relaxation * A_ij / colsum_j *
+    /// voxel_weights[j]`, so each voxel's total exponent per pass is bounded
+    /// by `relaxation * voxel_weights[j]` rather than `relaxation`.
+    pub voxel_weights: Option<Array1<f64>>,
+
+    /// Pick the MART relaxation automatically (see `AutoRelaxation`);
+    /// overrides the `relaxation` argument and `relaxation_schedule`.
+    pub auto_relaxation: Option<AutoRelaxation>,
+}
+
+/// Relaxation as a function of the iteration index.
+///
+/// Large early relaxation speeds up convergence, small late relaxation
+/// keeps noise from being fitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelaxationSchedule {
+    /// The same relaxation every iteration.
+    Constant(f64),
+    /// Straight line from `start` (first iteration) to `end` (last).
+    Linear { start: f64, end: f64 },
+    /// `start * factor^iter`.
+    Geometric { start: f64, factor: f64 },
+}
+
+impl RelaxationSchedule {
+    /// Relaxation for the 0-based iteration `iter` of `n_iters`.
+    pub fn at(&self, iter: usize, n_iters: usize) -> f64 {
+        match *self {
+            RelaxationSchedule::Constant(value) => value,
+            RelaxationSchedule::Linear { start, end } => {
+                if n_iters <= 1 {
+                    start
+                } else {
+                    start + (end - start) * iter as f64 / (n_iters - 1) as f64
+                }
+            }
+            RelaxationSchedule::Geometric { start, factor } => start * factor.powi(iter as i32),
+        }
+    }
+}
+
+/// Relaxation chosen by a line search over held-out rays (MART solvers
+/// only).
+///
+/// A seeded random `holdout` fraction of the rays is set aside. At the start
+/// of every block of `every` iterations, each candidate relaxation is tried
+/// with one MART pass over the remaining rays on a copy of the volume, and
+/// the candidate giving the lowest relative residual on the held-out rays is
+/// used for the block. If every candidate raises the held-out residual, the
+/// current relaxation (initially the solver's `relaxation` argument) is
+/// kept. The held-out rays only steer the choice; the iterations themselves
+/// still use every ray.
+///
+/// Each search costs one extra pass per candidate. Trial passes skip
+/// regularization and other constraints, and a resumed run (see
+/// `ReconOptions::start_iteration`) starts searching afresh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoRelaxation {
+    /// Relaxation values to try.
+    pub candidates: Vec<f64>,
+    /// Iterations per block; the search runs before each block.
+    pub every: usize,
+    /// Fraction of the rays held out to score the candidates, in (0, 1).
+    pub holdout: f64,
+    /// Seed for choosing the held-out rays.
+    pub seed: u64,
+}
+
+impl Default for AutoRelaxation {
+    fn default() -> Self {
+        AutoRelaxation {
+            candidates: vec![0.05, 0.1, 0.25, 0.5, 1.0],
+            every: 5,
+            holdout: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// Runs the `AutoRelaxation` search and remembers the current choice.
+pub(crate) struct RelaxationTuner<T> {
+    candidates: Vec<T>,
+    every: usize,
+    fit_rows: Vec<usize>,
+    held_out: Vec<usize>,
+    current: T,
+    iteration: usize,
+}
+
+impl<T: ReconFloat> RelaxationTuner<T> {
+    pub(crate) fn new(auto: &AutoRelaxation, m: usize, relaxation: T) -> Self {
+        let mut rows: Vec<usize> = (0..m).collect();
+        rows.shuffle(&mut ChaCha8Rng::seed_from_u64(auto.seed));
+        // keep at least one ray on each side
+        let n_held_out =
+            ((auto.holdout * m as f64).ceil() as usize).clamp(1.min(m), m.saturating_sub(1));
+        let mut held_out = rows.split_off(m - n_held_out);
+        rows.sort_unstable();
+        held_out.sort_unstable();
+        Self {
+            candidates: auto
+                .candidates
+                .iter()
+                .map(|&c| T::from(c).unwrap())
+                .collect(),
+            every: auto.every.max(1),
+            fit_rows: rows,
+            held_out,
+            current: relaxation,
+            iteration: 0,
+        }
+    }
+
+    /// Relaxation for the next iteration, searching first at the start of
+    /// a block.
+    ///
+    /// `trial(volume, rows, relaxation)` runs one pass over `rows`;
+    /// `held_out_residual(volume, rows)` scores a volume on `rows`.
+    pub(crate) fn next(
+        &mut self,
+        volume: &Array1<T>,
+        mut trial: impl FnMut(&mut Array1<T>, &[usize], T),
+        held_out_residual: impl Fn(&Array1<T>, &[usize]) -> T,
+    ) -> T {
+        if self.iteration.is_multiple_of(self.every) && !self.held_out.is_empty() {
+            let mut best = held_out_residual(volume, &self.held_out);
+            for &candidate in &self.candidates {
+                let mut trial_volume = volume.clone();
+                trial(&mut trial_volume, &self.fit_rows, candidate);
+                let score = held_out_residual(&trial_volume, &self.held_out);
+                if score < best {
+                    best = score;
+                    self.current = candidate;
+                }
+            }
+        }
+        self.iteration += 1;
+        self.current
+    }
+}
+
+/// Relative L2 residual restricted to `rows`, with `y_hat(i)` the estimated
+/// projection of ray `i`.
+pub(crate) fn relative_l2_rows<T: ReconFloat>(
+    projections: &Array1<T>,
+    rows: &[usize],
+    y_hat: impl Fn(usize) -> T,
+) -> T {
+    let (mut num, mut den) = (T::zero(), T::zero());
+    for &i in rows {
+        let diff = y_hat(i) - projections[i];
+        num = num + diff * diff;
+        den = den + projections[i] * projections[i];
+    }
+    if den > T::zero() {
+        (num / den).sqrt()
+    } else {
+        num.sqrt()
+    }
+}
+
+/// Starting volume for a reconstruction.
+///
+/// MART updates are multiplicative, so voxels that start at zero stay at
+/// zero; pick a positive start for MART.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitialGuess {
+    /// Every voxel starts at this value (default 1.0).
+    Uniform(f64),
+    /// Start from a given volume (length N), e.g. a previous reconstruction.
+    FromArray(Array1<f64>),
+    /// Start from the normalized backprojection (see `backprojection`).
+    Backprojection,
+}
+
+impl Default for InitialGuess {
+    fn default() -> Self {
+        InitialGuess::Uniform(1.0)
+    }
+}
+
+/// Forward projection `y = A * x`: the projections `volume` would produce.
+///
+/// Use it to simulate data from a phantom or to check how well a
+/// reconstruction explains the measurements.
+pub fn forward_project<T: ReconFloat, S: Data<Elem = T>>(
+    system_matrix: &ArrayBase<S, Ix2>,
+    volume: &Array1<T>,
+) -> Array1<T> {
+    assert_eq!(
+        volume.len(),
+        system_matrix.dim().1,
+        "volume must have length N"
+    );
+    system_matrix.dot(volume)
+}
+
+/// Backprojection `A^T * y`: smears each projection value back along its
+/// ray (length N).
+///
+/// The result is unnormalized, so voxels crossed by many or long rays get
+/// large values; see `backprojection` for the normalized warm start.
+pub fn back_project<T: ReconFloat, S: Data<Elem = T>>(
+    system_matrix: &ArrayBase<S, Ix2>,
+    projections: &Array1<T>,
+) -> Array1<T> {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    system_matrix.t().dot(projections)
+}
+
+/// Per-ray residual `A * x - y` of `volume` (length M), e.g. to see which
+/// rays a volume fails to explain.
+pub fn residual_vector<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    volume: &Array1<T>,
+) -> Array1<T> {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    system_matrix.forward(volume) - projections
+}
+
+/// Relative L2 residual `||A * x - y||_2 / ||y||_2` of `volume`, or the
+/// absolute norm `||A * x - y||_2` when `y` is all zeros.
+///
+/// This is the residual every solver reports after each iteration
+/// (`ReconReport::residual_history`, `final_residual`) and that
+/// `ReconOptions::tolerance` and `patience` watch, so QA checks on any
+/// volume use the same definition as the stopping rule.
+pub fn relative_residual<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    volume: &Array1<T>,
+) -> T {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    relative_l2(projections, &system_matrix.forward(volume))
+}
+
+/// Build the starting volume of length `n` described by `options`.
+///
+/// `backproject` is only called for `InitialGuess::Backprojection`, so
+/// solvers can pass their own (dense or sparse) implementation.
+pub(crate) fn initial_volume<T: ReconFloat>(
+    options: &ReconOptions,
+    n: usize,
+    backproject: impl FnOnce() -> Array1<T>,
+) -> Array1<T> {
+    let mut volume = match &options.initial_guess {
+        InitialGuess::Uniform(value) => Array1::from_elem(n, T::from(*value).unwrap()),
+        InitialGuess::FromArray(volume) => {
+            assert_eq!(volume.len(), n, "initial volume must have length N");
+            volume.mapv(|v| T::from(v).unwrap())
+        }
+        InitialGuess::Backprojection => backproject(),
+    };
+    if let Some(mask) = &options.mask {
+        assert_eq!(mask.len(), n, "mask must have length N");
+        apply_mask(&mut volume, mask);
+    }
+    volume
+}
+
+/// Zero every voxel outside `mask`.
+pub(crate) fn apply_mask<T: ReconFloat>(volume: &mut Array1<T>, mask: &Array1<bool>) {
+    for (v, &keep) in volume.iter_mut().zip(mask) {
+        if !keep {
+            *v = T::zero();
+        }
+    }
+}
+
+/// Normalized backprojection used as a warm start:
+///
+///   x_j = sum_i A_ij * (y_i / rowsum_i) / colsum_j
+///
+/// Dividing by the row sums first means a uniform object backprojects to
+/// itself instead of being scaled by the ray lengths. Voxels with a zero
+/// column sum (or rays with a zero row sum) contribute 0.
+pub fn backprojection<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+) -> Array1<T> {
+    assert_eq!(projections.len(), system_matrix.dim().0);
+    let weighted = normalize_by(projections, &system_matrix.row_sums());
+    normalize_by(
+        &system_matrix.adjoint(&weighted),
+        &system_matrix.column_sums(),
+    )
+}
+
+/// Element-wise `values / sums`, with 0 wherever the sum is not positive.
+pub(crate) fn normalize_by<T: ReconFloat>(values: &Array1<T>, sums: &Array1<T>) -> Array1<T> {
+    Array1::from_shape_fn(values.len(), |k| {
+        if sums[k] > T::zero() {
+            values[k] / sums[k]
+        } else {
+            T::zero()
+        }
+    })
+}
+
+/// Order in which rows (rays) are visited during a MART sweep.
+///
+/// MART's result depends on the ray order, and a fixed sequential order can
+/// leave directional streaks. `Shuffled` draws a fresh permutation every
+/// iteration from a ChaCha8 RNG seeded with `seed`, so runs with the same
+/// seed are bit-for-bit reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RowOrder {
+    /// Visit rays `0..M` in index order.
+    #[default]
+    Sequential,
+    /// Visit rays in a seeded random order, reshuffled each iteration.
+    Shuffled { seed: u64 },
+}
+
+/// Produces the per-iteration row order described by a `RowOrder`.
+pub(crate) struct RowSchedule {
+    order: Vec<usize>,
+    rng: Option<ChaCha8Rng>,
+}
+
+impl RowSchedule {
+    /// Schedule positioned after `skip` iterations, so a resumed run draws
+    /// the same shuffles as an uninterrupted one.
+    pub(crate) fn new(m: usize, row_order: RowOrder, skip: usize) -> Self {
+        let rng = match row_order {
+            RowOrder::Sequential => None,
+            RowOrder::Shuffled { seed } => Some(ChaCha8Rng::seed_from_u64(seed)),
+        };
+        let mut schedule = Self {
+            order: (0..m).collect(),
+            rng,
+        };
+        if schedule.rng.is_some() {
+            for _ in 0..skip {
+                schedule.next_order();
+            }
+        }
+        schedule
+    }
+
+    /// Row order for the next iteration.
+    pub(crate) fn next_order(&mut self) -> &[usize] {
+        if let Some(rng) = self.rng.as_mut() {
+            self.order.shuffle(rng);
+        }
+        &self.order
+    }
+}
+
+/// Stopping criterion that ended a converged run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopCriterion {
+    /// The residual changed by less than `ReconOptions::tolerance`.
+    Residual,
+    /// The volume changed by less than `ReconOptions::volume_tolerance`.
+    VolumeChange,
+}
+
+/// Outcome of a reconstruction run.
+#[derive(Debug, Clone)]
+pub struct ReconReport<T> {
+    /// Reconstructed volume (length N).
+    pub volume: Array1<T>,
+    /// Number of iterations actually performed (at most `n_iters`).
+    pub iterations_run: usize,
+    /// Relative L2 residual `||A*x - y|| / ||y||` of the returned volume.
+    pub final_residual: T,
+    /// Residual after each iteration (length `iterations_run`).
+    pub residual_history: Vec<T>,
+    /// True if a tolerance in `ReconOptions` stopped the loop; always
+    /// false when no tolerance is set.
+    pub converged: bool,
+    /// Criterion that stopped a converged run; `None` unless `converged`.
+    /// When both fire on the same iteration the residual is reported.
+    pub stopped_by: Option<StopCriterion>,
+    /// 0-based iteration after which the run was stopped as diverged (see
+    /// `ReconOptions::patience`); `volume` is the diverged volume.
+    pub diverged_at: Option<usize>,
+    /// True if `ReconOptions::stop` ended the run before `n_iters`.
+    pub interrupted: bool,
+}
+
+impl<T: ReconFloat> ReconReport<T> {
+    /// `ReconError::Diverged` if the run diverged, otherwise the report.
+    pub fn check_diverged(self) -> Result<Self, ReconError> {
+        match self.diverged_at {
+            Some(iteration) => Err(ReconError::Diverged {
+                iteration,
+                residual: self.final_residual.to_f64().unwrap(),
+            }),
+            None => Ok(self),
+        }
+    }
+}
+
+/// Apply the regularization and constraints from `options` to `volume`
+/// after the 0-based pass `iter`. The mask goes last so nothing undoes it.
+pub(crate) fn apply_constraints<T: ReconFloat>(
+    volume: &mut Array1<T>,
+    iter: usize,
+    options: &ReconOptions,
+) {
+    if let Some(reg) = &options.l2_regularization {
+        reg.apply(volume);
+    }
+    if let Some(huber) = &options.huber_regularization {
+        huber.apply(volume);
+    }
+    if let Some(tv) = options.tv_regularization.filter(|tv| tv.is_due(iter)) {
+        tv.apply(volume);
+    }
+    if options.clamp_nonnegative {
+        volume.mapv_inplace(|v| v.max(T::zero()));
+    }
+    if let Some((lo, hi)) = options.bounds {
+        let (lo, hi) = (T::from(lo).unwrap(), T::from(hi).unwrap());
+        volume.mapv_inplace(|v| v.max(lo).min(hi));
+    }
+    if let Some(mask) = &options.mask {
+        apply_mask(volume, mask);
+    }
+}
+
+/// Relative L2 residual `||y_hat - y|| / ||y||` of an estimated projection
+/// (see `relative_residual`).
+///
+/// Falls back to the absolute norm when `y` is all zeros.
+pub(crate) fn relative_l2<T: ReconFloat>(projections: &Array1<T>, y_hat: &Array1<T>) -> T {
+    let mut diff_sq = T::zero();
+    let mut norm_sq = T::zero();
+    for (&y, &y_est) in projections.iter().zip(y_hat) {
+        diff_sq = diff_sq + (y_est - y) * (y_est - y);
+        norm_sq = norm_sq + y * y;
+    }
+
+    if norm_sq > T::zero() {
+        (diff_sq / norm_sq).sqrt()
+    } else {
+        diff_sq.sqrt()
+    }
+}
+
+/// Shared outer loop for all solvers.
+///
+/// Runs `step` up to `n_iters` times, passing it the iteration index and
+/// the relaxation for that pass (`relaxation`, or the schedule in
+/// `options`), and applies
+/// `options`' constraints after every pass. After each pass the relative
+/// residual is evaluated with `residual` and handed to `callback` together
+/// with the 0-based iteration index and the current volume. When a
+/// tolerance is set, the loop stops once the residual changes by less than
+/// the tolerance, and with a volume tolerance once the relative change of
+/// the volume drops below it (whichever comes first); it also stops,
+/// recording `diverged_at`, when the volume stops being finite or the
+/// residual rises for `options.patience` iterations. Iteration indices
+/// start at `options.start_iteration`.
+pub(crate) fn run_iterations<T: ReconFloat>(
+    mut volume: Array1<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    mut step: impl FnMut(usize, &mut Array1<T>, T) + Send,
+    residual: impl Fn(&Array1<T>) -> T + Sync,
+    mut callback: impl FnMut(usize, &Array1<T>, T),
+) -> ReconReport<T> {
+    let threads = ThreadScope::new(options);
+    let tolerance = options.tolerance.map(|tol| T::from(tol).unwrap());
+    let volume_tolerance = options.volume_tolerance.map(|tol| T::from(tol).unwrap());
+    let mut history: Vec<T> = Vec::with_capacity(n_iters);
+    let mut stopped_by = None;
+    let mut diverged_at = None;
+    let mut interrupted = false;
+    let mut rising = 0;
+
+    let start = options.start_iteration;
+    let total = start + n_iters;
+    for iter in start..total {
+        let relaxation = match &options.relaxation_schedule {
+            Some(schedule) => T::from(schedule.at(iter, total)).unwrap(),
+            None => relaxation,
+        };
+        // only kept when the volume tolerance needs it
+        let before = volume_tolerance.map(|_| volume.clone());
+        threads.run(|| step(iter, &mut volume, relaxation));
+        apply_constraints(&mut volume, iter, options);
+
+        let current = threads.run(|| residual(&volume));
+        callback(iter, &volume, current);
+
+        let previous = history.last().copied();
+        history.push(current);
+
+        rising = if previous.is_some_and(|prev| current > prev) {
+            rising + 1
+        } else {
+            0
+        };
+        if volume.iter().any(|v| !v.is_finite())
+            || options.patience.is_some_and(|patience| rising >= patience)
+        {
+            diverged_at = Some(iter);
+            break;
+        }
+        if tolerance.is_some_and(|tol| previous.is_some_and(|prev| (prev - current).abs() < tol)) {
+            stopped_by = Some(StopCriterion::Residual);
+            break;
+        }
+        if let (Some(tol), Some(before)) = (volume_tolerance, &before) {
+            // ||x_new - x_old|| / ||x_old||, absolute from an all-zero start
+            if relative_l2(before, &volume) < tol {
+                stopped_by = Some(StopCriterion::VolumeChange);
+                break;
+            }
+        }
+        if iter + 1 < total
+            && options
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed))
+        {
+            interrupted = true;
+            break;
+        }
+    }
+
+    let final_residual = match history.last() {
+        Some(&last) => last,
+        None => threads.run(|| residual(&volume)),
+    };
+    ReconReport {
+        volume,
+        iterations_run: history.len(),
+        final_residual,
+        residual_history: history,
+        converged: stopped_by.is_some(),
+        stopped_by,
+        diverged_at,
+        interrupted,
+    }
+}
+
+/// Thread pool a solver runs its passes in (see `ReconOptions::threads`).
+///
+/// Only the step and residual closures run inside the pool; callbacks stay
+/// on the calling thread.
+struct ThreadScope {
+    #[cfg(feature = "rayon")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl ThreadScope {
+    #[cfg(feature = "rayon")]
+    fn new(options: &ReconOptions) -> Self {
+        let pool = options.threads.map(|n| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .expect("failed to start the reconstruction thread pool")
+        });
+        ThreadScope { pool }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn new(_options: &ReconOptions) -> Self {
+        ThreadScope {}
+    }
+
+    #[cfg(feature = "rayon")]
+    fn run<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn run<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        op()
+    }
+}
 
 /// Perform one MART iteration over all rays.
 ///
-/// projections:  length M (measured y)
+/// projections:  length M (measured y, non-negative)
 /// system_matrix: shape (M, N) (A)
 /// volume:      length N (x)
 /// relaxation:  relaxation parameter (lambda)
-pub fn mart_step(
-    projections: &Array1<f32>,
-    system_matrix: &Array2<f32>,
-    volume: &mut Array1<f32>,
-    relaxation: f32,
+///
+/// Returns an error instead of panicking when the inputs do not match the
+/// matrix or contain negative or non-finite values.
+pub fn mart_step<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+) -> Result<(), ReconError> {
+    check_mart_inputs(projections, system_matrix.dim(), Some(volume))?;
+    for i in 0..system_matrix.dim().0 {
+        mart_update_ray(
+            projections,
+            system_matrix,
+            i,
+            volume,
+            relaxation,
+            0,
+            &RayUpdate::default(),
+        );
+    }
+    Ok(())
+}
+
+/// `mart_step` without input checks; panics on a dimension mismatch.
+pub fn mart_step_unchecked<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
 ) {
     let (m, n) = system_matrix.dim();
     assert_eq!(projections.len(), m);
     assert_eq!(volume.len(), n);
 
     for i in 0..m {
-        let row = system_matrix.index_axis(Axis(0), i); // A_i*
+        mart_update_ray(
+            projections,
+            system_matrix,
+            i,
+            volume,
+            relaxation,
+            0,
+            &RayUpdate::default(),
+        );
+    }
+}
+
+/// Perform one MART iteration visiting the rays in `rows` order.
+///
+/// `rows` is typically a permutation of `0..M` (see `RowOrder`); rays not
+/// listed are skipped for this iteration.
+pub fn mart_step_rows<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    rows: &[usize],
+    volume: &mut Array1<T>,
+    relaxation: T,
+) -> Result<(), ReconError> {
+    let m = system_matrix.dim().0;
+    check_mart_inputs(projections, system_matrix.dim(), Some(volume))?;
+    if let Some(&index) = rows.iter().find(|&&i| i >= m) {
+        return Err(ReconError::RowOutOfRange { index, rows: m });
+    }
+
+    mart_sweep(
+        projections,
+        system_matrix,
+        rows,
+        volume,
+        relaxation,
+        0,
+        None,
+        &RayUpdate::default(),
+    );
+    Ok(())
+}
+
+/// MART sweep over `rows` without checks (inputs validated by the caller).
+///
+/// `iter` is the iteration the sweep belongs to, reported by the debug-build
+/// checks. `voxel_scale` selects the column- and/or voxel-weighted update (see
+/// `VoxelScale`).
+#[allow(clippy::too_many_arguments)]
+fn mart_sweep<T: ReconFloat, S: Data<Elem = T>>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    rows: &[usize],
+    volume: &mut Array1<T>,
+    relaxation: T,
+    iter: usize,
+    voxel_scale: Option<&VoxelScale<T>>,
+    ray: &RayUpdate<T>,
+) {
+    for &i in rows {
+        let Some(relaxation) = ray.relaxation(i, relaxation) else {
+            continue;
+        };
+        match voxel_scale {
+            None => mart_update_ray(projections, system_matrix, i, volume, relaxation, iter, ray),
+            Some(scale) => mart_update_ray_weighted(
+                projections,
+                system_matrix,
+                i,
+                volume,
+                relaxation,
+                iter,
+                scale,
+                ray,
+            ),
+        }
+    }
+}
+
+/// Per-voxel factors of the MART exponent, combining
+/// `ReconOptions::column_weighted` and `ReconOptions::voxel_weights`.
+#[derive(Debug, Clone)]
+pub(crate) enum VoxelScale<T> {
+    /// `x_j *= ratio^(relaxation * w_j)` (voxel weights only).
+    Voxel(Array1<T>),
+    /// `x_j *= ratio^(relaxation * A_ij * w_j)`, where `w_j` is `1 /
+    /// colsum_j` times the voxel weight, if any.
+    Column(Array1<T>),
+}
+
+impl<T: ReconFloat> VoxelScale<T> {
+    /// The scale `options` ask for, or `None` for plain MART. `col_sums` is
+    /// only called for column-weighted MART.
+    pub(crate) fn new(
+        options: &ReconOptions,
+        n: usize,
+        col_sums: impl FnOnce() -> Array1<T>,
+    ) -> Option<Self> {
+        let voxel_weights = options.voxel_weights.as_ref().map(|weights| {
+            assert_eq!(weights.len(), n, "voxel_weights must have length N");
+            weights.mapv(|w| T::from(w).unwrap())
+        });
+        match (options.column_weighted, voxel_weights) {
+            (false, None) => None,
+            (false, Some(weights)) => Some(VoxelScale::Voxel(weights)),
+            (true, None) => Some(VoxelScale::Column(inverse_column_sums(&col_sums()))),
+            (true, Some(weights)) => Some(VoxelScale::Column(
+                inverse_column_sums(&col_sums()) * &weights,
+            )),
+        }
+    }
+}
+
+/// Per-ray settings of the MART update (`ReconOptions::floor_eps`,
+/// `ratio_clamp`, `skip_eps`, `ray_weights` and `deterministic`), converted
+/// to the solver's element type.
+#[derive(Debug, Clone)]
+pub(crate) struct RayUpdate<T> {
+    floor: T,
+    clamp: Option<(T, T)>,
+    skip: T,
+    weights: Option<Array1<T>>,
+    deterministic: bool,
+}
+
+impl<T: ReconFloat> RayUpdate<T> {
+    pub(crate) fn new(options: &ReconOptions) -> Self {
+        let cast = |v: f64| T::from(v).unwrap();
+        Self {
+            floor: cast(options.floor_eps),
+            clamp: options.ratio_clamp.map(|(lo, hi)| (cast(lo), cast(hi))),
+            skip: cast(options.skip_eps),
+            weights: options
+                .ray_weights
+                .as_ref()
+                .map(|weights| weights.mapv(cast)),
+            deterministic: options.deterministic,
+        }
+    }
+
+    /// Estimated projection `y_hat_i = sum_j A_ij * x_j` of one row.
+    pub(crate) fn y_hat(&self, row: ArrayView1<T>, volume: &Array1<T>) -> T {
+        if self.deterministic {
+            pairwise_dot(row, volume.view())
+        } else {
+            row_dot(row, volume)
+        }
+    }
 
-        // estimated projection: y_hat_i = sum_j A_ij * x_j
-        let mut y_hat = 0.0f32;
-        for j in 0..n {
-            y_hat += row[j] * volume[j];
+    /// Relaxation for ray `i`, scaled by its weight; `None` for rays with
+    /// weight 0, which are skipped.
+    pub(crate) fn relaxation(&self, i: usize, relaxation: T) -> Option<T> {
+        match &self.weights {
+            Some(weights) if weights[i] == T::zero() => None,
+            Some(weights) => Some(relaxation * weights[i]),
+            None => Some(relaxation),
         }
+    }
 
-        if y_hat <= 0.0 {
+    /// `y / y_hat` clamped into the configured range, or `None` if the ray
+    /// should be skipped.
+    pub(crate) fn ratio(&self, y: T, y_hat: T) -> Option<T> {
+        if y_hat <= self.floor {
             // avoid division by zero / nonsense updates
-            continue;
+            return None;
+        }
+        let ratio = y / y_hat;
+        Some(match self.clamp {
+            Some((lo, hi)) => ratio.max(lo).min(hi),
+            None => ratio,
+        })
+    }
+
+    /// Whether a ray's MART factor `ratio^relaxation` is too close to 1.0 to
+    /// be worth applying.
+    pub(crate) fn negligible(&self, factor: T) -> bool {
+        (factor - T::one()).abs() < self.skip
+    }
+}
+
+impl<T: ReconFloat> Default for RayUpdate<T> {
+    /// Skip only rays with `y_hat <= 0`, no clamping, skipping or weights.
+    fn default() -> Self {
+        Self {
+            floor: T::zero(),
+            clamp: None,
+            skip: T::zero(),
+            weights: None,
+            deterministic: false,
+        }
+    }
+}
+
+/// `1 / colsum_j` for every column (0 for empty columns), used by the
+/// column-weighted MART update.
+pub(crate) fn inverse_column_sums<T: ReconFloat>(col_sums: &Array1<T>) -> Array1<T> {
+    normalize_by(&Array1::from_elem(col_sums.len(), T::one()), col_sums)
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// Rays the dense MART update skipped on this thread (`y_hat_i` at or
+    /// below the floor, or a negligible factor), counted in debug builds only.
+    static SKIPPED_RAYS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Number of rays the dense MART update has skipped on the calling thread
+/// so far.
+///
+/// Only available in debug builds, where `mart_step` and the dense MART
+/// solvers also assert on every ray that `y_hat_i`, the ratio and the
+/// updated voxels are finite, so a regression panics at the first bad ray
+/// (naming its iteration) instead of surfacing as a broken volume. This
+/// includes overflow, which release builds report as divergence instead
+/// (see `ReconReport::check_diverged`). The count is kept per thread, so
+/// reconstructions running side by side do not see each other's rays;
+/// subsets that `ReconOptions::parallel_subsets` sweeps on rayon workers
+/// are counted on those threads. Release builds compile all of this away.
+#[cfg(debug_assertions)]
+pub fn debug_skipped_rays() -> usize {
+    SKIPPED_RAYS.with(Cell::get)
+}
+
+#[inline]
+fn count_skipped_ray() {
+    #[cfg(debug_assertions)]
+    SKIPPED_RAYS.with(|count| count.set(count.get() + 1));
+}
+
+/// Debug-build checks of the update of ray `i` in iteration `iter`.
+#[inline]
+fn debug_check_ray<T: ReconFloat>(
+    row: ArrayView1<T>,
+    volume: &Array1<T>,
+    iter: usize,
+    i: usize,
+    y_hat: T,
+    ratio: T,
+) {
+    debug_assert!(
+        y_hat.is_finite(),
+        "MART iteration {iter}, ray {i}: y_hat {y_hat:?} is not finite"
+    );
+    debug_assert!(
+        ratio.is_finite(),
+        "MART iteration {iter}, ray {i}: ratio {ratio:?} is not finite (y_hat {y_hat:?})"
+    );
+    debug_assert!(
+        row.iter()
+            .zip(volume)
+            .all(|(&a_ij, x_j)| a_ij == T::zero() || x_j.is_finite()),
+        "MART iteration {iter}, ray {i}: update made voxel {:?} not finite \
+        (y_hat {y_hat:?}, ratio {ratio:?})",
+        row.iter()
+            .zip(volume)
+            .position(|(&a_ij, x_j)| a_ij != T::zero() && !x_j.is_finite())
+    );
+}
+
+/// Apply the multiplicative MART update for a single ray `i`.
+fn mart_update_ray<T: ReconFloat, S: Data<Elem = T>>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    i: usize,
+    volume: &mut Array1<T>,
+    relaxation: T,
+    iter: usize,
+    ray: &RayUpdate<T>,
+) {
+    let row = system_matrix.index_axis(Axis(0), i); // A_i*
+
+    // estimated projection: y_hat_i = sum_j A_ij * x_j
+    let y_hat = ray.y_hat(row, volume);
+
+    let Some(ratio) = ray.ratio(projections[i], y_hat) else {
+        count_skipped_ray();
+        return;
+    };
+    let factor = ratio.powf(relaxation);
+    if ray.negligible(factor) {
+        count_skipped_ray();
+        return;
+    }
+
+    scale_touched_voxels(row, volume, factor);
+    debug_check_ray(row, volume, iter, i, y_hat, ratio);
+}
+
+/// Column- or voxel-weighted MART update for ray `i`, e.g.
+///
+///   x_j *= (y_i / y_hat_i)^(relaxation * A_ij / colsum_j)
+#[allow(clippy::too_many_arguments)]
+fn mart_update_ray_weighted<T: ReconFloat, S: Data<Elem = T>>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    i: usize,
+    volume: &mut Array1<T>,
+    relaxation: T,
+    iter: usize,
+    voxel_scale: &VoxelScale<T>,
+    ray: &RayUpdate<T>,
+) {
+    let row = system_matrix.index_axis(Axis(0), i); // A_i*
+
+    let y_hat = ray.y_hat(row, volume);
+    let Some(ratio) = ray.ratio(projections[i], y_hat) else {
+        count_skipped_ray();
+        return;
+    };
+
+    if ray.negligible(ratio.powf(relaxation)) {
+        count_skipped_ray();
+        return;
+    }
+
+    let log_factor = relaxation * ratio.ln();
+    match voxel_scale {
+        VoxelScale::Voxel(weights) => scale_touched_voxels_by(row, volume, log_factor, weights),
+        VoxelScale::Column(weights) => {
+            scale_touched_voxels_weighted(row, volume, log_factor, weights)
         }
+    }
+    debug_check_ray(row, volume, iter, i, y_hat, ratio);
+}
+
+/// Dot product of one system-matrix row with the volume.
+///
+/// ndarray's `dot` sums contiguous rows with eight independent
+/// accumulators, which the compiler turns into SIMD on any target (SSE/AVX,
+/// NEON, ...) without `unsafe` or per-architecture code; strided rows fall
+/// back to a plain loop. It adds the products in a different order than a
+/// scalar loop, so results agree to rounding, not bit for bit.
+#[cfg(not(feature = "rayon"))]
+fn row_dot<T: ReconFloat>(row: ArrayView1<T>, volume: &Array1<T>) -> T {
+    row.dot(volume)
+}
 
-        let ratio = projections[i] / y_hat;
-        let factor = ratio.powf(relaxation);
+/// Dot product of one system-matrix row with the volume, split across
+/// columns with rayon. On a single thread this is the serial `row.dot`, so
+/// `ReconOptions::threads = Some(1)` matches the non-rayon build exactly.
+#[cfg(feature = "rayon")]
+fn row_dot<T: ReconFloat>(row: ArrayView1<T>, volume: &Array1<T>) -> T {
+    if rayon::current_num_threads() == 1 {
+        return row.dot(volume);
+    }
+    Zip::from(row)
+        .and(volume)
+        .par_fold(T::zero, |acc, &a_ij, &x_j| acc + a_ij * x_j, |a, b| a + b)
+}
 
-        for j in 0..n {
-            let a_ij = row[j];
-            if a_ij > 0.0 {
-                volume[j] *= factor;
+/// Rows at most this long are summed directly by `pairwise_dot`.
+const PAIRWISE_BLOCK: usize = 512;
+
+/// Dot product summed over a binary tree that halves the row until the
+/// pieces are at most `PAIRWISE_BLOCK` long (`ReconOptions::deterministic`).
+///
+/// The split points depend only on the row length, so the result is the same
+/// on any number of threads; with rayon the two halves are summed in
+/// parallel.
+fn pairwise_dot<T: ReconFloat>(row: ArrayView1<T>, volume: ArrayView1<T>) -> T {
+    if row.len() <= PAIRWISE_BLOCK {
+        return row.dot(&volume);
+    }
+    let mid = row.len() / 2;
+    let (row_left, row_right) = row.split_at(Axis(0), mid);
+    let (volume_left, volume_right) = volume.split_at(Axis(0), mid);
+    let (left, right) = join(
+        || pairwise_dot(row_left, volume_left),
+        || pairwise_dot(row_right, volume_right),
+    );
+    left + right
+}
+
+/// Run both closures in turn (the non-rayon stand-in for `rayon::join`).
+#[cfg(not(feature = "rayon"))]
+fn join<A, B>(a: impl FnOnce() -> A, b: impl FnOnce() -> B) -> (A, B) {
+    (a(), b())
+}
+
+/// Multiply every voxel the ray passes through (A_ij > 0) by `factor`.
+#[cfg(not(feature = "rayon"))]
+fn scale_touched_voxels<T: ReconFloat>(row: ArrayView1<T>, volume: &mut Array1<T>, factor: T) {
+    for j in 0..row.len() {
+        if row[j] > T::zero() {
+            volume[j] = volume[j] * factor;
+        }
+    }
+}
+
+/// Multiply every voxel the ray passes through (A_ij > 0) by `factor`,
+/// split across columns with rayon.
+#[cfg(feature = "rayon")]
+fn scale_touched_voxels<T: ReconFloat>(row: ArrayView1<T>, volume: &mut Array1<T>, factor: T) {
+    Zip::from(volume).and(row).par_for_each(|x_j, &a_ij| {
+        if a_ij > T::zero() {
+            *x_j = *x_j * factor;
+        }
+    });
+}
+
+/// Multiply each touched voxel by `exp(log_factor * weights[j])`.
+#[cfg(not(feature = "rayon"))]
+fn scale_touched_voxels_by<T: ReconFloat>(
+    row: ArrayView1<T>,
+    volume: &mut Array1<T>,
+    log_factor: T,
+    weights: &Array1<T>,
+) {
+    for j in 0..row.len() {
+        // a zero weight freezes the voxel (and would give 0 * ln(0) = NaN)
+        if row[j] > T::zero() && weights[j] > T::zero() {
+            volume[j] = volume[j] * (log_factor * weights[j]).exp();
+        }
+    }
+}
+
+/// Multiply each touched voxel by `exp(log_factor * weights[j])`, split
+/// across columns with rayon.
+#[cfg(feature = "rayon")]
+fn scale_touched_voxels_by<T: ReconFloat>(
+    row: ArrayView1<T>,
+    volume: &mut Array1<T>,
+    log_factor: T,
+    weights: &Array1<T>,
+) {
+    Zip::from(volume)
+        .and(row)
+        .and(weights)
+        .par_for_each(|x_j, &a_ij, &w_j| {
+            if a_ij > T::zero() && w_j > T::zero() {
+                *x_j = *x_j * (log_factor * w_j).exp();
             }
+        });
+}
+
+/// Multiply each touched voxel by `exp(log_factor * A_ij * weights[j])`.
+#[cfg(not(feature = "rayon"))]
+fn scale_touched_voxels_weighted<T: ReconFloat>(
+    row: ArrayView1<T>,
+    volume: &mut Array1<T>,
+    log_factor: T,
+    weights: &Array1<T>,
+) {
+    for j in 0..row.len() {
+        if row[j] > T::zero() && weights[j] > T::zero() {
+            volume[j] = volume[j] * (log_factor * row[j] * weights[j]).exp();
         }
     }
 }
 
+/// Multiply each touched voxel by `exp(log_factor * A_ij * weights[j])`,
+/// split across columns with rayon.
+#[cfg(feature = "rayon")]
+fn scale_touched_voxels_weighted<T: ReconFloat>(
+    row: ArrayView1<T>,
+    volume: &mut Array1<T>,
+    log_factor: T,
+    weights: &Array1<T>,
+) {
+    Zip::from(volume)
+        .and(row)
+        .and(weights)
+        .par_for_each(|x_j, &a_ij, &w_j| {
+            if a_ij > T::zero() && w_j > T::zero() {
+                *x_j = *x_j * (log_factor * a_ij * w_j).exp();
+            }
+        });
+}
+
 /// Simple MART reconstruction loop.
 ///
 /// - projections: length M
 /// - system_matrix: shape (M, N)
 /// - n_iters: number of MART passes over all rays
 /// - relaxation: relaxation parameter
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
+///
+/// Returns reconstructed volume (length N), or a `ReconError` if the
+/// projections or initial guess do not fit the system matrix or the run
+/// diverged.
+///
+/// `system_matrix` can be any 2D array, including an `ArrayView2` over
+/// borrowed memory such as a NumPy buffer.
+pub fn mart_reconstruct<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    mart_reconstruct_report(projections, system_matrix, n_iters, relaxation, options)
+        .map(|report| report.volume)
+}
+
+/// `mart_reconstruct` without input checks; panics on a dimension mismatch.
+pub fn mart_reconstruct_unchecked<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Array1<T> {
+    mart_run(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+    .volume
+}
+
+/// Like `mart_reconstruct`, but returns a `ReconReport` with the iteration
+/// count, the residual history and whether the tolerance in `options` (if
+/// any) was reached.
+pub fn mart_reconstruct_report<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<ReconReport<T>, ReconError> {
+    mart_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+}
+
+/// MART reconstruction loop that reports progress after every iteration.
+///
+/// `callback(iter, volume, residual)` receives the 0-based iteration index,
+/// the current volume and its relative L2 residual `||A*x - y|| / ||y||`.
+/// Use it to stream convergence metrics while the reconstruction runs.
+pub fn mart_reconstruct_with_callback<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    check_mart_inputs(projections, system_matrix.dim(), None)?;
+    check_initial_guess(options, system_matrix.dim().1)?;
+    check_mask(options, system_matrix.dim().1)?;
+    check_ray_weights(options, system_matrix.dim().0)?;
+    check_voxel_weights(options, system_matrix.dim().1)?;
+    mart_run(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        callback,
+    )
+    .check_diverged()
+}
+
+/// The MART loop behind the checked and unchecked entry points.
+fn mart_run<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> ReconReport<T> {
+    let (m, n) = system_matrix.dim();
+    assert_eq!(projections.len(), m);
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+    let mut schedule = RowSchedule::new(m, options.row_order, options.start_iteration);
+    let voxel_scale = VoxelScale::new(options, n, || system_matrix.sum_axis(Axis(0)));
+    let ray = RayUpdate::new(options);
+    let mut tuner = options
+        .auto_relaxation
+        .as_ref()
+        .map(|auto| RelaxationTuner::new(auto, m, relaxation));
+
+    run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |iter, volume, relaxation| {
+            let relaxation = match &mut tuner {
+                Some(tuner) => tuner.next(
+                    volume,
+                    |volume, rows, relaxation| {
+                        mart_sweep(
+                            projections,
+                            system_matrix,
+                            rows,
+                            volume,
+                            relaxation,
+                            iter,
+                            voxel_scale.as_ref(),
+                            &ray,
+                        )
+                    },
+                    |volume, rows| {
+                        relative_l2_rows(projections, rows, |i| {
+                            ray.y_hat(system_matrix.index_axis(Axis(0), i), volume)
+                        })
+                    },
+                ),
+                None => relaxation,
+            };
+            let rows = schedule.next_order();
+            mart_sweep(
+                projections,
+                system_matrix,
+                rows,
+                volume,
+                relaxation,
+                iter,
+                voxel_scale.as_ref(),
+                &ray,
+            )
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    )
+}
+
+/// MART with Tikhonov (L2) smoothing after every pass.
+///
+/// Shorthand for `mart_reconstruct` with `options.l2_regularization` set to
+/// `lambda_reg` on a grid of `volume_shape = [rows, cols]`; see
+/// `L2Regularization` for the update and `laplacian` for the boundary
+/// handling.
+pub fn mart_reconstruct_reg<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    lambda_reg: f64,
+    volume_shape: [usize; 2],
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    let options = ReconOptions {
+        l2_regularization: Some(L2Regularization {
+            weight: lambda_reg,
+            volume_shape,
+        }),
+        ..options.clone()
+    };
+    mart_reconstruct(projections, system_matrix, n_iters, relaxation, &options)
+}
+
+/// Continue a MART reconstruction from `start_volume`, e.g. a checkpoint.
+///
+/// Runs `remaining_iters` more passes with default `ReconOptions`; with the
+/// sequential row order this yields exactly the volume an uninterrupted
+/// `mart_reconstruct` would have produced. For runs with a relaxation
+/// schedule, TV or shuffled rows, call `mart_reconstruct` with
+/// `initial_guess` and `start_iteration` set instead.
+pub fn mart_resume<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    start_volume: &Array1<T>,
+    remaining_iters: usize,
+    relaxation: T,
+) -> Result<Array1<T>, ReconError> {
+    let options = ReconOptions {
+        initial_guess: InitialGuess::FromArray(start_volume.mapv(|v| v.to_f64().unwrap())),
+        ..ReconOptions::default()
+    };
+    mart_reconstruct(
+        projections,
+        system_matrix,
+        remaining_iters,
+        relaxation,
+        &options,
+    )
+}
+
+/// Partition ray indices `0..m` into `n_subsets` interleaved groups.
+///
+/// Subset `s` holds rays `s, s + n_subsets, s + 2 * n_subsets, ...`, so every
+/// ray lands in exactly one subset even when `m` is not divisible by
+/// `n_subsets` (the first `m % n_subsets` subsets get one extra ray).
+pub fn interleaved_subsets(m: usize, n_subsets: usize) -> Vec<Vec<usize>> {
+    assert!(n_subsets > 0, "n_subsets must be at least 1");
+    (0..n_subsets)
+        .map(|s| (s..m).step_by(n_subsets).collect())
+        .collect()
+}
+
+/// Perform one OS-MART outer iteration: a MART sweep over each subset in turn.
+///
+/// With a single subset this is exactly `mart_step`.
+pub fn os_mart_step<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    subsets: &[Vec<usize>],
+    volume: &mut Array1<T>,
+    relaxation: T,
+) {
+    let (m, n) = system_matrix.dim();
+    assert_eq!(projections.len(), m);
+    assert_eq!(volume.len(), n);
+
+    for subset in subsets {
+        for &i in subset {
+            mart_update_ray(
+                projections,
+                system_matrix,
+                i,
+                volume,
+                relaxation,
+                0,
+                &RayUpdate::default(),
+            );
+        }
+    }
+}
+
+/// Ordered-subset MART reconstruction loop.
+///
+/// Rays are split into `n_subsets` interleaved groups (see
+/// `interleaved_subsets`) and each outer iteration updates the volume once
+/// per subset. Visiting widely separated rays back to back decorrelates
+/// consecutive updates, which speeds up early convergence; `n_subsets = 1`
+/// reduces to plain `mart_reconstruct`.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of outer iterations
+/// - n_subsets: number of ray subsets (>= 1)
+/// - relaxation: relaxation parameter
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the inputs or options do not fit the matrix (as for
+/// `mart_reconstruct`).
+pub fn os_mart_reconstruct<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    n_subsets: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    os_mart_reconstruct_report(
+        projections,
+        system_matrix,
+        n_iters,
+        n_subsets,
+        relaxation,
+        options,
+    )
+    .map(|report| report.volume)
+}
+
+/// Like `os_mart_reconstruct`, but also reports the number of iterations run.
+pub fn os_mart_reconstruct_report<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    n_subsets: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<ReconReport<T>, ReconError> {
+    os_mart_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        n_subsets,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+}
+
+/// OS-MART reconstruction loop that reports progress after every
+/// iteration; see `mart_reconstruct_with_callback` for the callback contract.
+pub fn os_mart_reconstruct_with_callback<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array1<T>,
+    system_matrix: &ArrayBase<S, Ix2>,
+    n_iters: usize,
+    n_subsets: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_mart_inputs(projections, (m, n), None)?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+    check_ray_weights(options, m)?;
+    check_voxel_weights(options, n)?;
+    let subsets = interleaved_subsets(m, n_subsets);
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+    let voxel_scale = VoxelScale::new(options, n, || system_matrix.sum_axis(Axis(0)));
+    let ray = RayUpdate::new(options);
+    let subset_weights = (options.parallel_subsets && n_subsets > 1)
+        .then(|| subset_weights(system_matrix, &subsets));
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |iter, volume, relaxation| {
+            let sweep = |subset: &[usize], volume: &mut Array1<T>| {
+                mart_sweep(
+                    projections,
+                    system_matrix,
+                    subset,
+                    volume,
+                    relaxation,
+                    iter,
+                    voxel_scale.as_ref(),
+                    &ray,
+                )
+            };
+            match &subset_weights {
+                Some(weights) => parallel_subset_step(&subsets, weights, volume, sweep),
+                None => subsets.iter().for_each(|subset| sweep(subset, volume)),
+            }
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// Share `c_s,j / c_j` of every voxel's column sum contributed by each
+/// subset's rays, one row per subset (zero for voxels no ray sees).
+fn subset_weights<T: ReconFloat, S: Data<Elem = T>>(
+    system_matrix: &ArrayBase<S, Ix2>,
+    subsets: &[Vec<usize>],
+) -> Array2<T> {
+    let col_sums = system_matrix.sum_axis(Axis(0));
+    let mut weights = Array2::zeros((subsets.len(), col_sums.len()));
+    for (mut row, subset) in weights.rows_mut().into_iter().zip(subsets) {
+        for &i in subset {
+            Zip::from(&mut row)
+                .and(system_matrix.row(i))
+                .for_each(|w, &a_ij| *w = *w + a_ij);
+        }
+        Zip::from(&mut row)
+            .and(&col_sums)
+            .for_each(|w, &c| *w = if c > T::zero() { *w / c } else { T::zero() });
+    }
+    weights
+}
+
+/// One block-Jacobi OS-MART iteration (`ReconOptions::parallel_subsets`):
+/// `sweep` runs every subset on its own copy of `volume`, in parallel with
+/// rayon, and the per-subset factors are merged as a weighted geometric
+/// mean. The merge visits the subsets in order, so the result does not
+/// depend on how the sweeps were scheduled.
+fn parallel_subset_step<T: ReconFloat>(
+    subsets: &[Vec<usize>],
+    weights: &Array2<T>,
+    volume: &mut Array1<T>,
+    sweep: impl Fn(&[usize], &mut Array1<T>) + Sync,
+) {
+    let run = |subset: &Vec<usize>| {
+        let mut copy = volume.clone();
+        sweep(subset, &mut copy);
+        copy
+    };
+    #[cfg(feature = "rayon")]
+    let copies: Vec<Array1<T>> = {
+        use rayon::prelude::*;
+        subsets.par_iter().map(run).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let copies: Vec<Array1<T>> = subsets.iter().map(run).collect();
+
+    for j in 0..volume.len() {
+        let x = volume[j];
+        if x <= T::zero() {
+            // MART never revives a zero voxel
+            continue;
+        }
+        let log_factor = copies
+            .iter()
+            .zip(weights.rows())
+            .fold(T::zero(), |acc, (copy, w)| {
+                if w[j] > T::zero() {
+                    acc + w[j] * (copy[j] / x).ln()
+                } else {
+                    acc
+                }
+            });
+        volume[j] = x * log_factor.exp();
+    }
+}
+
+/// Perform one additive ART (Kaczmarz) iteration over all rays.
+///
+/// Unlike MART the update is additive, so voxels can reach (and leave) zero:
+///
+///   x_j += relaxation * (y_i - y_hat_i) / ||A_i||^2 * A_ij
+///
+/// projections:  length M (measured y)
+/// system_matrix: shape (M, N) (A)
+/// volume:      length N (x)
+/// relaxation:  relaxation parameter (lambda)
+pub fn art_step<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+) {
+    let (m, n) = system_matrix.dim();
+    assert_eq!(projections.len(), m);
+    assert_eq!(volume.len(), n);
+
+    let precomputed = Precomputed::new(system_matrix, None);
+    art_sweep(
+        projections,
+        system_matrix,
+        &precomputed,
+        volume,
+        relaxation,
+        None,
+    );
+}
+
+/// ART pass restricted to the voxels in `mask` (all voxels if `None`);
+/// `precomputed` must have been built with the same mask.
+fn art_sweep<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    precomputed: &Precomputed<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+    mask: Option<&Array1<bool>>,
+) {
+    let (m, n) = system_matrix.dim();
+    let keep = |j: usize| mask.is_none_or(|mask| mask[j]);
+
+    for i in 0..m {
+        let row = system_matrix.index_axis(Axis(0), i); // A_i*
+
+        let norm_sq = precomputed.row_norms_sq[i];
+        let y_hat = match mask {
+            None => row.dot(volume),
+            Some(mask) => (0..n)
+                .filter(|&j| mask[j])
+                .fold(T::zero(), |y_hat, j| y_hat + row[j] * volume[j]),
+        };
+        if norm_sq <= T::zero() {
+            // empty ray: nothing to update, and dividing would give NaN
+            continue;
+        }
+
+        let scale = relaxation * (projections[i] - y_hat) / norm_sq;
+        for j in (0..n).filter(|&j| keep(j)) {
+            volume[j] = volume[j] + scale * row[j];
+        }
+    }
+}
+
+/// Simple additive ART reconstruction loop.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of ART passes over all rays
+/// - relaxation: relaxation parameter
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the projections (which may be negative), initial
+/// guess, mask or ray weights do not fit the matrix.
+pub fn art_reconstruct<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    art_reconstruct_report(projections, system_matrix, n_iters, relaxation, options)
+        .map(|report| report.volume)
+}
+
+/// Like `art_reconstruct`, but also reports the number of iterations run.
+pub fn art_reconstruct_report<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<ReconReport<T>, ReconError> {
+    art_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+}
+
+/// ART reconstruction loop that reports progress after every
+/// iteration; see `mart_reconstruct_with_callback` for the callback contract.
+pub fn art_reconstruct_with_callback<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_projections(projections, (m, n))?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+    check_ray_weights(options, m)?;
+    let mask = options.mask.as_ref();
+    let precomputed = Precomputed::new(system_matrix, mask);
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            art_sweep(
+                projections,
+                system_matrix,
+                &precomputed,
+                volume,
+                relaxation,
+                mask,
+            )
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// Simple SIRT reconstruction loop.
+///
+/// Each iteration computes the full residual `y - A*x` using the volume from
+/// the start of the iteration and applies an additive update weighted by the
+/// inverse row and column sums of the system matrix:
+///
+///   x_j += relaxation / colsum_j * sum_i A_ij * (y_i - y_hat_i) / rowsum_i
+///
+/// Rays with a zero row sum and voxels with a zero column sum are skipped so
+/// they never divide by zero.
+///
+/// SIRT only uses `A * x` and `A^T * y`, so `system_matrix` can be a dense
+/// or sparse matrix, or any matrix-free `LinearOperator`.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of SIRT iterations
+/// - relaxation: relaxation parameter
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the projections, initial guess or mask do not fit
+/// the matrix.
+pub fn sirt_reconstruct<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    sirt_reconstruct_report(projections, system_matrix, n_iters, relaxation, options)
+        .map(|report| report.volume)
+}
+
+/// Like `sirt_reconstruct`, but also reports the number of iterations run.
+pub fn sirt_reconstruct_report<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<ReconReport<T>, ReconError> {
+    sirt_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+}
+
+/// SIRT reconstruction loop that reports progress after every
+/// iteration; see `mart_reconstruct_with_callback` for the callback contract.
+pub fn sirt_reconstruct_with_callback<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_projections(projections, (m, n))?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+
+    // with a mask, rays only count the length through unmasked voxels and
+    // masked voxels get no update; the sums match `Precomputed`'s, but an
+    // operator has no rows to take its row norms from
+    let (row_sums, col_sums) = masked_sums(system_matrix, options.mask.as_ref());
+
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            sirt_step(
+                projections,
+                system_matrix,
+                &row_sums,
+                &col_sums,
+                volume,
+                relaxation,
+            )
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// One simultaneous SIRT update using precomputed row and column sums.
+///
+/// Simultaneous solvers (SIRT, SART, SMART) must see the volume of the
+/// start of the iteration for every ray: writing voxels while later rays
+/// are still being projected would turn them into a sequential method
+/// whose result depends on the ray and voxel order. The steps therefore
+/// double-buffer: every increment is computed from `x_old`, a read-only
+/// view of the volume, into a separate buffer that becomes `x_new`, and the
+/// two are swapped only at the end.
+fn sirt_step<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    row_sums: &Array1<T>,
+    col_sums: &Array1<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+) {
+    let m = system_matrix.dim().0;
+    let x_old: &Array1<T> = volume;
+
+    // weighted residual: (y_i - y_hat_i) / rowsum_i, zero for empty rays
+    let y_hat = system_matrix.forward(x_old);
+    let mut weighted = Array1::<T>::zeros(m);
+    for i in 0..m {
+        if row_sums[i] > T::zero() {
+            weighted[i] = (projections[i] - y_hat[i]) / row_sums[i];
+        }
+    }
+
+    // backproject, then turn the correction buffer into x_new in place
+    let mut x_new = system_matrix.adjoint(&weighted);
+    Zip::from(&mut x_new)
+        .and(x_old)
+        .and(col_sums)
+        .for_each(|x_new, &x_old, &col_sum| {
+            *x_new = if col_sum > T::zero() {
+                x_old + relaxation * *x_new / col_sum
+            } else {
+                x_old
+            };
+        });
+    std::mem::swap(volume, &mut x_new);
+}
+
+/// Landweber reconstruction: plain gradient descent on `||A*x - y||^2 / 2`,
+///
+///   x += relaxation * A^T (y - A*x)
+///
+/// It converges (to the least-squares solution nearest the initial volume)
+/// for every relaxation in `(0, 2 / sigma_max(A)^2)`; see
+/// `estimate_landweber_spectral_radius` and `safe_relaxation`. Unlike SIRT
+/// the update is not normalized by row and column sums, so the usable
+/// relaxation depends on the scale of A. Only `A * x` and `A^T * y` are
+/// used, so `system_matrix` can be any `LinearOperator`.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of Landweber iterations
+/// - relaxation: step size
+/// - options: per-iteration constraints (e.g. `clamp_nonnegative`, which
+///   makes this projected Landweber) and stopping rule
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the projections, initial guess or mask do not fit
+/// the matrix.
+pub fn landweber_reconstruct<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    landweber_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+    .map(|report| report.volume)
+}
+
+/// Landweber reconstruction loop that reports progress after every
+/// iteration; see `mart_reconstruct_with_callback` for the callback contract.
+pub fn landweber_reconstruct_with_callback<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_projections(projections, (m, n))?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            let residual = projections - &system_matrix.forward(volume);
+            volume.scaled_add(relaxation, &system_matrix.adjoint(&residual));
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// Simultaneous ART (SART) reconstruction: an alias of `sirt_reconstruct`.
+///
+/// Every ray is updated at once, with its residual weighted by its area
+/// (the row sum) and every voxel's correction normalized by its column sum:
+///
+///   x_j += relaxation / colsum_j * sum_i (A_ij / rowsum_i) * (y_i - y_hat_i)
+///
+/// where all `y_hat` come from the volume at the start of the iteration.
+/// Averaging the corrections of all rays converges more smoothly than ART's
+/// ray-by-ray updates. With all rays in a single block this is exactly the
+/// SIRT iteration, so both names run the same code and, like SIRT, accept
+/// a dense or sparse matrix or any `LinearOperator`. Rays and voxels with a
+/// zero sum are skipped.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of SART iterations
+/// - relaxation: relaxation parameter
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the inputs do not fit the matrix (as for SIRT).
+pub fn sart_reconstruct<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    sirt_reconstruct(projections, system_matrix, n_iters, relaxation, options)
+}
+
+/// SART reconstruction loop that reports progress after every iteration;
+/// the alias of `sirt_reconstruct_with_callback` (see `sart_reconstruct`).
+pub fn sart_reconstruct_with_callback<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    sirt_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        callback,
+    )
+}
+
+/// Maximum-likelihood expectation maximization (MLEM) for Poisson data.
+///
+/// Each iteration forward-projects the whole volume and applies the
+/// multiplicative EM update
+///
+///   x_j <- x_j / s_j * sum_i A_ij * y_i / y_hat_i
+///
+/// with the sensitivity image `s_j = sum_i A_ij` computed once. It
+/// increases the Poisson log-likelihood of `y` every iteration, which makes
+/// it the statistically sound choice for emission or photon-limited data,
+/// where MART's ray-by-ray updates overfit noise. Like MART it needs
+/// non-negative projections and a positive start, and keeps the volume
+/// non-negative.
+///
+/// Rays with `y_hat_i == 0` contribute nothing to the update (no voxel on
+/// them can explain `y_i` yet), and voxels with zero sensitivity, which no
+/// ray sees, keep their initial value.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of EM iterations
+///
+/// Returns reconstructed volume (length N), starting from 1.0 everywhere.
+pub fn mlem_reconstruct<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+) -> Result<Array1<T>, ReconError> {
+    mlem_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        &ReconOptions::default(),
+        |_, _, _| {},
+    )
+    .map(|report| report.volume)
+}
+
+/// MLEM reconstruction loop with the constraints and stopping rule in
+/// `options`, reporting progress after every iteration; see
+/// `mart_reconstruct_with_callback` for the callback contract. MLEM has no
+/// relaxation, so `relaxation_schedule` is ignored.
+pub fn mlem_reconstruct_with_callback<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    check_mart_inputs(projections, system_matrix.dim(), None)?;
+    check_initial_guess(options, system_matrix.dim().1)?;
+    check_mask(options, system_matrix.dim().1)?;
+
+    let n = system_matrix.dim().1;
+    let precomputed = Precomputed::new(system_matrix, options.mask.as_ref());
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+
+    run_iterations(
+        volume,
+        n_iters,
+        T::one(),
+        options,
+        |_, volume, _| mlem_step(projections, system_matrix, &precomputed, volume),
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    )
+    .check_diverged()
+}
+
+/// One EM update using the precomputed sensitivity image.
+fn mlem_step<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    precomputed: &Precomputed<T>,
+    volume: &mut Array1<T>,
+) {
+    let sensitivity = &precomputed.col_sums;
+    // measured over estimated projections, zero where nothing is estimated
+    let y_hat = system_matrix.dot(&*volume);
+    let ratio = Array1::from_shape_fn(projections.len(), |i| {
+        if y_hat[i] > T::zero() {
+            projections[i] / y_hat[i]
+        } else {
+            T::zero()
+        }
+    });
+
+    let correction = system_matrix.t().dot(&ratio);
+    Zip::from(volume)
+        .and(&correction)
+        .and(sensitivity)
+        .for_each(|x_j, &c_j, &s_j| {
+            if s_j > T::zero() {
+                *x_j = *x_j * c_j / s_j;
+            }
+        });
+}
+
+/// Simultaneous MART (SMART).
+///
+/// Each iteration freezes the volume, collects the log-ratios of every ray
+/// and applies them to each voxel at once:
+///
+///   x_j <- x_j * exp(relaxation / s_j * sum_i A_ij * ln(y_i / y_hat_i))
+///
+/// with `s_j = sum_i A_ij`. The update is a geometric mean of the
+/// per-ray MART corrections, so unlike sequential MART the result does not
+/// depend on the order of the rays, and the ray-order streaks MART leaves
+/// on inconsistent data average out. With `relaxation <= 1` it converges
+/// towards a minimizer of the Kullback-Leibler distance between `A*x` and
+/// `y`, at the price of converging more slowly than MART, about like SIRT.
+///
+/// Rays are skipped as in MART (`y_hat_i` at or below `floor_eps`), and
+/// `ratio_clamp` and `ray_weights` apply to each ray's log-ratio;
+/// `row_order`, `column_weighted` and `auto_relaxation` have no effect.
+/// Voxels no ray sees keep their initial value.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: number of SMART iterations
+/// - relaxation: relaxation parameter (1.0 is the classic SMART step)
+/// - options: per-iteration constraints and stopping rule (see `ReconOptions`)
 ///
 /// Returns reconstructed volume (length N).
-pub fn mart_reconstruct(
-    projections: &Array1<f32>,
-    system_matrix: &Array2<f32>,
+pub fn smart_reconstruct<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
     n_iters: usize,
-    relaxation: f32,
-) -> Array1<f32> {
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    smart_reconstruct_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+    .map(|report| report.volume)
+}
+
+/// SMART reconstruction loop that reports progress after every iteration;
+/// see `mart_reconstruct_with_callback` for the callback contract.
+pub fn smart_reconstruct_with_callback<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    check_mart_inputs(projections, system_matrix.dim(), None)?;
+    check_initial_guess(options, system_matrix.dim().1)?;
+    check_mask(options, system_matrix.dim().1)?;
+    check_ray_weights(options, system_matrix.dim().0)?;
+    check_voxel_weights(options, system_matrix.dim().1)?;
+
     let n = system_matrix.dim().1;
-    let mut volume = Array1::<f32>::from_elem(n, 1.0); // uniform initial guess
+    let precomputed = Precomputed::new(system_matrix, options.mask.as_ref());
+    let ray = RayUpdate::new(options);
+    let voxel_weights = options
+        .voxel_weights
+        .as_ref()
+        .map(|weights| weights.mapv(|w| T::from(w).unwrap()));
+    let volume = initial_volume(options, n, || backprojection(projections, system_matrix));
+
+    run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            smart_step(
+                projections,
+                system_matrix,
+                &precomputed,
+                volume,
+                relaxation,
+                voxel_weights.as_ref(),
+                &ray,
+            )
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    )
+    .check_diverged()
+}
+
+/// One SMART update: accumulate `sum_i A_ij * ln(ratio_i)` against the
+/// frozen volume `x_old`, then scale every voxel once (double-buffered like
+/// `sirt_step`).
+fn smart_step<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &Array2<T>,
+    precomputed: &Precomputed<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+    voxel_weights: Option<&Array1<T>>,
+    ray: &RayUpdate<T>,
+) {
+    let x_old: &Array1<T> = volume;
+    let mut accumulation = Array1::<T>::zeros(x_old.len());
+    for (i, row) in system_matrix.rows().into_iter().enumerate() {
+        let Some(weight) = ray.relaxation(i, T::one()) else {
+            continue;
+        };
+        let Some(ratio) = ray.ratio(projections[i], ray.y_hat(row, x_old)) else {
+            continue;
+        };
+        // only touched voxels: a zero-measurement ray has ln(0) = -inf
+        let log_ratio = weight * ratio.ln();
+        Zip::from(&mut accumulation)
+            .and(row)
+            .for_each(|acc, &a_ij| {
+                if a_ij > T::zero() {
+                    *acc = *acc + a_ij * log_ratio;
+                }
+            });
+    }
+
+    // the accumulation buffer becomes x_new
+    let mut x_new = accumulation;
+    Zip::indexed(&mut x_new)
+        .and(x_old)
+        .and(&precomputed.col_sums)
+        .for_each(|j, x_new, &x_old, &s_j| {
+            let weight = voxel_weights.map_or(T::one(), |weights| weights[j]);
+            *x_new = if s_j > T::zero() && weight > T::zero() {
+                x_old * (relaxation * weight / s_j * *x_new).exp()
+            } else {
+                x_old
+            };
+        });
+    std::mem::swap(volume, &mut x_new);
+}
+
+/// Least-squares reconstruction with CGLS (conjugate gradient on the normal
+/// equations `A^T A x = A^T y`).
+///
+/// Unlike MART this minimizes `||A*x - y||` exactly: on a full-rank system
+/// it converges in at most N iterations in exact arithmetic, and it accepts
+/// negative projections and produces negative voxels where the data ask for
+/// them. It starts from x = 0 and ignores `ReconOptions`, since clamping or
+/// smoothing between iterations would break the conjugacy of the search
+/// directions. Like SIRT it accepts any `LinearOperator`.
+///
+/// - projections: length M
+/// - system_matrix: shape (M, N)
+/// - n_iters: maximum number of CG iterations; stops early once the
+///   normal-equation residual `A^T (y - A*x)` is exactly zero
+///
+/// Returns reconstructed volume (length N), or an error if the system has no
+/// rays or no voxels, or the projections do not fit the matrix.
+pub fn cgls_reconstruct<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    projections: &Array1<T>,
+    system_matrix: &A,
+    n_iters: usize,
+) -> Result<Array1<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_projections(projections, (m, n))?;
+
+    let mut volume = Array1::<T>::zeros(n);
+    let mut residual = projections.clone(); // r = y - A x
+    let mut gradient = system_matrix.adjoint(&residual); // s = A^T r
+    let mut direction = gradient.clone(); // p
+    let mut gamma = gradient.dot(&gradient);
 
     for _ in 0..n_iters {
-        mart_step(projections, system_matrix, &mut volume, relaxation);
+        if gamma <= T::zero() {
+            break;
+        }
+        let q = system_matrix.forward(&direction);
+        let q_norm_sq = q.dot(&q);
+        if q_norm_sq <= T::zero() {
+            break;
+        }
+
+        let alpha = gamma / q_norm_sq;
+        volume.scaled_add(alpha, &direction);
+        residual.scaled_add(-alpha, &q);
+
+        gradient = system_matrix.adjoint(&residual);
+        let gamma_next = gradient.dot(&gradient);
+        let beta = gamma_next / gamma;
+        direction.zip_mut_with(&gradient, |p, &s| *p = s + beta * *p);
+        gamma = gamma_next;
     }
 
-    volume
+    Ok(volume)
 }
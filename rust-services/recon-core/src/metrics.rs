@@ -0,0 +1,177 @@
+//! Image quality of a reconstruction against a known ground truth, e.g. the
+//! phantom a simulated scan was made from.
+//!
+//! Every metric takes the `reconstruction` first and the `reference`
+//! second; PSNR and SSIM measure against the reference's data range
+//! `max - min`, so swapping the arguments changes the result.
+//!
+//! SSIM follows Wang et al. (2004): local means, variances and covariance
+//! are taken under a Gaussian window with `sigma = 1.5` pixels (11 taps,
+//! the `3 * sigma` truncation of `filter::gaussian_blur`), with the
+//! stabilizing constants `C1 = (0.01 L)^2` and `C2 = (0.03 L)^2` for the
+//! data range `L`. The window replicates the edge pixels at the borders
+//! instead of cropping them, so values on small images differ slightly from
+//! implementations that only average the interior.
+//!
+//! Without a ground truth, `fraction_at_bounds` and the roughness measure
+//! `regularization::total_variation`, together with the final data-fit
+//! residual, serve as blind proxies for comparing runs.
+
+use ndarray::{s, Array1};
+
+use crate::filter::gaussian_blur;
+use crate::ReconFloat;
+
+/// Standard deviation of the SSIM window, in pixels.
+pub const SSIM_SIGMA: f64 = 1.5;
+
+fn to_f64<T: ReconFloat>(volume: &Array1<T>) -> Array1<f64> {
+    volume.mapv(|v| v.to_f64().unwrap())
+}
+
+fn check_lengths<T>(reconstruction: &Array1<T>, reference: &Array1<T>) {
+    assert_eq!(
+        reconstruction.len(),
+        reference.len(),
+        "reconstruction and reference must have equal length"
+    );
+}
+
+/// `max - min` of `reference`.
+pub fn data_range<T: ReconFloat>(reference: &Array1<T>) -> f64 {
+    let (lo, hi) = reference
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            let v = v.to_f64().unwrap();
+            (lo.min(v), hi.max(v))
+        });
+    hi - lo
+}
+
+/// Root-mean-square error `sqrt(mean((x - x_ref)^2))`.
+///
+/// Panics if the volumes differ in length.
+pub fn rmse<T: ReconFloat>(reconstruction: &Array1<T>, reference: &Array1<T>) -> f64 {
+    check_lengths(reconstruction, reference);
+    let sum_sq: f64 = reconstruction
+        .iter()
+        .zip(reference)
+        .map(|(&x, &r)| (x.to_f64().unwrap() - r.to_f64().unwrap()).powi(2))
+        .sum();
+    (sum_sq / reference.len() as f64).sqrt()
+}
+
+/// Peak signal-to-noise ratio `20 log10(L / RMSE)` in dB, with `L` the
+/// reference's data range.
+///
+/// Infinite for a perfect reconstruction, and NaN for a constant
+/// reference, which has no range to measure against.
+pub fn psnr<T: ReconFloat>(reconstruction: &Array1<T>, reference: &Array1<T>) -> f64 {
+    let range = data_range(reference);
+    if range == 0.0 {
+        return f64::NAN;
+    }
+    20.0 * (range / rmse(reconstruction, reference)).log10()
+}
+
+/// Mean SSIM of a 2D image `[rows, cols]` (C order) against `reference`,
+/// with `data_range` as `L` (see the module docs).
+///
+/// 1.0 means identical images; a `data_range` of 0 gives NaN wherever the
+/// images are flat. Panics if the lengths do not match `shape`.
+pub fn ssim_2d<T: ReconFloat>(
+    reconstruction: &Array1<T>,
+    reference: &Array1<T>,
+    shape: [usize; 2],
+    data_range: f64,
+) -> f64 {
+    check_lengths(reconstruction, reference);
+    assert_eq!(
+        reference.len(),
+        shape[0] * shape[1],
+        "image length must match the shape"
+    );
+    let (x, y) = (to_f64(reconstruction), to_f64(reference));
+    let window = |image: &Array1<f64>| gaussian_blur(image, &shape, SSIM_SIGMA);
+
+    let (mu_x, mu_y) = (window(&x), window(&y));
+    let sigma_xx = window(&(&x * &x)) - &mu_x * &mu_x;
+    let sigma_yy = window(&(&y * &y)) - &mu_y * &mu_y;
+    let sigma_xy = window(&(&x * &y)) - &mu_x * &mu_y;
+
+    let c1 = (0.01 * data_range).powi(2);
+    let c2 = (0.03 * data_range).powi(2);
+    let total: f64 = (0..x.len())
+        .map(|j| {
+            let luminance =
+                (2.0 * mu_x[j] * mu_y[j] + c1) / (mu_x[j] * mu_x[j] + mu_y[j] * mu_y[j] + c1);
+            let structure = (2.0 * sigma_xy[j] + c2) / (sigma_xx[j] + sigma_yy[j] + c2);
+            luminance * structure
+        })
+        .sum();
+    total / x.len() as f64
+}
+
+/// SSIM of every slice of a `[slices, rows, cols]` stack, all measured
+/// against the data range of the whole reference volume.
+pub fn ssim_slices<T: ReconFloat>(
+    reconstruction: &Array1<T>,
+    reference: &Array1<T>,
+    shape: [usize; 3],
+) -> Vec<f64> {
+    check_lengths(reconstruction, reference);
+    assert_eq!(
+        reference.len(),
+        shape.iter().product::<usize>(),
+        "volume length must match the shape"
+    );
+    let range = data_range(reference);
+    let [slices, rows, cols] = shape;
+    let area = rows * cols;
+    (0..slices)
+        .map(|z| {
+            let slice = |volume: &Array1<T>| volume.slice(s![z * area..(z + 1) * area]).to_owned();
+            ssim_2d(
+                &slice(reconstruction),
+                &slice(reference),
+                [rows, cols],
+                range,
+            )
+        })
+        .collect()
+}
+
+/// Fractions of the voxels at (or beyond) the lower and upper clamp bounds
+/// `(lo, hi)`, counting only the voxels inside `mask` (all when `None`).
+///
+/// Use an infinite end for a one-sided bound. A large share at a bound
+/// means the data push the volume against it, typical of an
+/// under-regularized run hitting the non-negativity floor. Both fractions
+/// are 0 for an empty mask.
+pub fn fraction_at_bounds<T: ReconFloat>(
+    volume: &Array1<T>,
+    bounds: (f64, f64),
+    mask: Option<&Array1<bool>>,
+) -> (f64, f64) {
+    if let Some(mask) = mask {
+        assert_eq!(
+            mask.len(),
+            volume.len(),
+            "mask and volume must have equal length"
+        );
+    }
+    let (lo, hi) = (T::from(bounds.0).unwrap(), T::from(bounds.1).unwrap());
+    let (mut counted, mut below, mut above) = (0usize, 0usize, 0usize);
+    for (j, &v) in volume.iter().enumerate() {
+        if mask.is_some_and(|mask| !mask[j]) {
+            continue;
+        }
+        counted += 1;
+        below += (v <= lo) as usize;
+        above += (v >= hi) as usize;
+    }
+    if counted == 0 {
+        return (0.0, 0.0);
+    }
+    (below as f64 / counted as f64, above as f64 / counted as f64)
+}
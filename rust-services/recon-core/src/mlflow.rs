@@ -0,0 +1,82 @@
+//! Best-effort metric logging to an MLflow tracking server.
+//!
+//! Metrics go to the REST `runs/log-metric` endpoint one at a time. A
+//! tracking server that is down or slow must never cost us a
+//! reconstruction, so failures are counted instead of returned, and after
+//! `MAX_CONSECUTIVE_FAILURES` failures in a row the logger stops sending.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+/// Failed requests in a row after which the logger gives up.
+const MAX_CONSECUTIVE_FAILURES: usize = 3;
+
+/// Per-request timeout, so an unreachable server stalls a run only briefly.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts metrics of one MLflow run.
+pub struct MlflowLogger {
+    endpoint: String,
+    run_id: String,
+    agent: ureq::Agent,
+    consecutive_failures: usize,
+    failures: usize,
+}
+
+impl MlflowLogger {
+    /// Log to run `run_id` on the tracking server at `tracking_uri`
+    /// (e.g. `http://localhost:5000`).
+    pub fn new(tracking_uri: &str, run_id: &str) -> Self {
+        Self {
+            endpoint: format!(
+                "{}/api/2.0/mlflow/runs/log-metric",
+                tracking_uri.trim_end_matches('/')
+            ),
+            run_id: run_id.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            consecutive_failures: 0,
+            failures: 0,
+        }
+    }
+
+    /// Record `key = value` at `step`. Non-finite values are skipped, since
+    /// MLflow rejects them.
+    pub fn log_metric(&mut self, key: &str, value: f64, step: usize) {
+        if !value.is_finite() || self.gave_up() {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let body = json!({
+            "run_id": self.run_id,
+            "key": key,
+            "value": value,
+            "timestamp": timestamp,
+            "step": step,
+        });
+
+        let request = self
+            .agent
+            .post(&self.endpoint)
+            .set("Content-Type", "application/json");
+        match request.send_string(&body.to_string()) {
+            Ok(_) => self.consecutive_failures = 0,
+            Err(_) => {
+                self.consecutive_failures += 1;
+                self.failures += 1;
+            }
+        }
+    }
+
+    /// Number of metrics that could not be delivered.
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    /// True once the logger has stopped sending after repeated failures.
+    pub fn gave_up(&self) -> bool {
+        self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES
+    }
+}
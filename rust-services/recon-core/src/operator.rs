@@ -0,0 +1,96 @@
+//! Linear operators for the solvers that never look at single entries.
+//!
+//! SIRT, CGLS and the normalized backprojection only need the products
+//! `A * x` and `A^T * y`, so they accept anything implementing
+//! `LinearOperator`: the dense and CSR system matrices, or a matrix-free
+//! projector (an analytic ray tracer, a GPU kernel) that never stores A.
+
+use ndarray::{Array1, ArrayBase, Axis, Data, Ix2};
+
+use crate::{back_project, forward_project, ReconFloat, SparseSystemMatrix};
+
+/// A linear map A from volumes (length N) to projections (length M).
+///
+/// Only `dim`, `forward` and `adjoint` are required. The row and column
+/// sums default to `A * 1` and `A^T * 1`; implementations that can sum
+/// their entries directly may override them. Operators must be `Sync`
+/// because the solvers run inside a thread pool (see
+/// `ReconOptions::threads`).
+pub trait LinearOperator<T: ReconFloat>: Sync {
+    /// Shape (M, N): number of rays and voxels.
+    fn dim(&self) -> (usize, usize);
+
+    /// Forward projection `A * x` (length M) of a volume of length N.
+    fn forward(&self, x: &Array1<T>) -> Array1<T>;
+
+    /// Adjoint `A^T * y` (length N) of projections of length M.
+    fn adjoint(&self, y: &Array1<T>) -> Array1<T>;
+
+    /// Row sums `sum_j A_ij` (length M), the ray lengths.
+    fn row_sums(&self) -> Array1<T> {
+        self.forward(&Array1::from_elem(self.dim().1, T::one()))
+    }
+
+    /// Column sums `sum_i A_ij` (length N), the sensitivity image.
+    fn column_sums(&self) -> Array1<T> {
+        self.adjoint(&Array1::from_elem(self.dim().0, T::one()))
+    }
+}
+
+impl<T: ReconFloat, S: Data<Elem = T> + Sync> LinearOperator<T> for ArrayBase<S, Ix2> {
+    fn dim(&self) -> (usize, usize) {
+        ArrayBase::dim(self)
+    }
+
+    fn forward(&self, x: &Array1<T>) -> Array1<T> {
+        forward_project(self, x)
+    }
+
+    fn adjoint(&self, y: &Array1<T>) -> Array1<T> {
+        back_project(self, y)
+    }
+
+    fn row_sums(&self) -> Array1<T> {
+        self.sum_axis(Axis(1))
+    }
+
+    fn column_sums(&self) -> Array1<T> {
+        self.sum_axis(Axis(0))
+    }
+}
+
+impl<T: ReconFloat> LinearOperator<T> for SparseSystemMatrix<T> {
+    fn dim(&self) -> (usize, usize) {
+        SparseSystemMatrix::dim(self)
+    }
+
+    fn forward(&self, x: &Array1<T>) -> Array1<T> {
+        self.dot(x)
+    }
+
+    fn adjoint(&self, y: &Array1<T>) -> Array1<T> {
+        self.t_dot(y)
+    }
+}
+
+/// Row and column sums of `operator` over the voxels kept by `mask` (all
+/// voxels if `None`), the sums stored in `Precomputed`.
+///
+/// SIRT and SART call this directly rather than building a `Precomputed`:
+/// they run over any `LinearOperator`, which only offers `A * x` and
+/// `A^T * y`, so the squared row norms `Precomputed` also holds for ART
+/// cannot be formed without the entries, and the two solvers never use
+/// them.
+pub(crate) fn masked_sums<T: ReconFloat, A: LinearOperator<T> + ?Sized>(
+    operator: &A,
+    mask: Option<&Array1<bool>>,
+) -> (Array1<T>, Array1<T>) {
+    match mask {
+        None => (operator.row_sums(), operator.column_sums()),
+        Some(mask) => {
+            assert_eq!(mask.len(), operator.dim().1, "mask must have length N");
+            let keep = mask.mapv(|keep| if keep { T::one() } else { T::zero() });
+            (operator.forward(&keep), operator.column_sums() * &keep)
+        }
+    }
+}
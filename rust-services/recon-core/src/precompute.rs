@@ -0,0 +1,56 @@
+//! Per-matrix sums that the iterative solvers reuse every pass.
+//!
+//! The system matrix never changes during a reconstruction, so the row and
+//! column sums that scale ART, SMART and MLEM updates are computed once,
+//! before the first iteration, and handed to every step. SIRT and SART
+//! only need the sums, which they take from the same masked sums over any
+//! `LinearOperator` (see `operator::masked_sums`).
+
+use ndarray::{Array1, Array2};
+
+use crate::operator::masked_sums;
+use crate::ReconFloat;
+
+/// Static row and column statistics of a dense system matrix A (shape
+/// (M, N)), restricted to the voxels of an optional mask.
+///
+/// With a mask, masked-out voxels count as absent: rays only see the
+/// unmasked part of their row, and masked columns sum to zero so their
+/// voxels get no additive update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precomputed<T> {
+    /// Squared row norms `||A_i||^2 = sum_j A_ij^2` (length M), ART's step
+    /// normalization.
+    pub row_norms_sq: Array1<T>,
+    /// Row sums `sum_j A_ij` (length M), the ray lengths through the
+    /// unmasked voxels.
+    pub row_sums: Array1<T>,
+    /// Column sums `s_j = sum_i A_ij` (length N). This is the sensitivity
+    /// image MLEM divides by, and SMART's column normalization.
+    pub col_sums: Array1<T>,
+}
+
+impl<T: ReconFloat> Precomputed<T> {
+    /// Compute the sums of `system_matrix` over the voxels kept by `mask`
+    /// (all voxels if `None`).
+    pub fn new(system_matrix: &Array2<T>, mask: Option<&Array1<bool>>) -> Self {
+        let (row_sums, col_sums) = masked_sums(system_matrix, mask);
+        let row_norms_sq = system_matrix
+            .rows()
+            .into_iter()
+            .map(|row| match mask {
+                None => row.dot(&row),
+                Some(mask) => row
+                    .iter()
+                    .zip(mask)
+                    .filter(|&(_, &keep)| keep)
+                    .fold(T::zero(), |acc, (&a, _)| acc + a * a),
+            })
+            .collect();
+        Precomputed {
+            row_norms_sq,
+            row_sums,
+            col_sums,
+        }
+    }
+}
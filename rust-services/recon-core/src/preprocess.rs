@@ -0,0 +1,222 @@
+//! Conversion of raw detector data into the line integrals the solvers
+//! reconstruct from, and rescaling of the rays of the resulting system.
+
+use ndarray::{Array1, Array2, Axis, Zip};
+
+use crate::{ReconError, ReconFloat, SparseSystemMatrix};
+
+/// Smallest transmission `I / I0` passed to the logarithm; darker (or
+/// non-positive) readings are clamped to it, capping the line integral at
+/// `-ln(MIN_TRANSMISSION)` (about 13.8) instead of `+inf`.
+pub const MIN_TRANSMISSION: f64 = 1e-6;
+
+/// Smallest denominator `flat - dark` used by `flat_dark_correct`; rays
+/// whose flat field does not exceed the dark field are divided by this
+/// instead.
+pub const MIN_FLAT_DARK_GAP: f64 = 1e-6;
+
+/// Normalize raw detector readings with dark and flat fields,
+/// `(I - dark) / (flat - dark)`.
+///
+/// `dark` (the reading with the beam off) and `flat` (the reading without
+/// an object) each hold a single value for every ray or one value per ray.
+/// The result is the transmission `I / I0` with detector offset and gain
+/// removed, ready for `log_transform` with `I0 = 1`. Where `flat <= dark`
+/// the denominator is clamped to `MIN_FLAT_DARK_GAP`; those rays are
+/// returned alongside the corrected data so callers can warn about them.
+pub fn flat_dark_correct<T: ReconFloat>(
+    intensities: &Array1<T>,
+    dark: &Array1<T>,
+    flat: &Array1<T>,
+) -> Result<(Array1<T>, Vec<usize>), ReconError> {
+    let m = intensities.len();
+    for (what, field) in [("dark field", dark), ("flat field", flat)] {
+        if field.len() != 1 && field.len() != m {
+            return Err(ReconError::DimensionMismatch {
+                what,
+                expected: m,
+                actual: field.len(),
+            });
+        }
+        if let Some(index) = field.iter().position(|v| !v.is_finite()) {
+            return Err(ReconError::NonFinite { what, index });
+        }
+    }
+    if let Some(index) = intensities.iter().position(|v| !v.is_finite()) {
+        return Err(ReconError::NonFinite {
+            what: "intensities",
+            index,
+        });
+    }
+
+    let min_gap = T::from(MIN_FLAT_DARK_GAP).unwrap();
+    let (dark, flat) = (dark.broadcast(m).unwrap(), flat.broadcast(m).unwrap());
+    let mut clamped = Vec::new();
+    let corrected = Zip::indexed(intensities)
+        .and(dark)
+        .and(flat)
+        .map_collect(|i, &v, &d, &f| {
+            if f <= d {
+                clamped.push(i);
+            }
+            (v - d) / (f - d).max(min_gap)
+        });
+    Ok((corrected, clamped))
+}
+
+/// Convert transmission intensities to line integrals, `-ln(I / I0)`.
+///
+/// `flat_field` holds the unattenuated intensity `I0`, either a single value
+/// for every ray or one value per ray (same length as `intensities`); it
+/// must be positive. Transmissions are clamped into `[MIN_TRANSMISSION, 1]`
+/// before the log: the lower end keeps dead or fully shadowed pixels
+/// finite, the upper end maps noise above the flat field to zero
+/// attenuation rather than to the negative projections MART rejects.
+pub fn log_transform<T: ReconFloat>(
+    intensities: &Array1<T>,
+    flat_field: &Array1<T>,
+) -> Result<Array1<T>, ReconError> {
+    let m = intensities.len();
+    if flat_field.len() != 1 && flat_field.len() != m {
+        return Err(ReconError::DimensionMismatch {
+            what: "flat field",
+            expected: m,
+            actual: flat_field.len(),
+        });
+    }
+    if let Some(index) = intensities.iter().position(|v| !v.is_finite()) {
+        return Err(ReconError::NonFinite {
+            what: "intensities",
+            index,
+        });
+    }
+    if let Some((index, &value)) = flat_field
+        .iter()
+        .enumerate()
+        .find(|(_, &v)| !(v.is_finite() && v > T::zero()))
+    {
+        return Err(ReconError::InvalidFlatField {
+            index,
+            value: value.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+
+    let min_transmission = T::from(MIN_TRANSMISSION).unwrap();
+    let flat_field = flat_field.broadcast(m).unwrap();
+    Ok(Zip::from(intensities)
+        .and(flat_field)
+        .map_collect(|&i, &i0| {
+            let transmission = (i / i0).max(min_transmission).min(T::one());
+            -transmission.ln()
+        }))
+}
+
+/// Scale every ray of `y = A x` to unit length: row `i` of `system_matrix`
+/// and `projections[i]` are both divided by the row's L2 norm `||A_i||`.
+///
+/// Dividing an equation by a constant leaves its solutions alone, so the
+/// scaled system has the same volume as the original one, but rays of very
+/// different lengths then respond alike to a single relaxation value. Rows
+/// that are entirely zero are left as they are. Returns the norms (1 for
+/// zero rows), which `restore_rows` uses to undo the scaling, e.g. before
+/// computing residuals in the original units.
+pub fn normalize_rows<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut Array2<T>,
+) -> Array1<T> {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    let norms: Array1<T> = system_matrix
+        .axis_iter(Axis(0))
+        .map(|row| row_norm(row.iter()))
+        .collect();
+    scale_rows(projections, system_matrix, &norms.mapv(T::recip));
+    norms
+}
+
+/// Sparse counterpart of `normalize_rows`.
+pub fn normalize_rows_sparse<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut SparseSystemMatrix<T>,
+) -> Array1<T> {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    let norms: Array1<T> = (0..projections.len())
+        .map(|i| row_norm(system_matrix.row(i).1.iter()))
+        .collect();
+    scale_rows_sparse(projections, system_matrix, &norms.mapv(T::recip));
+    norms
+}
+
+/// Undo `normalize_rows`, multiplying row `i` and `projections[i]` back by
+/// `norms[i]`.
+pub fn restore_rows<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut Array2<T>,
+    norms: &Array1<T>,
+) {
+    scale_rows(projections, system_matrix, norms);
+}
+
+/// Sparse counterpart of `restore_rows`.
+pub fn restore_rows_sparse<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut SparseSystemMatrix<T>,
+    norms: &Array1<T>,
+) {
+    scale_rows_sparse(projections, system_matrix, norms);
+}
+
+/// L2 norm of a row, or 1 if it is zero so that scaling by it is a no-op.
+fn row_norm<'a, T: ReconFloat>(row: impl Iterator<Item = &'a T>) -> T {
+    let norm = row.fold(T::zero(), |sum, &a_ij| sum + a_ij * a_ij).sqrt();
+    if norm > T::zero() {
+        norm
+    } else {
+        T::one()
+    }
+}
+
+fn scale_rows<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut Array2<T>,
+    factors: &Array1<T>,
+) {
+    assert_eq!(
+        factors.len(),
+        projections.len(),
+        "row factors must have length M"
+    );
+    Zip::from(system_matrix.rows_mut())
+        .and(projections)
+        .and(factors)
+        .for_each(|mut row, y_i, &factor| {
+            row.mapv_inplace(|a_ij| a_ij * factor);
+            *y_i = *y_i * factor;
+        });
+}
+
+fn scale_rows_sparse<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut SparseSystemMatrix<T>,
+    factors: &Array1<T>,
+) {
+    assert_eq!(
+        factors.len(),
+        projections.len(),
+        "row factors must have length M"
+    );
+    for (i, &factor) in factors.iter().enumerate() {
+        system_matrix
+            .row_values_mut(i)
+            .iter_mut()
+            .for_each(|a_ij| *a_ij = *a_ij * factor);
+        projections[i] = projections[i] * factor;
+    }
+}
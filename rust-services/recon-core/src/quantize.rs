@@ -0,0 +1,72 @@
+//! 16-bit quantization of reconstructed volumes for compact storage.
+//!
+//! Voxel values are mapped linearly onto `u16` codes,
+//! `q = round((x - offset) / scale)` clamped to `[0, 65535]`, and restored as
+//! `x = offset + scale * q`. Inside the quantized range the round trip is off
+//! by at most half a step (`scale / 2`).
+
+use ndarray::Array1;
+
+use crate::ReconFloat;
+
+/// Largest `u16` code.
+const MAX_CODE: f64 = u16::MAX as f64;
+
+/// Linear map between voxel values and `u16` codes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantization {
+    /// Value difference between consecutive codes (0 for an empty range).
+    pub scale: f64,
+    /// Value of code 0.
+    pub offset: f64,
+}
+
+impl Quantization {
+    /// Map `[lo, hi]` onto the full `u16` range.
+    ///
+    /// Panics unless `lo` and `hi` are finite with `lo <= hi`.
+    pub fn from_range(lo: f64, hi: f64) -> Self {
+        assert!(
+            lo.is_finite() && hi.is_finite() && lo <= hi,
+            "quantization range must be finite with lo <= hi"
+        );
+        Quantization {
+            scale: (hi - lo) / MAX_CODE,
+            offset: lo,
+        }
+    }
+
+    /// Map the finite min/max of `volume` onto the full `u16` range.
+    pub fn from_volume<T: ReconFloat>(volume: &Array1<T>) -> Self {
+        let (lo, hi) = volume
+            .iter()
+            .map(|v| v.to_f64().unwrap())
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        if lo > hi {
+            // no finite voxels
+            return Self::from_range(0.0, 0.0);
+        }
+        Self::from_range(lo, hi)
+    }
+
+    /// Encode `volume`. Values outside the range clamp to the nearest end;
+    /// NaN maps to 0.
+    pub fn quantize<T: ReconFloat>(&self, volume: &Array1<T>) -> Array1<u16> {
+        volume.mapv(|v| {
+            if self.scale == 0.0 {
+                return 0;
+            }
+            let code = ((v.to_f64().unwrap() - self.offset) / self.scale).round();
+            // `as` saturates, so NaN becomes 0
+            code.clamp(0.0, MAX_CODE) as u16
+        })
+    }
+
+    /// Decode `codes` back to voxel values.
+    pub fn dequantize<T: ReconFloat>(&self, codes: &Array1<u16>) -> Array1<T> {
+        codes.mapv(|q| T::from(self.offset + self.scale * f64::from(q)).unwrap())
+    }
+}
@@ -0,0 +1,262 @@
+//! Smoothness penalties applied to the volume between solver passes.
+//!
+//! The solvers work on a flattened volume; the penalties here view it as a
+//! 2D grid of shape `[rows, cols]` in C order (`j = r * cols + c`), the same
+//! layout `Geometry::volume_shape` uses. `HuberRegularization` and
+//! `total_variation` also take 3D stacks `[slices, rows, cols]`.
+
+use ndarray::Array1;
+
+use crate::filter::{correlate_axis, Border};
+use crate::ReconFloat;
+
+/// Tikhonov (L2) smoothing applied after every pass.
+///
+/// Each pass ends with one gradient step on `weight / 2 * ||D x||^2`, where
+/// `D` takes differences between 4-connected neighbors:
+///
+///   x <- x - weight * L x
+///
+/// with `L` the graph Laplacian from `laplacian`. Steps with `weight` up to
+/// 0.125 keep the smoothing monotone and a positive volume positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct L2Regularization {
+    /// Penalty weight (`lambda_reg`).
+    pub weight: f64,
+    /// Grid shape `[rows, cols]` of the flattened volume.
+    pub volume_shape: [usize; 2],
+}
+
+impl L2Regularization {
+    /// Apply one smoothing step to `volume` in place.
+    pub fn apply<T: ReconFloat>(&self, volume: &mut Array1<T>) {
+        let weight = T::from(self.weight).unwrap();
+        let lap = laplacian(volume, self.volume_shape);
+        volume.zip_mut_with(&lap, |x, &l| *x = *x - weight * l);
+    }
+}
+
+/// Graph Laplacian of a flattened 2D grid: `(L x)_j = sum_k (x_j - x_k)`
+/// over the 4-connected neighbors `k` of voxel `j`.
+///
+/// This is the negative of the usual 5-point stencil `del^2 x`, so
+/// subtracting it smooths. At the grid boundary only neighbors inside the
+/// grid are counted, which amounts to a zero-flux (Neumann) condition: edge
+/// voxels are pulled toward their in-grid neighbors only and a constant
+/// volume is left unchanged.
+pub fn laplacian<T: ReconFloat>(volume: &Array1<T>, volume_shape: [usize; 2]) -> Array1<T> {
+    let [rows, cols] = volume_shape;
+    assert_eq!(
+        volume.len(),
+        rows * cols,
+        "volume length must equal rows * cols"
+    );
+
+    Array1::from_shape_fn(volume.len(), |j| {
+        let (r, c) = (j / cols, j % cols);
+        let x = volume[j];
+        let mut acc = T::zero();
+        if r > 0 {
+            acc = acc + (x - volume[j - cols]);
+        }
+        if r + 1 < rows {
+            acc = acc + (x - volume[j + cols]);
+        }
+        if c > 0 {
+            acc = acc + (x - volume[j - 1]);
+        }
+        if c + 1 < cols {
+            acc = acc + (x - volume[j + 1]);
+        }
+        acc
+    })
+}
+
+/// Huber (edge-preserving) smoothing applied after every pass.
+///
+/// Each pass ends with one gradient step on `weight * sum_j h(|grad x|_j)`,
+/// where the Huber function `h(t) = t^2 / 2` for `t <= delta` and
+/// `delta * (t - delta / 2)` above:
+///
+///   x <- x + weight * div(grad x * min(1, delta / |grad x|))
+///
+/// Voxel differences below `delta` (noise) are smoothed exactly like
+/// `L2Regularization`, which this becomes for an infinite `delta`. Across
+/// edges steeper than `delta` the pull is capped, so edges stay sharp
+/// instead of being blurred, without the staircasing TV produces on smooth
+/// ramps. The gradient is isotropic over all axes of the grid. Steps with
+/// `weight` up to 0.125 on a 2D grid (1/12 on a 3D stack) are stable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HuberRegularization {
+    /// Gradient magnitude (in volume intensity units) where the penalty
+    /// turns from quadratic to linear.
+    pub delta: f64,
+    /// Penalty weight (`lambda_reg`).
+    pub weight: f64,
+    /// Grid shape `[slices, rows, cols]` of the flattened volume; a 2D grid
+    /// is `[1, rows, cols]`.
+    pub volume_shape: [usize; 3],
+}
+
+impl HuberRegularization {
+    /// Apply one smoothing step to `volume` in place.
+    pub fn apply<T: ReconFloat>(&self, volume: &mut Array1<T>) {
+        let shape = self.volume_shape;
+        assert_eq!(
+            volume.len(),
+            shape.iter().product::<usize>(),
+            "volume length must equal slices * rows * cols"
+        );
+        let weight = T::from(self.weight).unwrap();
+        let delta = T::from(self.delta).unwrap();
+
+        let gradient = forward_differences(volume, &shape);
+        let scale = Array1::from_shape_fn(volume.len(), |j| {
+            let norm = gradient
+                .iter()
+                .fold(T::zero(), |acc, g| acc + g[j] * g[j])
+                .sqrt();
+            if norm > delta {
+                delta / norm
+            } else {
+                T::one()
+            }
+        });
+
+        // backward-difference divergence of the capped flux (see `divergence`)
+        let backward = [-T::one(), T::one()];
+        let mut div = Array1::<T>::zeros(volume.len());
+        for (axis, g) in gradient.iter().enumerate() {
+            div = div + correlate_axis(&(g * &scale), &shape, axis, &backward, 1, Border::Zero);
+        }
+        volume.zip_mut_with(&div, |x, &d| *x = *x + weight * d);
+    }
+}
+
+/// Number of inner iterations used by `TvRegularization::apply`.
+const TV_INNER_STEPS: usize = 20;
+
+/// Total-variation (TV) denoising applied every `every` passes.
+///
+/// Replaces the volume `f` by an approximation of the TV proximal point
+///
+///   argmin_u 1/2 * ||u - f||^2 + weight * TV(u)
+///
+/// with `TV(u) = sum_j |grad u|_j` (isotropic, see `total_variation`). Unlike `L2Regularization`
+/// this keeps sharp edges and flattens noise within piecewise-constant
+/// regions. The prox is computed with a few iterations of Chambolle's
+/// projected gradient descent on the dual problem, which is stable for any
+/// `weight` with a fixed step of 1/8.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TvRegularization {
+    /// TV weight (in volume intensity units); larger values smooth more.
+    pub weight: f64,
+    /// Apply after every `every`-th pass (1 = every pass, 0 = never).
+    pub every: usize,
+    /// Grid shape `[rows, cols]` of the flattened volume.
+    pub volume_shape: [usize; 2],
+}
+
+impl TvRegularization {
+    /// Whether the step is due after the 0-based pass `iter`.
+    pub fn is_due(&self, iter: usize) -> bool {
+        self.every > 0 && (iter + 1).is_multiple_of(self.every)
+    }
+
+    /// Apply one TV denoising step to `volume` in place.
+    pub fn apply<T: ReconFloat>(&self, volume: &mut Array1<T>) {
+        let [rows, cols] = self.volume_shape;
+        assert_eq!(
+            volume.len(),
+            rows * cols,
+            "volume length must equal rows * cols"
+        );
+        if self.weight <= 0.0 {
+            return;
+        }
+
+        let weight = T::from(self.weight).unwrap();
+        let tau = T::from(0.125).unwrap();
+        let n = volume.len();
+
+        // dual field p = (px, py), one 2-vector per voxel
+        let mut px = Array1::<T>::zeros(n);
+        let mut py = Array1::<T>::zeros(n);
+        for _ in 0..TV_INNER_STEPS {
+            let div = divergence(&px, &py, self.volume_shape);
+            let v = Array1::from_shape_fn(n, |j| div[j] - volume[j] / weight);
+            let (gx, gy) = gradient(&v, self.volume_shape);
+            for j in 0..n {
+                let norm = (gx[j] * gx[j] + gy[j] * gy[j]).sqrt();
+                let denom = T::one() + tau * norm;
+                px[j] = (px[j] + tau * gx[j]) / denom;
+                py[j] = (py[j] + tau * gy[j]) / denom;
+            }
+        }
+
+        let div = divergence(&px, &py, self.volume_shape);
+        volume.zip_mut_with(&div, |x, &d| *x = *x - weight * d);
+    }
+}
+
+/// Isotropic total variation `TV(x) = sum_j |grad x|_j` of a flattened
+/// `[slices, rows, cols]` grid (a 2D grid is `[1, rows, cols]`).
+///
+/// This is the penalty `TvRegularization` minimizes, on the same forward
+/// differences, so it doubles as a roughness measure of a reconstruction:
+/// noise and streaks raise it, over-smoothing lowers it. Accumulated in
+/// f64.
+pub fn total_variation<T: ReconFloat>(volume: &Array1<T>, volume_shape: [usize; 3]) -> f64 {
+    assert_eq!(
+        volume.len(),
+        volume_shape.iter().product::<usize>(),
+        "volume length must equal slices * rows * cols"
+    );
+    let gradient = forward_differences(volume, &volume_shape);
+    (0..volume.len())
+        .map(|j| {
+            gradient
+                .iter()
+                .map(|g| g[j].to_f64().unwrap().powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .sum()
+}
+
+/// Forward differences of a flattened grid along each of its axes.
+///
+/// The edge voxel is replicated past the end of every axis, so the last
+/// difference along it is zero (zero-flux boundary).
+fn forward_differences<T: ReconFloat>(u: &Array1<T>, shape: &[usize]) -> Vec<Array1<T>> {
+    let forward = [-T::one(), T::one()];
+    (0..shape.len())
+        .map(|axis| correlate_axis(u, shape, axis, &forward, 0, Border::Replicate))
+        .collect()
+}
+
+/// Forward-difference gradient `(d/dc, d/dr)` of a flattened 2D grid (see
+/// `forward_differences`).
+fn gradient<T: ReconFloat>(u: &Array1<T>, volume_shape: [usize; 2]) -> (Array1<T>, Array1<T>) {
+    let mut differences = forward_differences(u, &volume_shape);
+    let gx = differences.pop().unwrap();
+    let gy = differences.pop().unwrap();
+    (gx, gy)
+}
+
+/// Discrete divergence, the negative adjoint of `gradient`.
+///
+/// Uses backward differences with zeros before the first column/row, so the
+/// first one only sees its own value. The dual field is zero in the last
+/// column/row (where `gradient` is), so the last one only sees its
+/// predecessor, matching the zero-flux gradient.
+fn divergence<T: ReconFloat>(
+    px: &Array1<T>,
+    py: &Array1<T>,
+    volume_shape: [usize; 2],
+) -> Array1<T> {
+    let backward = [-T::one(), T::one()];
+    let dx = correlate_axis(px, &volume_shape, 1, &backward, 1, Border::Zero);
+    let dy = correlate_axis(py, &volume_shape, 0, &backward, 1, Border::Zero);
+    dx + dy
+}
@@ -0,0 +1,694 @@
+//! Compressed sparse row (CSR) system matrices.
+//!
+//! Real tomography system matrices are overwhelmingly zero, so storing only
+//! the nonzeros per ray keeps memory proportional to the total ray length
+//! instead of M * N.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use ndarray::{Array1, Array2};
+
+use crate::validation::{
+    check_initial_guess, check_mart_inputs, check_mask, check_ray_weights, check_voxel_weights,
+};
+use crate::{
+    backprojection, cgls_reconstruct, initial_volume, relative_l2_rows, relative_residual,
+    run_iterations, RayUpdate, ReconError, ReconFloat, ReconOptions, ReconReport, RelaxationTuner,
+    RowSchedule, VoxelScale,
+};
+
+/// System matrix A of shape (M, N) stored in CSR form.
+///
+/// The layout matches `scipy.sparse.csr_matrix`: the nonzeros of row `i` are
+/// `data[indptr[i]..indptr[i + 1]]`, located at columns
+/// `indices[indptr[i]..indptr[i + 1]]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseSystemMatrix<T = f32> {
+    n_rows: usize,
+    n_cols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T: ReconFloat> SparseSystemMatrix<T> {
+    /// Build a CSR matrix from its raw parts.
+    ///
+    /// Panics if the parts are inconsistent with `shape` (wrong `indptr`
+    /// length, non-monotonic offsets, or column indices out of range).
+    pub fn new(
+        shape: (usize, usize),
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        data: Vec<T>,
+    ) -> Self {
+        let (n_rows, n_cols) = shape;
+        assert_eq!(indptr.len(), n_rows + 1, "indptr must have M + 1 entries");
+        assert_eq!(indptr[0], 0, "indptr must start at 0");
+        assert!(
+            indptr.windows(2).all(|w| w[0] <= w[1]),
+            "indptr must be non-decreasing"
+        );
+        assert_eq!(
+            indices.len(),
+            data.len(),
+            "indices and data must have equal length"
+        );
+        assert_eq!(
+            indptr[n_rows],
+            data.len(),
+            "indptr[M] must equal the number of nonzeros"
+        );
+        assert!(
+            indices.iter().all(|&j| j < n_cols),
+            "column index out of range"
+        );
+
+        Self {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            data,
+        }
+    }
+
+    /// Convert a dense matrix, keeping only its nonzero entries.
+    pub fn from_dense(dense: &Array2<T>) -> Self {
+        let (n_rows, n_cols) = dense.dim();
+        let mut indptr = Vec::with_capacity(n_rows + 1);
+        let mut indices = Vec::new();
+        let mut data = Vec::new();
+
+        indptr.push(0);
+        for row in dense.rows() {
+            for (j, &a_ij) in row.iter().enumerate() {
+                if a_ij != T::zero() {
+                    indices.push(j);
+                    data.push(a_ij);
+                }
+            }
+            indptr.push(data.len());
+        }
+
+        Self {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            data,
+        }
+    }
+
+    /// Convert COO triplets (`scipy.sparse.coo_matrix`'s `row`, `col` and
+    /// `data`), given in any order.
+    ///
+    /// Entries are sorted by row and column, and duplicates are summed like
+    /// scipy's `tocsr` does; explicit zeros are kept. Panics if the three
+    /// arrays differ in length or an index is out of range for `shape`.
+    pub fn from_coo(shape: (usize, usize), rows: &[usize], cols: &[usize], values: &[T]) -> Self {
+        let (n_rows, n_cols) = shape;
+        assert!(
+            rows.len() == values.len() && cols.len() == values.len(),
+            "row, col and data must have equal length"
+        );
+        assert!(rows.iter().all(|&i| i < n_rows), "row index out of range");
+        assert!(
+            cols.iter().all(|&j| j < n_cols),
+            "column index out of range"
+        );
+
+        // bucket the entries by row (a counting sort), then sort and merge
+        // each row's columns
+        let mut starts = vec![0; n_rows + 1];
+        for &i in rows {
+            starts[i + 1] += 1;
+        }
+        for i in 0..n_rows {
+            starts[i + 1] += starts[i];
+        }
+        let mut next = starts.clone();
+        let mut entries = vec![(0, T::zero()); values.len()];
+        for ((&i, &j), &a_ij) in rows.iter().zip(cols).zip(values) {
+            entries[next[i]] = (j, a_ij);
+            next[i] += 1;
+        }
+
+        let mut indptr = Vec::with_capacity(n_rows + 1);
+        let mut indices = Vec::with_capacity(values.len());
+        let mut data = Vec::with_capacity(values.len());
+        indptr.push(0);
+        for i in 0..n_rows {
+            let row = &mut entries[starts[i]..starts[i + 1]];
+            row.sort_by_key(|&(j, _)| j);
+            for &(j, a_ij) in row.iter() {
+                if indices.len() > indptr[i] && indices.last() == Some(&j) {
+                    let sum = data.last_mut().unwrap();
+                    *sum = *sum + a_ij;
+                } else {
+                    indices.push(j);
+                    data.push(a_ij);
+                }
+            }
+            indptr.push(data.len());
+        }
+
+        Self {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            data,
+        }
+    }
+
+    /// Shape (M, N), mirroring `Array2::dim`.
+    pub fn dim(&self) -> (usize, usize) {
+        (self.n_rows, self.n_cols)
+    }
+
+    /// Number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Row offsets (length M + 1) into `indices` and `data`.
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    /// Column index of every stored entry.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Value of every stored entry.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Matrix-vector product `A * x`.
+    pub fn dot(&self, x: &Array1<T>) -> Array1<T> {
+        assert_eq!(x.len(), self.n_cols);
+        Array1::from_shape_fn(self.n_rows, |i| {
+            let (cols, vals) = self.row(i);
+            cols.iter()
+                .zip(vals)
+                .fold(T::zero(), |acc, (&j, &a_ij)| acc + a_ij * x[j])
+        })
+    }
+
+    /// Transposed product `A^T * y`.
+    pub fn t_dot(&self, y: &Array1<T>) -> Array1<T> {
+        assert_eq!(y.len(), self.n_rows);
+        let mut out = Array1::<T>::zeros(self.n_cols);
+        for i in 0..self.n_rows {
+            let (cols, vals) = self.row(i);
+            for (&j, &a_ij) in cols.iter().zip(vals) {
+                out[j] = out[j] + a_ij * y[i];
+            }
+        }
+        out
+    }
+
+    /// Column indices and values of the stored entries of row `i`.
+    pub fn row(&self, i: usize) -> (&[usize], &[T]) {
+        let range = self.indptr[i]..self.indptr[i + 1];
+        (&self.indices[range.clone()], &self.data[range])
+    }
+
+    /// The matrix made of rows `rows` of this one, in that order (rows may
+    /// repeat).
+    pub fn select_rows(&self, rows: &[usize]) -> Self {
+        let mut indptr = Vec::with_capacity(rows.len() + 1);
+        let mut indices = Vec::new();
+        let mut data = Vec::new();
+
+        indptr.push(0);
+        for &i in rows {
+            let (cols, vals) = self.row(i);
+            indices.extend_from_slice(cols);
+            data.extend_from_slice(vals);
+            indptr.push(data.len());
+        }
+        Self::new((rows.len(), self.n_cols), indptr, indices, data)
+    }
+
+    /// Mutable values of the stored entries of row `i` (in the order of
+    /// `row(i)`).
+    pub(crate) fn row_values_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[self.indptr[i]..self.indptr[i + 1]]
+    }
+
+    /// Stored entries of row `i` whose columns fall in `cols`.
+    ///
+    /// Found by binary search, so the row's column indices must be
+    /// increasing (see `has_sorted_indices`); this holds for matrices from
+    /// `from_dense`, `Geometry::build_system_matrix` and scipy's canonical
+    /// CSR format.
+    pub fn row_in_columns(&self, i: usize, cols: Range<usize>) -> (&[usize], &[T]) {
+        let (indices, data) = self.row(i);
+        let start = indices.partition_point(|&j| j < cols.start);
+        let end = start + indices[start..].partition_point(|&j| j < cols.end);
+        (&indices[start..end], &data[start..end])
+    }
+
+    /// Whether every row stores its column indices in strictly increasing
+    /// order.
+    pub fn has_sorted_indices(&self) -> bool {
+        (0..self.n_rows).all(|i| self.row(i).0.windows(2).all(|w| w[0] < w[1]))
+    }
+
+    /// Sort the entries of every row by column index.
+    pub fn sort_indices(&mut self) {
+        for i in 0..self.n_rows {
+            let range = self.indptr[i]..self.indptr[i + 1];
+            let mut entries: Vec<(usize, T)> = self.indices[range.clone()]
+                .iter()
+                .copied()
+                .zip(self.data[range.clone()].iter().copied())
+                .collect();
+            entries.sort_by_key(|&(j, _)| j);
+            for (k, (j, a_ij)) in range.zip(entries) {
+                self.indices[k] = j;
+                self.data[k] = a_ij;
+            }
+        }
+    }
+}
+
+/// Sparse counterpart of `forward_project`.
+pub fn forward_project_sparse<T: ReconFloat>(
+    system_matrix: &SparseSystemMatrix<T>,
+    volume: &Array1<T>,
+) -> Array1<T> {
+    system_matrix.dot(volume)
+}
+
+/// Sparse counterpart of `back_project` (unnormalized `A^T * y`).
+pub fn back_project_sparse<T: ReconFloat>(
+    system_matrix: &SparseSystemMatrix<T>,
+    projections: &Array1<T>,
+) -> Array1<T> {
+    system_matrix.t_dot(projections)
+}
+
+/// Sparse counterpart of `backprojection`.
+pub fn backprojection_sparse<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+) -> Array1<T> {
+    backprojection(projections, system_matrix)
+}
+
+/// Perform one MART iteration over all rays of a sparse system matrix.
+///
+/// Same update as `mart_step`, but each ray only visits its stored nonzeros.
+pub fn mart_step_sparse<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    volume: &mut Array1<T>,
+    relaxation: T,
+) {
+    let rows: Vec<usize> = (0..system_matrix.dim().0).collect();
+    mart_step_sparse_rows(projections, system_matrix, &rows, volume, relaxation);
+}
+
+/// Sparse counterpart of `mart_step_rows`: one MART iteration visiting the
+/// rays in `rows` order.
+pub fn mart_step_sparse_rows<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    rows: &[usize],
+    volume: &mut Array1<T>,
+    relaxation: T,
+) {
+    let (m, n) = system_matrix.dim();
+    assert_eq!(projections.len(), m);
+    assert_eq!(volume.len(), n);
+
+    sparse_sweep(
+        projections,
+        system_matrix,
+        rows,
+        volume,
+        relaxation,
+        None,
+        &RayUpdate::default(),
+    );
+}
+
+/// Sparse MART sweep; `voxel_scale` selects the column- and/or
+/// voxel-weighted update (see `VoxelScale`).
+fn sparse_sweep<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    rows: &[usize],
+    volume: &mut Array1<T>,
+    relaxation: T,
+    voxel_scale: Option<&VoxelScale<T>>,
+    ray: &RayUpdate<T>,
+) {
+    let entries = |i| {
+        let (cols, vals) = system_matrix.row(i);
+        (T::zero(), cols, vals)
+    };
+    sweep_entries(
+        projections,
+        rows,
+        volume,
+        relaxation,
+        voxel_scale,
+        ray,
+        entries,
+    );
+}
+
+/// MART sweep that only updates the entries `(offset, cols, vals)` returned
+/// by `entries(i)` for each ray; `offset` is the part of `y_hat_i`
+/// contributed by the voxels left out.
+fn sweep_entries<'a, T: ReconFloat>(
+    projections: &Array1<T>,
+    rows: &[usize],
+    volume: &mut Array1<T>,
+    relaxation: T,
+    voxel_scale: Option<&VoxelScale<T>>,
+    ray: &RayUpdate<T>,
+    entries: impl Fn(usize) -> (T, &'a [usize], &'a [T]),
+) {
+    for &i in rows {
+        let Some(relaxation) = ray.relaxation(i, relaxation) else {
+            continue;
+        };
+        let (offset, cols, vals) = entries(i);
+
+        // estimated projection: y_hat_i = sum_j A_ij * x_j
+        let mut y_hat = offset;
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            y_hat = y_hat + a_ij * volume[j];
+        }
+
+        let Some(ratio) = ray.ratio(projections[i], y_hat) else {
+            continue;
+        };
+        if ray.negligible(ratio.powf(relaxation)) {
+            continue;
+        }
+        match voxel_scale {
+            None => {
+                let factor = ratio.powf(relaxation);
+                for (&j, &a_ij) in cols.iter().zip(vals) {
+                    if a_ij > T::zero() {
+                        volume[j] = volume[j] * factor;
+                    }
+                }
+            }
+            Some(VoxelScale::Voxel(weights)) => {
+                let log_factor = relaxation * ratio.ln();
+                for (&j, &a_ij) in cols.iter().zip(vals) {
+                    // a zero weight freezes the voxel (and would give 0 * ln(0) = NaN)
+                    if a_ij > T::zero() && weights[j] > T::zero() {
+                        volume[j] = volume[j] * (log_factor * weights[j]).exp();
+                    }
+                }
+            }
+            Some(VoxelScale::Column(weights)) => {
+                let log_factor = relaxation * ratio.ln();
+                for (&j, &a_ij) in cols.iter().zip(vals) {
+                    if a_ij > T::zero() && weights[j] > T::zero() {
+                        volume[j] = volume[j] * (log_factor * a_ij * weights[j]).exp();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// MART reconstruction loop over a sparse system matrix.
+///
+/// Produces the same result as `mart_reconstruct` on the equivalent dense
+/// matrix.
+pub fn mart_reconstruct_sparse<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    mart_reconstruct_sparse_report(projections, system_matrix, n_iters, relaxation, options)
+        .map(|report| report.volume)
+}
+
+/// Like `mart_reconstruct_sparse`, but also reports the number of
+/// iterations run.
+pub fn mart_reconstruct_sparse_report<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<ReconReport<T>, ReconError> {
+    mart_reconstruct_sparse_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        options,
+        |_, _, _| {},
+    )
+}
+
+/// Sparse MART reconstruction loop that reports progress after every
+/// iteration; see `mart_reconstruct_with_callback` for the callback contract.
+///
+/// Checks the projections and the per-voxel and per-ray options against the
+/// matrix like the dense solver, returning an error instead of panicking on
+/// a mismatch.
+pub fn mart_reconstruct_sparse_with_callback<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_mart_inputs(projections, (m, n), None)?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+    check_ray_weights(options, m)?;
+    check_voxel_weights(options, n)?;
+    let volume = initial_volume(options, n, || {
+        backprojection_sparse(projections, system_matrix)
+    });
+    let mut schedule = RowSchedule::new(m, options.row_order, options.start_iteration);
+    let voxel_scale = VoxelScale::new(options, n, || {
+        system_matrix.t_dot(&Array1::from_elem(m, T::one()))
+    });
+    let ray = RayUpdate::new(options);
+    let mut tuner = options
+        .auto_relaxation
+        .as_ref()
+        .map(|auto| RelaxationTuner::new(auto, m, relaxation));
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            let relaxation = match &mut tuner {
+                Some(tuner) => tuner.next(
+                    volume,
+                    |volume, rows, relaxation| {
+                        sparse_sweep(
+                            projections,
+                            system_matrix,
+                            rows,
+                            volume,
+                            relaxation,
+                            voxel_scale.as_ref(),
+                            &ray,
+                        )
+                    },
+                    |volume, rows| {
+                        relative_l2_rows(projections, rows, |i| {
+                            let (cols, vals) = system_matrix.row(i);
+                            cols.iter()
+                                .zip(vals)
+                                .fold(T::zero(), |acc, (&j, &a_ij)| acc + a_ij * volume[j])
+                        })
+                    },
+                ),
+                None => relaxation,
+            };
+            let rows = schedule.next_order();
+            sparse_sweep(
+                projections,
+                system_matrix,
+                rows,
+                volume,
+                relaxation,
+                voxel_scale.as_ref(),
+                &ray,
+            )
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// Block-coordinate sparse MART: like `mart_reconstruct_sparse`, but each
+/// iteration updates the volume one block of voxels at a time.
+///
+/// The N voxels are split into `n_blocks` contiguous column ranges of
+/// (nearly) equal size. Per iteration, every block in turn gets a full MART
+/// sweep over the rays that only rescales its own voxels: each ray's
+/// estimate is the block's part `sum_{j in block} A_ij x_j`, read through
+/// `SparseSystemMatrix::row_in_columns`, plus the contribution of every other
+/// block, which is frozen for the duration of the sweep. A block sweep thus
+/// only touches the matrix entries in that block's columns.
+///
+/// One block is exactly `mart_reconstruct_sparse`. With more blocks a ray's
+/// mismatch is corrected by one block's voxels at a time, which moves it
+/// only part of the way towards its measurement (the block's share of the
+/// projection), so per iteration the blocked solver lags behind the
+/// whole-volume one. Both have the same fixed points on consistent data, so
+/// the difference is one of convergence speed: expect a somewhat higher
+/// residual for the same iteration count, growing with the number of blocks.
+/// Blocks of many rows of voxels lose little; blocks of a few voxels each
+/// can need several times the iterations.
+///
+/// Rows whose column indices are not sorted are sorted in a private copy of
+/// the matrix first. `ReconOptions::auto_relaxation` is ignored when there
+/// is more than one block; all other options behave (and are checked) as
+/// for `mart_reconstruct_sparse`. More blocks than voxels give one voxel per
+/// block; `n_blocks = 0` is `ReconError::InvalidBlocks`.
+pub fn mart_reconstruct_sparse_blocked<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+    relaxation: T,
+    n_blocks: usize,
+    options: &ReconOptions,
+) -> Result<Array1<T>, ReconError> {
+    mart_reconstruct_sparse_blocked_with_callback(
+        projections,
+        system_matrix,
+        n_iters,
+        relaxation,
+        n_blocks,
+        options,
+        |_, _, _| {},
+    )
+    .map(|report| report.volume)
+}
+
+/// `mart_reconstruct_sparse_blocked` with a progress callback; see
+/// `mart_reconstruct_with_callback` for the callback contract.
+pub fn mart_reconstruct_sparse_blocked_with_callback<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+    relaxation: T,
+    n_blocks: usize,
+    options: &ReconOptions,
+    callback: impl FnMut(usize, &Array1<T>, T),
+) -> Result<ReconReport<T>, ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_mart_inputs(projections, (m, n), None)?;
+    check_initial_guess(options, n)?;
+    check_mask(options, n)?;
+    check_ray_weights(options, m)?;
+    check_voxel_weights(options, n)?;
+    if n_blocks == 0 {
+        return Err(ReconError::InvalidBlocks {
+            blocks: n_blocks,
+            voxels: n,
+        });
+    }
+    let blocks = column_blocks(n, n_blocks);
+    if blocks.len() <= 1 {
+        return mart_reconstruct_sparse_with_callback(
+            projections,
+            system_matrix,
+            n_iters,
+            relaxation,
+            options,
+            callback,
+        );
+    }
+
+    let system_matrix = if system_matrix.has_sorted_indices() {
+        Cow::Borrowed(system_matrix)
+    } else {
+        let mut sorted = system_matrix.clone();
+        sorted.sort_indices();
+        Cow::Owned(sorted)
+    };
+    let system_matrix = system_matrix.as_ref();
+    let volume = initial_volume(options, n, || {
+        backprojection_sparse(projections, system_matrix)
+    });
+    let mut schedule = RowSchedule::new(m, options.row_order, options.start_iteration);
+    let voxel_scale = VoxelScale::new(options, n, || {
+        system_matrix.t_dot(&Array1::from_elem(m, T::one()))
+    });
+    let ray = RayUpdate::new(options);
+
+    Ok(run_iterations(
+        volume,
+        n_iters,
+        relaxation,
+        options,
+        |_, volume, relaxation| {
+            let rows = schedule.next_order();
+            let mut y_hat = system_matrix.dot(volume);
+            for cols in &blocks {
+                let block_part = |volume: &Array1<T>, i| {
+                    let (cols, vals) = system_matrix.row_in_columns(i, cols.clone());
+                    cols.iter()
+                        .zip(vals)
+                        .fold(T::zero(), |acc, (&j, &a_ij)| acc + a_ij * volume[j])
+                };
+                let others = Array1::from_shape_fn(m, |i| y_hat[i] - block_part(volume, i));
+                let entries = |i| {
+                    let (cols, vals) = system_matrix.row_in_columns(i, cols.clone());
+                    (others[i], cols, vals)
+                };
+                sweep_entries(
+                    projections,
+                    rows,
+                    volume,
+                    relaxation,
+                    voxel_scale.as_ref(),
+                    &ray,
+                    entries,
+                );
+                y_hat = Array1::from_shape_fn(m, |i| others[i] + block_part(volume, i));
+            }
+        },
+        |volume| relative_residual(projections, system_matrix, volume),
+        callback,
+    ))
+}
+
+/// Split `0..n` into `n_blocks` contiguous ranges whose lengths differ by at
+/// most one, dropping empty ones.
+fn column_blocks(n: usize, n_blocks: usize) -> Vec<Range<usize>> {
+    (0..n_blocks)
+        .map(|b| b * n / n_blocks..(b + 1) * n / n_blocks)
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// Sparse counterpart of `cgls_reconstruct` (which also accepts a
+/// `SparseSystemMatrix` directly).
+pub fn cgls_reconstruct_sparse<T: ReconFloat>(
+    projections: &Array1<T>,
+    system_matrix: &SparseSystemMatrix<T>,
+    n_iters: usize,
+) -> Result<Array1<T>, ReconError> {
+    cgls_reconstruct(projections, system_matrix, n_iters)
+}
@@ -0,0 +1,226 @@
+//! Multi-energy (spectral) reconstruction, e.g. the two projection sets of a
+//! dual-energy scan.
+//!
+//! Projections are stacked into a `(num_energies, M)` array, one row per
+//! energy channel, and reconstructed channel by channel into a
+//! `(num_energies, N)` volume. The channels share nothing but the grid: the
+//! system matrix is either shared by all energies (one source, one
+//! trajectory) or given per energy (e.g. separate source-detector pairs),
+//! and each channel starts from `options`' initial guess on its own.
+//!
+//! `BasisDecomposition` builds on the reconstructed channels. Each voxel's
+//! attenuation at energy `e` is modelled as a mix of two basis materials,
+//!
+//!   mu_e(j) = c_e1 * a_1(j) + c_e2 * a_2(j)
+//!
+//! with calibration coefficients `c_ek` (the attenuation of material `k` at
+//! energy `e`, in the units of the reconstruction), and the material
+//! amounts `a_k(j)` are solved for voxel by voxel; with more than two
+//! energies in the least-squares sense. The decomposition is only as good
+//! as the channels: beam hardening and noise in the lower-energy channel
+//! carry straight into the material maps.
+
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2};
+
+use crate::{
+    mart_reconstruct, mart_reconstruct_sparse, ReconError, ReconFloat, ReconOptions,
+    SparseSystemMatrix,
+};
+
+/// Reconstruct every row of `projections` with `reconstruct`, which gets
+/// the 0-based energy channel and its projections, and stack the channel
+/// volumes into a `(num_energies, N)` array.
+///
+/// This is the building block for any solver; see
+/// `mart_reconstruct_spectral` for the MART version. Fails with the first
+/// channel's error, or with `DimensionMismatch` if the channels do not all
+/// return volumes of the same length.
+pub fn reconstruct_channels<T: ReconFloat>(
+    projections: &Array2<T>,
+    mut reconstruct: impl FnMut(usize, &Array1<T>) -> Result<Array1<T>, ReconError>,
+) -> Result<Array2<T>, ReconError> {
+    let mut volumes: Vec<Array1<T>> = Vec::with_capacity(projections.nrows());
+    for (energy, channel) in projections.axis_iter(Axis(0)).enumerate() {
+        let volume = reconstruct(energy, &channel.to_owned())?;
+        if let Some(first) = volumes.first().filter(|first| first.len() != volume.len()) {
+            return Err(ReconError::DimensionMismatch {
+                what: "channel volume",
+                expected: first.len(),
+                actual: volume.len(),
+            });
+        }
+        volumes.push(volume);
+    }
+
+    let n = volumes.first().map_or(0, Array1::len);
+    let mut stacked = Array2::zeros((volumes.len(), n));
+    for (mut row, volume) in stacked.axis_iter_mut(Axis(0)).zip(&volumes) {
+        row.assign(volume);
+    }
+    Ok(stacked)
+}
+
+/// Matrix for `energy`: the shared one, or that channel's.
+fn channel_matrix<M>(
+    system_matrices: &[M],
+    num_energies: usize,
+    energy: usize,
+) -> Result<&M, ReconError> {
+    match system_matrices.len() {
+        1 => Ok(&system_matrices[0]),
+        len if len == num_energies => Ok(&system_matrices[energy]),
+        len => Err(ReconError::DimensionMismatch {
+            what: "system matrices (one shared, or one per energy)",
+            expected: num_energies,
+            actual: len,
+        }),
+    }
+}
+
+/// `mart_reconstruct` for every energy channel of `projections`
+/// (`(num_energies, M)`), returning the `(num_energies, N)` volume.
+///
+/// `system_matrices` holds a single matrix shared by all energies or one
+/// per energy (`std::slice::from_ref` turns one matrix into a shared
+/// slice). Input errors are reported as for `mart_reconstruct`, with
+/// indices into the failing channel.
+pub fn mart_reconstruct_spectral<T: ReconFloat, S: Data<Elem = T> + Sync>(
+    projections: &Array2<T>,
+    system_matrices: &[ArrayBase<S, Ix2>],
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array2<T>, ReconError> {
+    let num_energies = projections.nrows();
+    reconstruct_channels(projections, |energy, channel| {
+        let system_matrix = channel_matrix(system_matrices, num_energies, energy)?;
+        mart_reconstruct(channel, system_matrix, n_iters, relaxation, options)
+    })
+}
+
+/// Sparse counterpart of `mart_reconstruct_spectral`.
+///
+/// Only the number of matrices is checked; like `mart_reconstruct_sparse`,
+/// a dimension mismatch within a channel panics.
+pub fn mart_reconstruct_sparse_spectral<T: ReconFloat>(
+    projections: &Array2<T>,
+    system_matrices: &[SparseSystemMatrix<T>],
+    n_iters: usize,
+    relaxation: T,
+    options: &ReconOptions,
+) -> Result<Array2<T>, ReconError> {
+    let num_energies = projections.nrows();
+    reconstruct_channels(projections, |energy, channel| {
+        let system_matrix = channel_matrix(system_matrices, num_energies, energy)?;
+        mart_reconstruct_sparse(channel, system_matrix, n_iters, relaxation, options)
+    })
+}
+
+/// Two-material basis decomposition of reconstructed energy channels; see
+/// the module docs for the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasisDecomposition {
+    /// `coefficients[e] = [c_e1, c_e2]`: attenuation of the two basis
+    /// materials at energy `e`.
+    coefficients: Vec<[f64; 2]>,
+    /// Inverse of the 2x2 normal matrix `C^T C`.
+    inverse_gram: [[f64; 2]; 2],
+}
+
+/// `sin^2` of the angle between the two materials' coefficient vectors
+/// below which they are too close to parallel to tell apart.
+const MIN_MATERIAL_SEPARATION: f64 = 1e-12;
+
+impl BasisDecomposition {
+    /// Decomposition for calibration `coefficients`, one `[c_e1, c_e2]` row
+    /// per energy (at least two).
+    ///
+    /// Fails with `NonFinite` on a NaN or infinite coefficient and with
+    /// `SingularBasis` when the two materials cannot be separated, i.e.
+    /// their coefficients are (nearly) proportional across the energies.
+    pub fn new(coefficients: Vec<[f64; 2]>) -> Result<Self, ReconError> {
+        if let Some(index) = coefficients.iter().flatten().position(|c| !c.is_finite()) {
+            return Err(ReconError::NonFinite {
+                what: "basis coefficients",
+                index,
+            });
+        }
+
+        let (mut g11, mut g12, mut g22) = (0.0, 0.0, 0.0);
+        for &[c1, c2] in &coefficients {
+            g11 += c1 * c1;
+            g12 += c1 * c2;
+            g22 += c2 * c2;
+        }
+        let determinant = g11 * g22 - g12 * g12;
+        // Cauchy-Schwarz bounds the determinant by g11 * g22
+        if determinant <= MIN_MATERIAL_SEPARATION * g11 * g22 {
+            return Err(ReconError::SingularBasis {
+                energies: coefficients.len(),
+            });
+        }
+        let inverse_gram = [
+            [g22 / determinant, -g12 / determinant],
+            [-g12 / determinant, g11 / determinant],
+        ];
+        Ok(Self {
+            coefficients,
+            inverse_gram,
+        })
+    }
+
+    /// Number of energy channels the coefficients describe.
+    pub fn num_energies(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Material amounts `(2, N)` for the reconstructed `channels`
+    /// (`(num_energies, N)`), the least-squares solution of the model for
+    /// every voxel.
+    ///
+    /// Amounts are not clamped: noise can make a material slightly
+    /// negative where it is absent.
+    pub fn decompose<T: ReconFloat>(&self, channels: &Array2<T>) -> Result<Array2<T>, ReconError> {
+        if channels.nrows() != self.num_energies() {
+            return Err(ReconError::DimensionMismatch {
+                what: "energy channels",
+                expected: self.num_energies(),
+                actual: channels.nrows(),
+            });
+        }
+
+        let mut materials = Array2::zeros((2, channels.ncols()));
+        for (j, voxel) in channels.axis_iter(Axis(1)).enumerate() {
+            // C^T mu, then (C^T C)^-1 C^T mu
+            let (mut b1, mut b2) = (0.0, 0.0);
+            for (&[c1, c2], &mu) in self.coefficients.iter().zip(voxel) {
+                let mu = mu.to_f64().unwrap();
+                b1 += c1 * mu;
+                b2 += c2 * mu;
+            }
+            let [[i11, i12], [i21, i22]] = self.inverse_gram;
+            materials[[0, j]] = T::from(i11 * b1 + i12 * b2).unwrap();
+            materials[[1, j]] = T::from(i21 * b1 + i22 * b2).unwrap();
+        }
+        Ok(materials)
+    }
+
+    /// Channels `(num_energies, N)` the model predicts for `materials`
+    /// (`(2, N)`); the inverse of `decompose` for consistent data.
+    pub fn synthesize<T: ReconFloat>(
+        &self,
+        materials: &Array2<T>,
+    ) -> Result<Array2<T>, ReconError> {
+        if materials.nrows() != 2 {
+            return Err(ReconError::DimensionMismatch {
+                what: "basis materials",
+                expected: 2,
+                actual: materials.nrows(),
+            });
+        }
+        let coefficients = Array2::from_shape_fn((self.num_energies(), 2), |(e, k)| {
+            T::from(self.coefficients[e][k]).unwrap()
+        });
+        Ok(coefficients.dot(materials))
+    }
+}
@@ -0,0 +1,145 @@
+//! Incremental MART for projections that arrive over time.
+//!
+//! `MartState` keeps a volume alive between batches of rays instead of
+//! reconstructing from scratch whenever new data comes in.
+
+use ndarray::{Array1, ArrayBase, Axis, Data, Ix2};
+
+use crate::validation::{check_initial_guess, check_mart_inputs, check_mask, check_voxel_weights};
+use crate::{
+    apply_constraints, forward_project, initial_volume, mart_sweep, relative_l2, RayUpdate,
+    ReconError, ReconFloat, ReconOptions, VoxelScale,
+};
+
+/// A long-lived MART reconstruction, refined one batch of rays at a time.
+///
+/// Each `update` runs one MART sweep over the batch's rays, in the order
+/// given, starting from the current volume. Rays from earlier batches are
+/// not stored and not revisited, so this differs from restarting
+/// `mart_reconstruct` on all the rays so far:
+///
+/// - A restart makes `n_iters` passes over every ray; the stream sees each
+///   ray once per `update` call it is passed to. Call `update` again with
+///   the same batch to refine it further, at the cost of fitting that batch
+///   more tightly than older ones.
+/// - With `column_weighted` the update uses the column sums of all rays seen
+///   so far, which are smaller early on than in a full reconstruction.
+/// - The constraints in the options (regularization, clamping, bounds,
+///   mask) are applied after every batch rather than after every pass.
+///
+/// Without column weighting or constraints, feeding the rows of a system
+/// matrix as consecutive batches reproduces one iteration of
+/// `mart_reconstruct` with sequential row order exactly.
+///
+/// `ReconOptions::initial_guess` sets the starting volume;
+/// `InitialGuess::Backprojection` has nothing to backproject and starts at
+/// 1.0. The per-ray options `floor_eps`, `ratio_clamp`, `skip_eps` and
+/// `deterministic` apply to every update. Options tied to a fixed set of rays or a fixed
+/// iteration count (`ray_weights`, `row_order`, `relaxation_schedule`,
+/// `auto_relaxation`, `tolerance`, `patience`, `threads`) are ignored.
+#[derive(Debug, Clone)]
+pub struct MartState<T> {
+    volume: Array1<T>,
+    col_sums: Array1<T>,
+    relaxation: T,
+    options: ReconOptions,
+    ray: RayUpdate<T>,
+    rays_seen: usize,
+    updates: usize,
+}
+
+impl<T: ReconFloat> MartState<T> {
+    /// Start a reconstruction of `n` voxels with the given relaxation.
+    pub fn new(n: usize, relaxation: T, options: &ReconOptions) -> Result<Self, ReconError> {
+        check_initial_guess(options, n)?;
+        check_mask(options, n)?;
+        check_voxel_weights(options, n)?;
+        let options = ReconOptions {
+            ray_weights: None,
+            ..options.clone()
+        };
+        Ok(MartState {
+            volume: initial_volume(&options, n, || Array1::ones(n)),
+            col_sums: Array1::zeros(n),
+            relaxation,
+            ray: RayUpdate::new(&options),
+            options,
+            rays_seen: 0,
+            updates: 0,
+        })
+    }
+
+    /// Refine the volume with a batch of new rays: `new_rows` (shape
+    /// (k, N)) are their system-matrix rows and `new_projections` (length
+    /// k) their measurements.
+    ///
+    /// Returns the relative L2 residual of the batch after the update.
+    pub fn update<S: Data<Elem = T>>(
+        &mut self,
+        new_projections: &Array1<T>,
+        new_rows: &ArrayBase<S, Ix2>,
+    ) -> Result<T, ReconError> {
+        let (k, n) = new_rows.dim();
+        check_mart_inputs(new_projections, (k, self.volume.len()), Some(&self.volume))?;
+        if n != self.volume.len() {
+            return Err(ReconError::DimensionMismatch {
+                what: "system matrix rows",
+                expected: self.volume.len(),
+                actual: n,
+            });
+        }
+
+        self.col_sums
+            .zip_mut_with(&new_rows.sum_axis(Axis(0)), |sum, &col| *sum = *sum + col);
+        let voxel_scale = VoxelScale::new(&self.options, n, || self.col_sums.clone());
+        let rows: Vec<usize> = (0..k).collect();
+        mart_sweep(
+            new_projections,
+            new_rows,
+            &rows,
+            &mut self.volume,
+            self.relaxation,
+            self.updates,
+            voxel_scale.as_ref(),
+            &self.ray,
+        );
+        apply_constraints(&mut self.volume, self.updates, &self.options);
+
+        let residual = relative_l2(new_projections, &forward_project(new_rows, &self.volume));
+        if self.volume.iter().any(|v| !v.is_finite()) {
+            return Err(ReconError::Diverged {
+                iteration: self.updates,
+                residual: residual.to_f64().unwrap_or(f64::NAN),
+            });
+        }
+        self.updates += 1;
+        self.rays_seen += k;
+        Ok(residual)
+    }
+
+    /// The current volume (length N).
+    pub fn volume(&self) -> &Array1<T> {
+        &self.volume
+    }
+
+    /// Consume the state and return the volume.
+    pub fn into_volume(self) -> Array1<T> {
+        self.volume
+    }
+
+    /// Column sums `sum_i A_ij` over every ray passed to `update` so far.
+    pub fn column_sums(&self) -> &Array1<T> {
+        &self.col_sums
+    }
+
+    /// Total number of rays passed to `update` so far, counting a ray
+    /// again each time it is passed.
+    pub fn rays_seen(&self) -> usize {
+        self.rays_seen
+    }
+
+    /// Number of successful `update` calls so far.
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+}
@@ -0,0 +1,361 @@
+//! Sanity checks for system matrices.
+//!
+//! A malformed matrix rarely makes a solver fail loudly: rays without any
+//! nonzero are skipped, voxels no ray touches keep their initial value, and
+//! a single NaN spreads through the whole volume. These checks turn such
+//! inputs into an error listing the offending indices, and
+//! `sanitize_inputs` removes NaN/Inf from real data with detector dropouts.
+
+use std::fmt;
+
+use ndarray::{Array1, Array2, Axis};
+
+use crate::{InitialGuess, ReconError, ReconFloat, ReconOptions, SparseSystemMatrix};
+
+/// Everything `validate_system_matrix` found wrong with a matrix.
+///
+/// Entry positions are `(row, col)`; all lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemMatrixIssues {
+    /// Rays (rows) whose entries are all zero.
+    pub zero_rows: Vec<usize>,
+    /// Voxels (columns) no ray touches.
+    pub zero_cols: Vec<usize>,
+    /// Entries below zero (intersection lengths cannot be negative).
+    pub negative: Vec<(usize, usize)>,
+    /// NaN or infinite entries.
+    pub non_finite: Vec<(usize, usize)>,
+}
+
+impl SystemMatrixIssues {
+    /// True if no problem was found.
+    pub fn is_empty(&self) -> bool {
+        self.zero_rows.is_empty()
+            && self.zero_cols.is_empty()
+            && self.negative.is_empty()
+            && self.non_finite.is_empty()
+    }
+
+    fn into_result(self) -> Result<(), ReconError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(ReconError::InvalidSystemMatrix(self))
+        }
+    }
+}
+
+/// Show at most this many indices per category in the error message.
+const MAX_LISTED: usize = 10;
+
+fn describe<I: fmt::Debug>(label: &str, items: &[I]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let mut text = format!(
+        "{} {label} {:?}",
+        items.len(),
+        &items[..items.len().min(MAX_LISTED)]
+    );
+    if items.len() > MAX_LISTED {
+        text += &format!(" (and {} more)", items.len() - MAX_LISTED);
+    }
+    Some(text)
+}
+
+impl fmt::Display for SystemMatrixIssues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            describe("all-zero rows", &self.zero_rows),
+            describe("all-zero columns", &self.zero_cols),
+            describe("negative entries", &self.negative),
+            describe("non-finite entries", &self.non_finite),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Check a dense system matrix for all-zero rows and columns, negative
+/// entries and NaN/Inf. A matrix with no rows or no columns is
+/// `ReconError::Empty`.
+pub fn validate_system_matrix<T: ReconFloat>(system_matrix: &Array2<T>) -> Result<(), ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_not_empty(m, n)?;
+    let mut issues = SystemMatrixIssues::default();
+    let mut col_touched = vec![false; n];
+
+    for (i, row) in system_matrix.rows().into_iter().enumerate() {
+        let mut row_touched = false;
+        for (j, &a_ij) in row.iter().enumerate() {
+            check_entry(&mut issues, i, j, a_ij);
+            if a_ij != T::zero() {
+                row_touched = true;
+                col_touched[j] = true;
+            }
+        }
+        if !row_touched {
+            issues.zero_rows.push(i);
+        }
+    }
+    issues.zero_cols = untouched(&col_touched);
+
+    issues.into_result()
+}
+
+/// Sparse counterpart of `validate_system_matrix`; explicitly stored zeros
+/// count as zero.
+pub fn validate_system_matrix_sparse<T: ReconFloat>(
+    system_matrix: &SparseSystemMatrix<T>,
+) -> Result<(), ReconError> {
+    let (m, n) = system_matrix.dim();
+    check_not_empty(m, n)?;
+    let mut issues = SystemMatrixIssues::default();
+    let mut col_touched = vec![false; n];
+
+    for i in 0..m {
+        let (cols, vals) = system_matrix.row(i);
+        let mut row_touched = false;
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            check_entry(&mut issues, i, j, a_ij);
+            if a_ij != T::zero() {
+                row_touched = true;
+                col_touched[j] = true;
+            }
+        }
+        if !row_touched {
+            issues.zero_rows.push(i);
+        }
+    }
+    issues.zero_cols = untouched(&col_touched);
+    // CSR rows may list columns in any order
+    issues.negative.sort_unstable();
+    issues.non_finite.sort_unstable();
+
+    issues.into_result()
+}
+
+fn check_entry<T: ReconFloat>(issues: &mut SystemMatrixIssues, i: usize, j: usize, a_ij: T) {
+    if !a_ij.is_finite() {
+        issues.non_finite.push((i, j));
+    } else if a_ij < T::zero() {
+        issues.negative.push((i, j));
+    }
+}
+
+fn untouched(touched: &[bool]) -> Vec<usize> {
+    touched
+        .iter()
+        .enumerate()
+        .filter(|(_, &t)| !t)
+        .map(|(j, _)| j)
+        .collect()
+}
+
+/// What `sanitize_inputs` replaced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// Number of NaN or infinite projections.
+    pub projections: usize,
+    /// Number of NaN or infinite system-matrix entries.
+    pub matrix_entries: usize,
+    /// Rays with a non-finite projection or matrix entry, sorted.
+    pub disabled_rays: Vec<usize>,
+}
+
+impl SanitizeReport {
+    /// True if the inputs were already finite.
+    pub fn is_clean(&self) -> bool {
+        self.disabled_rays.is_empty()
+    }
+}
+
+/// Replace NaN/Inf in the projections and a dense system matrix, disabling
+/// every ray they touch.
+///
+/// A disabled ray has its projection and its whole matrix row set to zero,
+/// which every solver treats as carrying no information: MART, SMART and ART
+/// skip it and SIRT and MLEM give it no weight. Its voxels are still
+/// reconstructed from the other rays. Zeroing only the bad entry instead
+/// would leave a ray whose measurement no longer matches its row.
+pub fn sanitize_inputs<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut Array2<T>,
+) -> SanitizeReport {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    let mut report = SanitizeReport::default();
+    for (i, mut row) in system_matrix.axis_iter_mut(Axis(0)).enumerate() {
+        let bad_entries = row.iter().filter(|a_ij| !a_ij.is_finite()).count();
+        sanitize_ray(&mut report, i, &mut projections[i], bad_entries, || {
+            row.fill(T::zero())
+        });
+    }
+    report
+}
+
+/// Sparse counterpart of `sanitize_inputs`; the sparsity pattern is kept,
+/// with explicit zeros in disabled rows.
+pub fn sanitize_inputs_sparse<T: ReconFloat>(
+    projections: &mut Array1<T>,
+    system_matrix: &mut SparseSystemMatrix<T>,
+) -> SanitizeReport {
+    assert_eq!(
+        projections.len(),
+        system_matrix.dim().0,
+        "projections must have length M"
+    );
+    let mut report = SanitizeReport::default();
+    for i in 0..projections.len() {
+        let row = system_matrix.row_values_mut(i);
+        let bad_entries = row.iter().filter(|a_ij| !a_ij.is_finite()).count();
+        sanitize_ray(&mut report, i, &mut projections[i], bad_entries, || {
+            row.fill(T::zero())
+        });
+    }
+    report
+}
+
+fn sanitize_ray<T: ReconFloat>(
+    report: &mut SanitizeReport,
+    i: usize,
+    projection: &mut T,
+    bad_entries: usize,
+    zero_row: impl FnOnce(),
+) {
+    let bad_projection = !projection.is_finite();
+    if !bad_projection && bad_entries == 0 {
+        return;
+    }
+    report.projections += usize::from(bad_projection);
+    report.matrix_entries += bad_entries;
+    report.disabled_rays.push(i);
+    *projection = T::zero();
+    zero_row();
+}
+
+/// Reject systems with no rays or no voxels.
+pub(crate) fn check_not_empty(m: usize, n: usize) -> Result<(), ReconError> {
+    if m == 0 || n == 0 {
+        return Err(ReconError::Empty { rays: m, voxels: n });
+    }
+    Ok(())
+}
+
+/// Check that `projections` match the matrix `shape` and are finite, as the
+/// additive solvers (which accept negative projections) need.
+pub(crate) fn check_projections<T: ReconFloat>(
+    projections: &Array1<T>,
+    shape: (usize, usize),
+) -> Result<(), ReconError> {
+    check_not_empty(shape.0, shape.1)?;
+    check_len("projections", projections.len(), shape.0)?;
+    check_finite("projections", projections.iter().copied())
+}
+
+/// Cheap O(M + N) checks run by the `Result`-returning MART entry points.
+///
+/// Verifies that `projections` (and `volume`, if given) match the matrix
+/// `shape`, are finite, and that the projections are non-negative. The
+/// matrix entries themselves are not scanned; use `validate_system_matrix`
+/// once up front for that.
+pub(crate) fn check_mart_inputs<T: ReconFloat>(
+    projections: &Array1<T>,
+    shape: (usize, usize),
+    volume: Option<&Array1<T>>,
+) -> Result<(), ReconError> {
+    check_projections(projections, shape)?;
+    if let Some((index, &value)) = projections.iter().enumerate().find(|(_, &y)| y < T::zero()) {
+        return Err(ReconError::NegativeProjection {
+            index,
+            value: value.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+
+    if let Some(volume) = volume {
+        check_len("volume", volume.len(), shape.1)?;
+        check_finite("volume", volume.iter().copied())?;
+    }
+    Ok(())
+}
+
+/// Check that an `InitialGuess::FromArray` has length `n` and is finite.
+pub(crate) fn check_initial_guess(options: &ReconOptions, n: usize) -> Result<(), ReconError> {
+    match &options.initial_guess {
+        InitialGuess::FromArray(volume) => {
+            check_len("initial volume", volume.len(), n)?;
+            check_finite("initial volume", volume.iter().copied())
+        }
+        InitialGuess::Uniform(value) => check_finite("initial volume", std::iter::once(*value)),
+        InitialGuess::Backprojection => Ok(()),
+    }
+}
+
+/// Check that `ReconOptions::mask`, if set, has length `n`.
+pub(crate) fn check_mask(options: &ReconOptions, n: usize) -> Result<(), ReconError> {
+    match &options.mask {
+        Some(mask) => check_len("mask", mask.len(), n),
+        None => Ok(()),
+    }
+}
+
+/// Check that `ReconOptions::ray_weights`, if set, has length `m` and is
+/// finite and non-negative.
+pub(crate) fn check_ray_weights(options: &ReconOptions, m: usize) -> Result<(), ReconError> {
+    let Some(weights) = &options.ray_weights else {
+        return Ok(());
+    };
+    check_len("ray weights", weights.len(), m)?;
+    check_finite("ray weights", weights.iter().copied())?;
+    match weights.iter().position(|&w| w < 0.0) {
+        Some(index) => Err(ReconError::NegativeWeight {
+            index,
+            value: weights[index],
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check that `ReconOptions::voxel_weights`, if set, has length `n` and is
+/// finite and non-negative.
+pub(crate) fn check_voxel_weights(options: &ReconOptions, n: usize) -> Result<(), ReconError> {
+    let Some(weights) = &options.voxel_weights else {
+        return Ok(());
+    };
+    check_len("voxel weights", weights.len(), n)?;
+    check_finite("voxel weights", weights.iter().copied())?;
+    match weights.iter().position(|&w| w < 0.0) {
+        Some(index) => Err(ReconError::NegativeVoxelWeight {
+            index,
+            value: weights[index],
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_len(what: &'static str, actual: usize, expected: usize) -> Result<(), ReconError> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(ReconError::DimensionMismatch {
+            what,
+            expected,
+            actual,
+        })
+    }
+}
+
+fn check_finite<T: ReconFloat>(
+    what: &'static str,
+    values: impl Iterator<Item = T>,
+) -> Result<(), ReconError> {
+    match values.enumerate().find(|(_, v)| !v.is_finite()) {
+        Some((index, _)) => Err(ReconError::NonFinite { what, index }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,78 @@
+use ndarray::{Array1, Array2};
+
+use recon_core::{mart_reconstruct_report, AutoRelaxation, ReconOptions};
+
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix =
+        Array2::from_shape_fn((40, 16), |(i, j)| ((i * 7 + j * 3) % 5) as f64 / 4.0);
+    let phantom = Array1::from_shape_fn(16, |j| 0.5 + (j % 4) as f64 / 3.0);
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+fn auto(candidates: Vec<f64>) -> ReconOptions {
+    ReconOptions {
+        auto_relaxation: Some(AutoRelaxation {
+            candidates,
+            every: 3,
+            holdout: 0.2,
+            seed: 7,
+        }),
+        ..ReconOptions::default()
+    }
+}
+
+#[test]
+fn search_picks_a_better_relaxation() {
+    let (projections, system_matrix) = problem();
+
+    let fixed = mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        12,
+        0.01,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let tuned = mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        12,
+        0.01,
+        &auto(vec![0.01, 0.5, 1.0]),
+    )
+    .unwrap();
+    assert!(
+        tuned.final_residual < 0.5 * fixed.final_residual,
+        "tuned {} vs fixed {}",
+        tuned.final_residual,
+        fixed.final_residual
+    );
+
+    let again = mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        12,
+        0.01,
+        &auto(vec![0.01, 0.5, 1.0]),
+    )
+    .unwrap();
+    assert_eq!(again.volume, tuned.volume);
+}
+
+#[test]
+fn keeps_current_relaxation_when_every_candidate_is_worse() {
+    let (projections, system_matrix) = problem();
+
+    // relaxation 3 overshoots so far that the held-out residual always grows
+    let fixed = mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        12,
+        0.5,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let tuned =
+        mart_reconstruct_report(&projections, &system_matrix, 12, 0.5, &auto(vec![3.0])).unwrap();
+    assert_eq!(tuned.volume, fixed.volume);
+}
@@ -0,0 +1,45 @@
+use ndarray::{Array1, Array2};
+
+use recon_core::{back_project, back_project_sparse, Geometry};
+
+#[test]
+fn single_ray_lights_up_its_voxels() {
+    let geometry = Geometry::from_json(
+        r#"{
+            "kind": "parallel_beam",
+            "num_angles": 6,
+            "angle_start_deg": 10.0,
+            "num_detectors": 10,
+            "volume_shape": [8, 12]
+        }"#,
+    )
+    .unwrap();
+    let sparse = geometry.build_system_matrix::<f64>();
+    let (m, n) = sparse.dim();
+
+    let mut dense = Array2::<f64>::zeros((m, n));
+    for i in 0..m {
+        let (cols, vals) = sparse.row(i);
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            dense[[i, j]] = a_ij;
+        }
+    }
+
+    // an oblique ray (angle 2, detector 3) in a non-square grid catches
+    // transposed indexing
+    let ray = 2 * geometry.num_detectors + 3;
+    let mut projections = Array1::<f64>::zeros(m);
+    projections[ray] = 1.0;
+
+    let (touched, _) = sparse.row(ray);
+    assert!(!touched.is_empty());
+
+    for volume in [
+        back_project(&dense, &projections),
+        back_project_sparse(&sparse, &projections),
+    ] {
+        assert_eq!(volume.len(), n);
+        let lit: Vec<usize> = (0..n).filter(|&j| volume[j] != 0.0).collect();
+        assert_eq!(lit, touched);
+    }
+}
@@ -0,0 +1,107 @@
+use ndarray::{array, Array1};
+
+use recon_core::{
+    mart_reconstruct_sparse, mart_reconstruct_sparse_blocked, phantom, Geometry, PhantomKind,
+    ReconOptions, SparseSystemMatrix,
+};
+
+fn problem() -> (Array1<f64>, SparseSystemMatrix<f64>) {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 12, "num_detectors": 12,
+         "volume_shape": [8, 8]}"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix();
+    let truth = phantom::<f64>(PhantomKind::Shapes, [8, 8]).mapv(|x| x + 0.05);
+    (system_matrix.dot(&truth), system_matrix)
+}
+
+#[test]
+fn row_in_columns_selects_the_range() {
+    let dense = array![
+        [1.0, 0.0, 2.0, 3.0, 0.0, 4.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 5.0]
+    ];
+    let a = SparseSystemMatrix::from_dense(&dense);
+
+    assert_eq!(
+        a.row_in_columns(0, 1..4),
+        (&[2usize, 3][..], &[2.0, 3.0][..])
+    );
+    assert_eq!(a.row_in_columns(0, 0..6), a.row(0));
+    assert_eq!(a.row_in_columns(1, 0..5).0.len(), 0);
+    assert_eq!(a.row_in_columns(1, 5..6), (&[5usize][..], &[5.0][..]));
+}
+
+#[test]
+fn sort_indices_restores_sorted_rows() {
+    let mut a = SparseSystemMatrix::new((1, 4), vec![0, 3], vec![3, 0, 2], vec![1.0f64, 2.0, 3.0]);
+    assert!(!a.has_sorted_indices());
+    a.sort_indices();
+    assert!(a.has_sorted_indices());
+    assert_eq!(a.row(0), (&[0usize, 2, 3][..], &[2.0, 3.0, 1.0][..]));
+}
+
+#[test]
+fn one_block_is_plain_sparse_mart() {
+    let (projections, system_matrix) = problem();
+    let options = ReconOptions::default();
+
+    let plain = mart_reconstruct_sparse(&projections, &system_matrix, 5, 0.5, &options).unwrap();
+    let blocked =
+        mart_reconstruct_sparse_blocked(&projections, &system_matrix, 5, 0.5, 1, &options).unwrap();
+    assert_eq!(blocked, plain);
+}
+
+#[test]
+fn blocks_converge_like_the_whole_volume() {
+    let (projections, system_matrix) = problem();
+    let options = ReconOptions::default();
+    let residual = |volume: &Array1<f64>| {
+        let diff = &system_matrix.dot(volume) - &projections;
+        diff.dot(&diff).sqrt() / projections.dot(&projections).sqrt()
+    };
+
+    let plain = residual(
+        &mart_reconstruct_sparse(&projections, &system_matrix, 30, 0.5, &options).unwrap(),
+    );
+    for n_blocks in [2, 4, 16, 64, 100] {
+        let blocked = mart_reconstruct_sparse_blocked(
+            &projections,
+            &system_matrix,
+            30,
+            0.5,
+            n_blocks,
+            &options,
+        )
+        .unwrap();
+        let blocked = residual(&blocked);
+        assert!(
+            blocked < 4.0 * plain,
+            "{n_blocks} blocks: {blocked} vs {plain}"
+        );
+    }
+}
+
+#[test]
+fn unsorted_rows_give_the_same_result() {
+    let (projections, system_matrix) = problem();
+    let (m, n) = system_matrix.dim();
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    for i in 0..m {
+        let (cols, vals) = system_matrix.row(i);
+        indices.extend(cols.iter().rev());
+        data.extend(vals.iter().rev());
+        indptr.push(indices.len());
+    }
+    let reversed = SparseSystemMatrix::new((m, n), indptr, indices, data);
+
+    let options = ReconOptions::default();
+    let sorted =
+        mart_reconstruct_sparse_blocked(&projections, &system_matrix, 3, 0.5, 4, &options).unwrap();
+    let unsorted =
+        mart_reconstruct_sparse_blocked(&projections, &reversed, 3, 0.5, 4, &options).unwrap();
+    assert_eq!(sorted, unsorted);
+}
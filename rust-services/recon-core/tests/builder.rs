@@ -0,0 +1,143 @@
+use ndarray::array;
+
+use recon_core::{
+    mart_reconstruct_report, mart_reconstruct_sparse_blocked, mart_reconstruct_sparse_report,
+    os_mart_reconstruct, MartBuilder, ReconError, ReconOptions, SparseSystemMatrix, StopCriterion,
+};
+
+#[test]
+fn builder_matches_the_free_functions() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.5, 1.5, 1.0]);
+    let builder = MartBuilder::new()
+        .iterations(40)
+        .relaxation(0.5)
+        .bounds(0.0, 1.2)
+        .mask(array![true, true, false])
+        .tolerance(1e-12);
+
+    let options = ReconOptions {
+        bounds: Some((0.0, 1.2)),
+        mask: Some(array![true, true, false]),
+        tolerance: Some(1e-12),
+        ..ReconOptions::default()
+    };
+    let report = builder.run(&projections, &system_matrix).unwrap();
+    let expected =
+        mart_reconstruct_report(&projections, &system_matrix, 40, 0.5, &options).unwrap();
+    assert_eq!(report.volume, expected.volume);
+    assert_eq!(report.residual_history, expected.residual_history);
+    assert_eq!(report.volume[2], 0.0);
+    assert!(report.volume.iter().all(|&v| v <= 1.2));
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let report = builder.run(&projections, &sparse).unwrap();
+    assert_eq!(
+        report.volume,
+        mart_reconstruct_sparse_report(&projections, &sparse, 40, 0.5, &options)
+            .unwrap()
+            .volume
+    );
+    assert_eq!(builder.options().bounds, Some((0.0, 1.2)));
+}
+
+#[test]
+fn builder_reports_stopping_and_errors_like_the_solver() {
+    let system_matrix = array![[1.0, 1.0], [1.0, 0.0]];
+    let projections = array![3.0_f64, 1.0];
+
+    let mut seen = 0;
+    let report = MartBuilder::new()
+        .iterations(5000)
+        .relaxation(0.5)
+        .configure(|options| options.volume_tolerance = Some(1e-9))
+        .run_with_callback(&projections, &system_matrix, |_, _, _| seen += 1)
+        .unwrap();
+    assert_eq!(report.stopped_by, Some(StopCriterion::VolumeChange));
+    assert_eq!(seen, report.iterations_run);
+    assert!((report.volume[1] - 2.0).abs() < 1e-6, "{}", report.volume);
+
+    let result = MartBuilder::new()
+        .ray_weights(array![1.0])
+        .run(&projections, &system_matrix);
+    assert!(matches!(
+        result,
+        Err(ReconError::DimensionMismatch {
+            expected: 2,
+            actual: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn builder_checks_sparse_inputs() {
+    let sparse = SparseSystemMatrix::from_dense(&array![[1.0, 1.0], [1.0, 0.0]]);
+    let result = MartBuilder::new().run(&array![1.0, 2.0, 3.0], &sparse);
+    assert!(matches!(
+        result,
+        Err(ReconError::DimensionMismatch {
+            expected: 2,
+            actual: 3,
+            ..
+        })
+    ));
+    let result = MartBuilder::new().run(&array![1.0, -2.0], &sparse);
+    assert!(matches!(
+        result,
+        Err(ReconError::NegativeProjection { index: 1, .. })
+    ));
+}
+
+#[test]
+fn builder_runs_subsets_and_blocks() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.5, 1.5, 1.0]);
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let options = ReconOptions::default();
+    let builder = MartBuilder::new().iterations(20).relaxation(0.5);
+
+    let report = builder.clone().subsets(2).run(&projections, &system_matrix);
+    assert_eq!(
+        report.unwrap().volume,
+        os_mart_reconstruct(&projections, &system_matrix, 20, 2, 0.5, &options).unwrap()
+    );
+    let report = builder.clone().blocks(3).run(&projections, &sparse);
+    assert_eq!(
+        report.unwrap().volume,
+        mart_reconstruct_sparse_blocked(&projections, &sparse, 20, 0.5, 3, &options).unwrap()
+    );
+
+    assert_eq!(
+        builder.clone().subsets(2).run(&projections, &sparse).err(),
+        Some(ReconError::Unsupported {
+            what: "ordered-subset MART",
+            matrix: "sparse"
+        })
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .blocks(3)
+            .run(&projections, &system_matrix)
+            .err(),
+        Some(ReconError::Unsupported {
+            what: "voxel-blocked MART",
+            matrix: "dense"
+        })
+    );
+    assert!(matches!(
+        builder.blocks(0).run(&projections, &sparse),
+        Err(ReconError::InvalidBlocks { blocks: 0, .. })
+    ));
+}
@@ -0,0 +1,60 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{cgls_reconstruct, cgls_reconstruct_sparse, SparseSystemMatrix};
+
+/// Overdetermined (8 x 4), well-conditioned system.
+fn system_matrix() -> Array2<f64> {
+    array![
+        [2.0, 0.5, 0.0, 0.1],
+        [0.3, 1.5, 0.2, 0.0],
+        [0.0, 0.4, 1.8, 0.3],
+        [0.1, 0.0, 0.5, 2.2],
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 1.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+fn relative_residual(
+    system_matrix: &Array2<f64>,
+    volume: &Array1<f64>,
+    projections: &Array1<f64>,
+) -> f64 {
+    let diff = system_matrix.dot(volume) - projections;
+    diff.dot(&diff).sqrt() / projections.dot(projections).sqrt()
+}
+
+#[test]
+fn consistent_system_reaches_machine_precision() {
+    let system_matrix = system_matrix();
+    // CGLS is not restricted to positive volumes
+    let truth = array![1.0, -0.5, 2.0, 0.25];
+    let projections = system_matrix.dot(&truth);
+
+    let volume = cgls_reconstruct(&projections, &system_matrix, 20).unwrap();
+    let residual = relative_residual(&system_matrix, &volume, &projections);
+    assert!(residual < 1e-14, "residual {residual:e}");
+    assert!(
+        (&volume - &truth).iter().all(|d| d.abs() < 1e-12),
+        "{volume}"
+    );
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let sparse_volume = cgls_reconstruct_sparse(&projections, &sparse, 20).unwrap();
+    assert!((&sparse_volume - &volume).iter().all(|d| d.abs() < 1e-12));
+}
+
+#[test]
+fn inconsistent_system_satisfies_normal_equations() {
+    let system_matrix = system_matrix();
+    let projections = array![2.1, 1.9, 4.2, 1.3, 0.4, 1.6, 2.2, 1.1];
+
+    let volume = cgls_reconstruct(&projections, &system_matrix, 20).unwrap();
+
+    // the least-squares gradient A^T (A x - y) vanishes at the solution
+    let gradient = system_matrix
+        .t()
+        .dot(&(system_matrix.dot(&volume) - &projections));
+    assert!(gradient.iter().all(|g| g.abs() < 1e-12), "{gradient}");
+}
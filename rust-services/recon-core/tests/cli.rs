@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The toy recorded run: a 2-ray, 2-voxel matrix with a placeholder
+/// geometry.json that has no angle/detector metadata.
+const TOPAS_RUN: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../data/raw/topas_runs/topas_run_0001"
+);
+
+fn output_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("recon_core_cli_{}_{name}.npy", std::process::id()))
+}
+
+fn sidecar_path(output: &Path) -> PathBuf {
+    let mut sidecar = output.as_os_str().to_owned();
+    sidecar.push(".meta.json");
+    PathBuf::from(sidecar)
+}
+
+/// Remove an output and its `<output>.meta.json` sidecar.
+fn remove_outputs(output: &Path) {
+    for path in [output.to_path_buf(), sidecar_path(output)] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn mart_cli(args: &[&str], output: &Path) -> Output {
+    let run = Path::new(TOPAS_RUN);
+    Command::new(env!("CARGO_BIN_EXE_mart_cli"))
+        .arg("--projections")
+        .arg(run.join("projections.npy"))
+        .arg("--system-matrix")
+        .arg(run.join("system_matrix.npy"))
+        .arg("--geometry")
+        .arg(run.join("geometry.json"))
+        .arg("--output")
+        .arg(output)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn precomputed_matrix_runs_with_a_placeholder_geometry() {
+    let output = output_path("placeholder");
+    let result = mart_cli(&["--n-iters", "50", "--relaxation", "0.5"], &output);
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(result.status.success(), "{stderr}");
+    assert!(stderr.contains("no angle/detector metadata"), "{stderr}");
+    assert!(output.exists());
+    remove_outputs(&output);
+}
+
+#[test]
+fn continue_updates_the_output_despite_no_clobber() {
+    let output = output_path("continue");
+    let args = ["--n-iters", "5", "--relaxation", "0.5", "--no-clobber"];
+    let first = mart_cli(&args, &output);
+    assert!(
+        first.status.success(),
+        "{}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+
+    let again = mart_cli(&args, &output);
+    let stderr = String::from_utf8_lossy(&again.stderr);
+    assert!(!again.status.success());
+    assert!(stderr.contains("already exists"), "{stderr}");
+
+    let continued = mart_cli(&[&args[..], &["--continue"]].concat(), &output);
+    let stderr = String::from_utf8_lossy(&continued.stderr);
+    assert!(continued.status.success(), "{stderr}");
+    let meta: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(sidecar_path(&output)).unwrap()).unwrap();
+    assert_eq!(meta["provenance"]["iterations_run"], 10);
+    let previous = &meta["provenance"]["inputs"]["previous_output"];
+    assert_eq!(previous["path"], output.display().to_string());
+    assert_eq!(previous["xxh3_64"].as_str().map(str::len), Some(16));
+    remove_outputs(&output);
+}
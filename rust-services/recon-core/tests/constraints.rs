@@ -0,0 +1,58 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{art_reconstruct, sirt_reconstruct, ReconOptions};
+
+/// Consistent 2x2 system whose exact solution is x = [2, -1].
+fn negative_solution_system() -> (Array1<f32>, Array2<f32>) {
+    let system_matrix = array![[1.0, 1.0], [1.0, 0.0]];
+    let projections = array![1.0, 2.0];
+    (projections, system_matrix)
+}
+
+#[test]
+fn unconstrained_art_goes_negative() {
+    let (projections, system_matrix) = negative_solution_system();
+    let volume = art_reconstruct(
+        &projections,
+        &system_matrix,
+        50,
+        1.0,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+
+    assert!(
+        volume.iter().any(|&v| v < 0.0),
+        "expected a negative voxel, got {volume:?}"
+    );
+}
+
+#[test]
+fn nonneg_clamp_keeps_art_volume_nonnegative() {
+    let (projections, system_matrix) = negative_solution_system();
+    let options = ReconOptions {
+        clamp_nonnegative: true,
+        ..ReconOptions::default()
+    };
+    let volume = art_reconstruct(&projections, &system_matrix, 50, 1.0, &options).unwrap();
+
+    assert!(
+        volume.iter().all(|&v| v >= 0.0),
+        "clamped volume went negative: {volume:?}"
+    );
+}
+
+#[test]
+fn nonneg_clamp_keeps_sirt_volume_nonnegative() {
+    let (projections, system_matrix) = negative_solution_system();
+    let options = ReconOptions {
+        clamp_nonnegative: true,
+        ..ReconOptions::default()
+    };
+    let volume = sirt_reconstruct(&projections, &system_matrix, 200, 1.0, &options).unwrap();
+
+    assert!(
+        volume.iter().all(|&v| v >= 0.0),
+        "clamped volume went negative: {volume:?}"
+    );
+}
@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use ndarray::{array, Array1};
+use ndarray_npy::NpzWriter;
+
+use recon_core::io::load_csr_npz;
+use recon_core::SparseSystemMatrix;
+
+/// Unsorted triplets with a duplicate (1, 0) and an explicit zero at (2, 2).
+fn triplets() -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+    (
+        vec![2, 0, 1, 0, 1, 2],
+        vec![0, 2, 0, 0, 0, 2],
+        vec![4.0, 2.0, 1.0, 3.0, 0.5, 0.0],
+    )
+}
+
+fn save_coo(name: &str, rows: &[i64], cols: &[i64], data: &[f64], shape: [i64; 2]) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("recon_core_coo_{}_{name}.npz", std::process::id()));
+    let mut npz = NpzWriter::new(File::create(&path).unwrap());
+    npz.add_array("row.npy", &Array1::from(rows.to_vec()))
+        .unwrap();
+    npz.add_array("col.npy", &Array1::from(cols.to_vec()))
+        .unwrap();
+    npz.add_array("data.npy", &Array1::from(data.to_vec()))
+        .unwrap();
+    npz.add_array("shape.npy", &Array1::from(shape.to_vec()))
+        .unwrap();
+    npz.finish().unwrap();
+    path
+}
+
+#[test]
+fn coo_entries_are_sorted_and_duplicates_summed() {
+    let (rows, cols, data) = triplets();
+    let matrix = SparseSystemMatrix::from_coo((3, 3), &rows, &cols, &data);
+
+    assert_eq!(matrix.indptr(), &[0, 2, 3, 5]);
+    assert_eq!(matrix.indices(), &[0, 2, 0, 0, 2]);
+    assert_eq!(matrix.data(), &[3.0, 2.0, 1.5, 4.0, 0.0]);
+    assert!(matrix.has_sorted_indices());
+
+    let dense = array![[3.0, 0.0, 2.0], [1.5, 0.0, 0.0], [4.0, 0.0, 0.0]];
+    let x = array![1.0, 2.0, 3.0];
+    assert_eq!(matrix.dot(&x), dense.dot(&x));
+}
+
+#[test]
+fn coo_npz_loads_as_csr() {
+    let (rows, cols, data) = triplets();
+    let as_i64 = |v: &[usize]| v.iter().map(|&v| v as i64).collect::<Vec<_>>();
+    let path = save_coo("valid", &as_i64(&rows), &as_i64(&cols), &data, [3, 4]);
+    let expected = SparseSystemMatrix::from_coo((3, 4), &rows, &cols, &data);
+    for mmap in [false, true] {
+        let matrix: SparseSystemMatrix<f64> = load_csr_npz(&path, mmap).unwrap();
+        assert_eq!(matrix, expected);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    let path = save_coo("out_of_range", &[0, 3], &[0, 1], &[1.0, 2.0], [3, 4]);
+    let err = load_csr_npz::<f64>(&path, false).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(err.to_string().contains("inconsistent COO arrays"), "{err}");
+}
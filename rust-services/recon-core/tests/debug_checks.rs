@@ -0,0 +1,36 @@
+#![cfg(debug_assertions)]
+
+use ndarray::array;
+
+use recon_core::{debug_skipped_rays, mart_reconstruct, mart_step, InitialGuess, ReconOptions};
+
+#[test]
+fn rays_with_a_zero_estimate_are_counted() {
+    // ray 0 only crosses a zero voxel, so y_hat_0 = 0 and it is skipped
+    let system_matrix = array![[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
+    let mut volume = array![0.0, 1.0];
+    mart_step(&array![1.0, 2.0, 3.0], &system_matrix, &mut volume, 1.0).unwrap();
+    assert_eq!(debug_skipped_rays(), 1);
+    assert_eq!(volume[0], 0.0);
+}
+
+#[test]
+#[should_panic(expected = "MART iteration 0, ray 0: ratio inf is not finite")]
+fn infinite_ratio_panics_at_the_ray_that_made_it() {
+    // a subnormal y_hat makes the ratio infinite
+    let system_matrix = array![[1e-10, 1.0]];
+    let mut volume = array![1e-300, 0.0];
+    mart_step(&array![1e300], &system_matrix, &mut volume, 1.0).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "MART iteration 4, ray 0: update made voxel Some(0) not finite")]
+fn overflowing_voxel_panics_with_its_iteration() {
+    // with relaxation 3 the exponent of x overshoots 0 further each pass
+    // (1e-10, 1e20, 1e-40, 1e80, 1e-160); the next factor 1e480 overflows
+    let options = ReconOptions {
+        initial_guess: InitialGuess::FromArray(array![1e-10]),
+        ..ReconOptions::default()
+    };
+    let _ = mart_reconstruct(&array![1.0], &array![[1.0]], 10, 3.0, &options);
+}
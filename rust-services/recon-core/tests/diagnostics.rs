@@ -0,0 +1,101 @@
+use ndarray::{array, Array2};
+
+use recon_core::diagnostics::SAFE_RELAXATION_FRACTION;
+use recon_core::{
+    estimate_condition, estimate_condition_sparse, estimate_sirt_spectral_radius,
+    relative_residual, safe_relaxation, sirt_reconstruct, ReconOptions, SparseSystemMatrix,
+};
+
+#[test]
+fn recovers_the_singular_values_of_a_scaled_identity() {
+    // 12 x 10: singular values 1..=10, two empty rays
+    let system_matrix =
+        Array2::from_shape_fn((12, 10), |(i, j)| if i == j { (j + 1) as f64 } else { 0.0 });
+
+    let estimate = estimate_condition(&system_matrix, 30);
+    // power iteration closes in on sigma_max from below
+    assert!(
+        estimate.sigma_max <= 10.0 && estimate.sigma_max > 10.0 - 1e-4,
+        "{estimate:?}"
+    );
+    assert!((estimate.sigma_min - 1.0).abs() < 1e-9, "{estimate:?}");
+    assert!((estimate.condition_number() - 10.0).abs() < 1e-3);
+
+    let sparse = estimate_condition_sparse(&SparseSystemMatrix::from_dense(&system_matrix), 30);
+    assert!((sparse.condition_number() - estimate.condition_number()).abs() < 1e-9);
+}
+
+#[test]
+fn underdetermined_systems_ignore_the_null_space() {
+    let system_matrix = array![
+        [1.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 2.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 3.0, 0.0, 0.0f32]
+    ];
+
+    let estimate = estimate_condition(&system_matrix, 20);
+    assert!(
+        (estimate.condition_number() - 3.0).abs() < 1e-3,
+        "{estimate:?}"
+    );
+}
+
+#[test]
+fn rank_deficient_matrix_is_flagged() {
+    // voxels 0 and 1 are crossed by exactly the same rays
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.5, 0.5, 1.0],
+        [0.0, 0.0, 2.0],
+        [1.0, 1.0, 1.0]
+    ];
+
+    let estimate = estimate_condition(&system_matrix, 20);
+    assert!(estimate.condition_number() > 1e6, "{estimate:?}");
+}
+
+#[test]
+fn sirt_spectral_radius_of_a_nonnegative_matrix_is_one() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.5, 0.5, 1.0],
+        [0.0, 0.0, 2.0],
+        [1.0, 3.0, 1.0]
+    ];
+
+    let rho = estimate_sirt_spectral_radius(&system_matrix, None, 30);
+    assert!(rho <= 1.0 + 1e-12 && rho > 1.0 - 1e-6, "{rho}");
+    let sparse =
+        estimate_sirt_spectral_radius(&SparseSystemMatrix::from_dense(&system_matrix), None, 30);
+    assert!((sparse - rho).abs() < 1e-12);
+    assert!((safe_relaxation(rho) - 2.0 * SAFE_RELAXATION_FRACTION).abs() < 1e-5);
+}
+
+#[test]
+fn safe_relaxation_converges_where_a_larger_one_diverges() {
+    // negative lobes push the spectral radius of C A^T R A past 1
+    let system_matrix = array![
+        [0.0, 2.0, -0.5],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, -0.5],
+        [2.0, -0.5, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.3, 0.9, 0.6]);
+
+    let rho = estimate_sirt_spectral_radius(&system_matrix, None, 50);
+    assert!((rho - 2.4330).abs() < 1e-3, "{rho}");
+
+    let options = ReconOptions::default();
+    let residual = |relaxation: f64| {
+        let volume =
+            sirt_reconstruct(&projections, &system_matrix, 200, relaxation, &options).unwrap();
+        relative_residual(&projections, &system_matrix, &volume)
+    };
+    assert!(residual(safe_relaxation(rho)) < 1e-6);
+    let diverged = residual(1.0);
+    assert!(
+        diverged > 1.0 || diverged.is_nan(),
+        "relaxation 1 is above the bound {}",
+        2.0 / rho
+    );
+}
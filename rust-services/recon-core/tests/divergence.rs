@@ -0,0 +1,53 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{art_reconstruct_report, mart_reconstruct, ReconError, ReconOptions};
+
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+// debug builds assert on every MART ray that the update stays finite
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "not finite"))]
+fn non_finite_volume_is_reported_as_diverged() {
+    let (projections, system_matrix) = problem();
+
+    let result = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        50,
+        5.0,
+        &ReconOptions::default(),
+    );
+    assert!(
+        matches!(result, Err(ReconError::Diverged { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn rising_residual_stops_after_patience() {
+    let (projections, system_matrix) = problem();
+    let options = ReconOptions {
+        patience: Some(3),
+        ..ReconOptions::default()
+    };
+
+    // ART overshoots for relaxation > 2, so the residual grows every pass
+    let report = art_reconstruct_report(&projections, &system_matrix, 20, 3.0, &options).unwrap();
+    assert_eq!(report.diverged_at, Some(3));
+    assert_eq!(report.iterations_run, 4);
+    assert!(report.check_diverged().is_err());
+
+    let stable = art_reconstruct_report(&projections, &system_matrix, 20, 0.5, &options).unwrap();
+    assert_eq!(stable.diverged_at, None);
+    assert_eq!(stable.iterations_run, 20);
+}
@@ -0,0 +1,69 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    art_reconstruct, cgls_reconstruct, landweber_reconstruct, mart_reconstruct,
+    mart_reconstruct_sparse, mart_reconstruct_sparse_blocked, mlem_reconstruct,
+    os_mart_reconstruct, sart_reconstruct, sirt_reconstruct, smart_reconstruct,
+    validate_system_matrix, validate_system_matrix_sparse, ReconError, ReconOptions,
+    SparseSystemMatrix,
+};
+
+/// Every solver on the same system, dense and sparse. Landweber steps by
+/// `A^T r` without normalization, so it gets the relaxation that solves the
+/// 1x1 system below in one step.
+fn all_solvers(
+    projections: &Array1<f64>,
+    system_matrix: &Array2<f64>,
+) -> Vec<Result<Array1<f64>, ReconError>> {
+    let options = ReconOptions::default();
+    let sparse = SparseSystemMatrix::from_dense(system_matrix);
+    vec![
+        mart_reconstruct(projections, system_matrix, 3, 1.0, &options),
+        os_mart_reconstruct(projections, system_matrix, 3, 2, 1.0, &options),
+        smart_reconstruct(projections, system_matrix, 3, 1.0, &options),
+        mlem_reconstruct(projections, system_matrix, 3),
+        art_reconstruct(projections, system_matrix, 3, 1.0, &options),
+        sirt_reconstruct(projections, system_matrix, 3, 1.0, &options),
+        sart_reconstruct(projections, system_matrix, 3, 1.0, &options),
+        landweber_reconstruct(projections, system_matrix, 3, 0.25, &options),
+        cgls_reconstruct(projections, system_matrix, 3),
+        mart_reconstruct_sparse(projections, &sparse, 3, 1.0, &options),
+        mart_reconstruct_sparse_blocked(projections, &sparse, 3, 1.0, 2, &options),
+        sirt_reconstruct(projections, &sparse, 3, 1.0, &options),
+    ]
+}
+
+#[test]
+fn no_rays_is_empty() {
+    let system_matrix = Array2::<f64>::zeros((0, 4));
+    for result in all_solvers(&Array1::zeros(0), &system_matrix) {
+        assert_eq!(result, Err(ReconError::Empty { rays: 0, voxels: 4 }));
+    }
+    assert_eq!(
+        validate_system_matrix(&system_matrix),
+        Err(ReconError::Empty { rays: 0, voxels: 4 })
+    );
+}
+
+#[test]
+fn no_voxels_is_empty() {
+    let system_matrix = Array2::<f64>::zeros((3, 0));
+    for result in all_solvers(&array![1.0, 2.0, 3.0], &system_matrix) {
+        assert_eq!(result, Err(ReconError::Empty { rays: 3, voxels: 0 }));
+    }
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    assert_eq!(
+        validate_system_matrix_sparse(&sparse),
+        Err(ReconError::Empty { rays: 3, voxels: 0 })
+    );
+}
+
+#[test]
+fn single_ray_single_voxel_is_solved() {
+    let system_matrix = array![[2.0]];
+    validate_system_matrix(&system_matrix).unwrap();
+    for volume in all_solvers(&array![3.0], &system_matrix) {
+        let volume = volume.unwrap();
+        assert!((volume[0] - 1.5).abs() < 1e-12, "{volume}");
+    }
+}
@@ -0,0 +1,80 @@
+use ndarray::{array, Array1};
+
+use recon_core::filter::{correlate_axis, gaussian_blur, resample_linear, Border};
+
+#[test]
+fn correlate_handles_both_borders() {
+    let volume = array![1.0, 2.0, 4.0];
+    let kernel = [1.0, 10.0, 100.0];
+
+    let replicated = correlate_axis(&volume, &[3], 0, &kernel, 1, Border::Replicate);
+    assert_eq!(
+        replicated,
+        array![1.0 + 10.0 + 200.0, 1.0 + 20.0 + 400.0, 2.0 + 40.0 + 400.0]
+    );
+    let zero = correlate_axis(&volume, &[3], 0, &kernel, 1, Border::Zero);
+    assert_eq!(zero, array![10.0 + 200.0, 1.0 + 20.0 + 400.0, 2.0 + 40.0]);
+
+    // along the first axis of a [2, 3] grid the stride is a whole row
+    let grid = array![1.0, 2.0, 3.0, 10.0, 20.0, 30.0];
+    let down = correlate_axis(&grid, &[2, 3], 0, &[-1.0, 1.0], 0, Border::Replicate);
+    assert_eq!(down, array![9.0, 18.0, 27.0, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn blur_keeps_constants_and_skips_zero_sigma() {
+    let constant = Array1::from_elem(4 * 5 * 6, 2.5f64);
+    let blurred = gaussian_blur(&constant, &[4, 5, 6], 1.3);
+    assert!(blurred.iter().all(|v| (v - 2.5).abs() < 1e-12), "{blurred}");
+
+    let volume = Array1::from_shape_fn(30, |j| (j * 7 % 11) as f64);
+    assert_eq!(gaussian_blur(&volume, &[5, 6], 0.0), volume);
+}
+
+#[test]
+fn impulse_spreads_into_a_separable_gaussian() {
+    let n = 11;
+    let mut impulse = Array1::<f64>::zeros(n * n * n);
+    let center = (5 * n + 5) * n + 5;
+    impulse[center] = 1.0;
+
+    let blurred = gaussian_blur(&impulse, &[n, n, n], 1.0);
+    assert!((blurred.sum() - 1.0).abs() < 1e-12);
+    // the 1D kernel along one axis relative to its center tap
+    let ratio = blurred[center + 1] / blurred[center];
+    assert!((ratio - (-0.5f64).exp()).abs() < 1e-12);
+    // separable: the diagonal neighbor picks up the factor twice
+    assert!((blurred[center + n + 1] / blurred[center] - ratio * ratio).abs() < 1e-12);
+    assert_eq!(blurred[center + 1], blurred[center - 1]);
+    assert_eq!(blurred[center + n * n], blurred[center + 1]);
+
+    // single-slice stacks are only blurred in-plane
+    let plane = Array1::from_shape_fn(n * n, |j| (j % 5) as f64);
+    assert_eq!(
+        gaussian_blur(&plane, &[1, n, n], 0.8),
+        gaussian_blur(&plane, &[n, n], 0.8)
+    );
+}
+
+#[test]
+fn linear_upsampling_interpolates_between_centers() {
+    // centers of the 4 output voxels sit at -0.25, 0.25, 0.75 and 1.25
+    let upsampled = resample_linear(&array![1.0, 3.0], &[2], &[4]);
+    assert_eq!(upsampled, array![1.0, 1.5, 2.5, 3.0]);
+
+    let constant = resample_linear(&Array1::from_elem(3 * 2 * 2, 0.7), &[3, 2, 2], &[6, 4, 5]);
+    assert_eq!(constant.len(), 6 * 4 * 5);
+    assert!(constant.iter().all(|&v| (v - 0.7f64).abs() < 1e-12));
+}
+
+#[test]
+fn bilinear_upsampling_is_separable() {
+    // a ramp along the columns stays a ramp; rows are just repeated
+    let ramp = array![0.0, 2.0, 0.0, 2.0];
+    let upsampled = resample_linear(&ramp, &[2, 2], &[3, 4]);
+    let row = array![0.0, 0.5, 1.5, 2.0];
+    for r in 0..3 {
+        assert_eq!(upsampled.slice(ndarray::s![r * 4..(r + 1) * 4]), row);
+    }
+    assert_eq!(resample_linear(&ramp, &[2, 2], &[2, 2]), ramp);
+}
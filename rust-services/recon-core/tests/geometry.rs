@@ -0,0 +1,302 @@
+use ndarray::{Array1, Axis};
+
+use recon_core::{
+    cgls_reconstruct, mart_reconstruct_sparse, Geometry, GeometryKind, ReconOptions,
+    SparseSystemMatrix,
+};
+
+/// Detector bins (per angle) that see a nonzero projection of `phantom`.
+fn lit_bins(
+    geometry: &Geometry,
+    system_matrix: &SparseSystemMatrix<f64>,
+    phantom: &Array1<f64>,
+) -> Vec<Vec<usize>> {
+    let projections = system_matrix.dot(phantom);
+    (0..geometry.num_angles)
+        .map(|a| {
+            (0..geometry.num_detectors)
+                .filter(|&d| projections[a * geometry.num_detectors + d] > 0.0)
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn fan_beam_single_voxel_hits_central_bins() {
+    // 5x5 grid, source 10 units from the axis, 9-pixel flat detector whose
+    // pixels step tan(gamma) by 0.04. Only the three central rays cross the
+    // middle voxel ([-0.5, 0.5]^2): |tan(gamma)| * 10.5 < 0.5 for them and
+    // |tan(gamma)| * 9.5 > 0.5 for the next ones out.
+    let fan_angle = 2.0 * 0.18f64.atan().to_degrees();
+    let geometry = Geometry::from_json(&format!(
+        r#"{{
+            "kind": "fan_beam",
+            "source_to_detector": 20.0,
+            "source_to_center": 10.0,
+            "fan_angle": {fan_angle},
+            "num_angles": 2,
+            "num_detectors": 9,
+            "volume_shape": [5, 5]
+        }}"#
+    ))
+    .unwrap();
+    assert!(matches!(geometry.kind, GeometryKind::FanBeam { .. }));
+
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    assert_eq!(system_matrix.dim(), (18, 25));
+
+    let mut phantom = Array1::<f64>::zeros(25);
+    phantom[2 * 5 + 2] = 1.0;
+
+    assert_eq!(
+        lit_bins(&geometry, &system_matrix, &phantom),
+        vec![vec![3, 4, 5], vec![3, 4, 5]]
+    );
+}
+
+#[test]
+fn fan_beam_rejects_source_inside_grid() {
+    let err = Geometry::from_json(
+        r#"{
+            "kind": "fan_beam",
+            "source_to_detector": 8.0,
+            "source_to_center": 2.0,
+            "fan_angle": 30.0,
+            "num_angles": 4,
+            "num_detectors": 8,
+            "volume_shape": [8, 8]
+        }"#,
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn explicit_angle_list_builds_exactly_those_angles() {
+    let uniform = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 4, "num_detectors": 6, "volume_shape": [6, 6]}"#,
+    )
+    .unwrap();
+    let listed = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "angles_deg": [0, 45, 90, 135], "num_detectors": 6,
+         "volume_shape": [6, 6]}"#,
+    )
+    .unwrap();
+    assert_eq!(listed.num_angles, 4);
+    assert_eq!(listed.angles_rad(), uniform.angles_rad());
+    assert_eq!(
+        listed.build_system_matrix::<f64>(),
+        uniform.build_system_matrix::<f64>()
+    );
+
+    // non-uniform, limited-angle sampling: three views bunched near 0
+    let sparse_view = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "angles_deg": [-10, 0, 2.5], "num_detectors": 6,
+         "volume_shape": [6, 6]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        sparse_view.angles_rad(),
+        vec![-10f64.to_radians(), 0.0, 2.5f64.to_radians()]
+    );
+    assert_eq!(sparse_view.build_system_matrix::<f64>().dim(), (18, 36));
+    assert!(sparse_view.check_num_rays(18).is_ok());
+    let err = sparse_view.check_num_rays(24).unwrap_err().to_string();
+    assert!(err.contains("3 angles x 6 detectors = 18 rays"), "{err}");
+}
+
+#[test]
+fn angle_list_must_match_num_angles() {
+    let err = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 5, "angles_deg": [0, 90], "num_detectors": 4,
+         "volume_shape": [4, 4]}"#,
+    );
+    assert!(err
+        .unwrap_err()
+        .to_string()
+        .contains("angles_deg lists 2 angles but num_angles is 5"));
+
+    let err = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "angles_deg": [], "num_detectors": 4,
+         "volume_shape": [4, 4]}"#,
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn angle_subset_selects_matrix_rows() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 6, "num_detectors": 5, "angle_start_deg": 7.5,
+            "volume_shape": [5, 5]}"#,
+    )
+    .unwrap();
+    let full = geometry.build_system_matrix::<f64>();
+
+    let angles = [4, 1, 2];
+    let subset = geometry.select_angles(&angles).unwrap();
+    assert_eq!(subset.num_angles, 3);
+    let rows = geometry.angle_rows(&angles);
+    assert_eq!(
+        rows,
+        [20, 21, 22, 23, 24, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]
+    );
+    assert_eq!(subset.build_system_matrix::<f64>(), full.select_rows(&rows));
+
+    assert!(geometry.select_angles(&[]).is_err());
+    assert!(geometry.select_angles(&[6]).is_err());
+    assert!(geometry.select_angles(&[1, 1]).is_err());
+}
+
+#[test]
+fn selecting_every_angle_reproduces_the_full_reconstruction() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 8, "num_detectors": 6, "volume_shape": [6, 6]}"#,
+    )
+    .unwrap();
+    let full = geometry.build_system_matrix::<f64>();
+    let projections = full.dot(&Array1::from_shape_fn(36, |j| 0.5 + (j % 5) as f64 / 4.0));
+
+    let all: Vec<usize> = (0..8).collect();
+    let subset = geometry.select_angles(&all).unwrap();
+    let rows = geometry.angle_rows(&all);
+    let options = ReconOptions::default();
+    let selected = projections.select(Axis(0), &rows);
+    assert_eq!(
+        mart_reconstruct_sparse(&selected, &subset.build_system_matrix(), 10, 0.5, &options)
+            .unwrap(),
+        mart_reconstruct_sparse(&projections, &full, 10, 0.5, &options).unwrap(),
+    );
+}
+
+#[test]
+fn system_matrix_must_match_the_geometry() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 6, "num_detectors": 5, "volume_shape": [4, 4]}"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    geometry.check_system_matrix(system_matrix.dim()).unwrap();
+
+    // a matrix precomputed for a different scan: 8 detectors over a 4x5 grid
+    let other = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 6, "num_detectors": 8, "volume_shape": [4, 5]}"#,
+    )
+    .unwrap();
+    let message = geometry
+        .check_system_matrix(other.build_system_matrix::<f64>().dim())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.contains("48 rows") && message.contains("6 angles x 5 detectors = 30 rays"),
+        "{message}"
+    );
+    assert!(
+        message.contains("20 columns") && message.contains("4x4 volume = 16 voxels"),
+        "{message}"
+    );
+
+    let wrong_volume = geometry
+        .check_system_matrix((30, 20))
+        .unwrap_err()
+        .to_string();
+    assert!(!wrong_volume.contains("rows"), "{wrong_volume}");
+}
+
+#[test]
+fn fingerprint_identifies_the_geometry_not_its_spelling() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 6, "num_detectors": 8, "volume_shape": [4, 4]}"#,
+    )
+    .unwrap();
+    // same geometry: other key order, a defaulted key spelled out
+    let respelled = Geometry::from_json(
+        r#"{
+            "volume_shape": [4, 4],
+            "num_detectors": 8,
+            "angle_range_deg": 180.0,
+            "num_angles": 6,
+            "kind": "parallel_beam"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(geometry.fingerprint(), respelled.fingerprint());
+
+    let rotated = Geometry {
+        angle_start_deg: 1.0,
+        ..geometry.clone()
+    };
+    assert_ne!(geometry.fingerprint(), rotated.fingerprint());
+
+    // the parts of a built matrix rebuild it, as a saved matrix is loaded
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    let (indptr, indices, data) = (
+        system_matrix.indptr().to_vec(),
+        system_matrix.indices().to_vec(),
+        system_matrix.data().to_vec(),
+    );
+    assert_eq!(
+        SparseSystemMatrix::new(system_matrix.dim(), indptr, indices, data),
+        system_matrix
+    );
+}
+
+#[test]
+fn roi_reconstruction_models_the_outside_as_background() {
+    let json = r#"{"kind": "parallel_beam", "num_angles": 16, "num_detectors": 12,
+        "volume_shape": [8, 10]"#;
+    let full = Geometry::from_json(&format!("{json}}}")).unwrap();
+    let roi = Geometry::from_json(&format!(
+        r#"{json}, "roi": {{"x0": 3, "y0": 2, "nx": 4, "ny": 3, "background": 0.25}}}}"#
+    ))
+    .unwrap();
+    assert_eq!((roi.reconstruction_shape(), roi.num_voxels()), ([3, 4], 12));
+    let (full_matrix, roi_matrix) = (
+        full.build_system_matrix::<f64>(),
+        roi.build_system_matrix::<f64>(),
+    );
+    roi.check_system_matrix(roi_matrix.dim()).unwrap();
+    assert!(full.background_projections::<f64>().is_none());
+
+    // the object is the background everywhere but inside the ROI
+    let inside = Array1::from_shape_fn(12, |k| 1.0 + 0.1 * k as f64);
+    let mut object = Array1::from_elem(80, 0.25);
+    for (k, &value) in inside.iter().enumerate() {
+        object[(2 + k / 4) * 10 + 3 + k % 4] = value;
+    }
+    let projections = full_matrix.dot(&object);
+    let background = roi.background_projections::<f64>().unwrap();
+    let modeled = roi_matrix.dot(&inside) + &background;
+    assert!(projections
+        .iter()
+        .zip(&modeled)
+        .all(|(a, b)| (a - b).abs() < 1e-9));
+
+    let corrected = &projections - &background;
+    let volume = cgls_reconstruct(&corrected, &roi_matrix, 30).unwrap();
+    assert!(
+        volume
+            .iter()
+            .zip(&inside)
+            .all(|(x, t)| (x - t).abs() < 1e-6),
+        "{volume}"
+    );
+}
+
+#[test]
+fn roi_must_fit_the_grid() {
+    let geometry = |roi: &str| {
+        Geometry::from_json(&format!(
+            r#"{{"kind": "parallel_beam", "num_angles": 4, "num_detectors": 8,
+             "volume_shape": [6, 8], "roi": {roi}}}"#
+        ))
+    };
+    geometry(r#"{"x0": 4, "y0": 0, "nx": 4, "ny": 6}"#).unwrap();
+    let message = geometry(r#"{"x0": 5, "y0": 0, "nx": 4, "ny": 6}"#)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("columns 5..9"), "{message}");
+    assert!(geometry(r#"{"x0": 0, "y0": 0, "nx": 0, "ny": 6}"#).is_err());
+    let message = geometry(r#"{"x0": 0, "y0": 0, "z0": 0, "nx": 2, "ny": 2, "nz": 4}"#)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("nz = 4"), "{message}");
+}
@@ -0,0 +1,107 @@
+//! Golden-image regression test: MART on a small Shepp-Logan-like phantom
+//! must keep reproducing the committed reference volume.
+//!
+//! After an intentional change to the solver output, regenerate the
+//! reference with
+//!
+//!   RECON_UPDATE_GOLDEN=1 cargo test --test golden
+//!
+//! and commit `tests/data/shepp_logan_16_mart.npy` along with the change.
+
+use std::path::PathBuf;
+
+use ndarray::{Array1, Array2};
+use ndarray_npy::{read_npy, write_npy};
+
+use recon_core::{mart_reconstruct, Geometry, ReconOptions};
+
+const SIZE: usize = 16;
+const N_ITERS: usize = 20;
+const RELAXATION: f64 = 0.5;
+
+/// Allowed per-voxel deviation from the reference. Covers summation-order
+/// differences (e.g. the rayon build), not algorithmic changes.
+const TOLERANCE: f64 = 1e-9;
+
+fn reference_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/shepp_logan_16_mart.npy")
+}
+
+/// Shepp-Logan-like phantom on a `SIZE x SIZE` grid (row-major, row 0 at
+/// the top): an outer skull ellipse, a darker brain, and three small
+/// features. Everything stays positive so MART can represent it.
+fn phantom() -> Array1<f64> {
+    // (value added, center x, center y, semi-axis x, semi-axis y) in [-1, 1]^2
+    let ellipses = [
+        (1.0, 0.0, 0.0, 0.69, 0.92),
+        (-0.6, 0.0, -0.02, 0.62, 0.85),
+        (0.3, 0.22, 0.0, 0.16, 0.4),
+        (0.3, -0.22, 0.0, 0.2, 0.3),
+        (0.2, 0.0, 0.35, 0.21, 0.25),
+    ];
+    let mut volume = Array1::from_elem(SIZE * SIZE, 0.0);
+    for r in 0..SIZE {
+        for c in 0..SIZE {
+            let x = (c as f64 + 0.5) / SIZE as f64 * 2.0 - 1.0;
+            let y = 1.0 - (r as f64 + 0.5) / SIZE as f64 * 2.0;
+            for &(value, cx, cy, ax, ay) in &ellipses {
+                if ((x - cx) / ax).powi(2) + ((y - cy) / ay).powi(2) <= 1.0 {
+                    volume[r * SIZE + c] += value;
+                }
+            }
+        }
+    }
+    volume
+}
+
+fn dense_system_matrix(geometry: &Geometry) -> Array2<f64> {
+    let sparse = geometry.build_system_matrix::<f64>();
+    let mut dense = Array2::zeros(sparse.dim());
+    for i in 0..sparse.dim().0 {
+        let (cols, vals) = sparse.row(i);
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            dense[[i, j]] = a_ij;
+        }
+    }
+    dense
+}
+
+#[test]
+fn mart_matches_golden_phantom_reconstruction() {
+    let geometry = Geometry::from_json(&format!(
+        r#"{{"kind": "parallel_beam", "num_angles": 24, "num_detectors": 24,
+         "volume_shape": [{SIZE}, {SIZE}]}}"#
+    ))
+    .unwrap();
+    let system_matrix = dense_system_matrix(&geometry);
+    let phantom = phantom();
+    let projections = system_matrix.dot(&phantom);
+
+    let volume = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        N_ITERS,
+        RELAXATION,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+
+    if std::env::var_os("RECON_UPDATE_GOLDEN").is_some() {
+        write_npy(reference_path(), &volume).unwrap();
+    }
+    let reference: Array1<f64> = read_npy(reference_path()).unwrap();
+    assert_eq!(reference.len(), volume.len());
+    let worst = (&volume - &reference)
+        .iter()
+        .fold(0.0f64, |acc, d| acc.max(d.abs()));
+    assert!(
+        worst <= TOLERANCE,
+        "volume deviates from the golden reference by up to {worst:e}"
+    );
+
+    // expected accuracy: 20 passes recover the phantom to ~10% relative RMS
+    let error = &volume - &phantom;
+    let relative_rms = (error.dot(&error) / phantom.dot(&phantom)).sqrt();
+    println!("relative RMS error vs phantom: {relative_rms:.4}");
+    assert!(relative_rms < 0.15, "relative RMS error {relative_rms}");
+}
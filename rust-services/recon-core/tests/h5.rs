@@ -0,0 +1,83 @@
+#![cfg(feature = "hdf5")]
+
+use std::path::PathBuf;
+
+use hdf5::File;
+use ndarray::array;
+
+use recon_core::h5::{read_array1_h5, read_system_matrix_h5, H5SystemMatrix};
+use recon_core::SparseSystemMatrix;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("recon_core_{}_{name}.h5", std::process::id()))
+}
+
+#[test]
+fn reads_projections_and_dense_matrix() {
+    let path = temp_path("dense");
+    {
+        let file = File::create(&path).unwrap();
+        let scan = file.create_group("scan").unwrap();
+        // stored as f32, read back as f64
+        let projections = scan
+            .new_dataset::<f32>()
+            .shape(3)
+            .create("projections")
+            .unwrap();
+        projections.write_raw(&[1.0f32, 2.0, 0.5][..]).unwrap();
+        let system_matrix = scan
+            .new_dataset::<f64>()
+            .shape((3, 2))
+            .create("system_matrix")
+            .unwrap();
+        system_matrix
+            .write_raw(&[1.0, 0.0, 0.0, 1.0, 0.5, 0.5][..])
+            .unwrap();
+    }
+
+    let projections = read_array1_h5::<f64>(&path, "scan/projections").unwrap();
+    assert_eq!(projections, array![1.0, 2.0, 0.5]);
+    match read_system_matrix_h5::<f64>(&path, "/scan/system_matrix").unwrap() {
+        H5SystemMatrix::Dense(dense) => {
+            assert_eq!(dense, array![[1.0, 0.0], [0.0, 1.0], [0.5, 0.5]])
+        }
+        other => panic!("expected a dense matrix, got {other:?}"),
+    }
+    assert!(read_array1_h5::<f64>(&path, "scan/system_matrix").is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reads_csr_group() {
+    let path = temp_path("csr");
+    {
+        let file = File::create(&path).unwrap();
+        let group = file.create_group("system_matrix").unwrap();
+        let write_i32 = |name: &str, values: &[i32]| {
+            let dataset = group
+                .new_dataset::<i32>()
+                .shape(values.len())
+                .create(name)
+                .unwrap();
+            dataset.write_raw(values).unwrap();
+        };
+        write_i32("indptr", &[0, 2, 3]);
+        write_i32("indices", &[0, 2, 1]);
+        write_i32("shape", &[2, 3]);
+        let data = group.new_dataset::<f64>().shape(3).create("data").unwrap();
+        data.write_raw(&[1.0, 2.0, 3.0][..]).unwrap();
+    }
+
+    match read_system_matrix_h5::<f32>(&path, "system_matrix").unwrap() {
+        H5SystemMatrix::Sparse(sparse) => {
+            let x = array![1.0, 1.0, 1.0];
+            assert_eq!(sparse.dim(), (2, 3));
+            assert_eq!(
+                sparse.dot(&x),
+                SparseSystemMatrix::from_dense(&array![[1.0, 0.0, 2.0], [0.0, 3.0, 0.0]]).dot(&x)
+            );
+        }
+        other => panic!("expected a sparse matrix, got {other:?}"),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
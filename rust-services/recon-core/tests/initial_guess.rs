@@ -0,0 +1,106 @@
+use ndarray::Array1;
+
+use recon_core::{
+    filter::resample_linear, mart_reconstruct_sparse, mart_reconstruct_sparse_report,
+    mart_reconstruct_sparse_with_callback, phantom, Geometry, InitialGuess, PhantomKind,
+    ReconOptions,
+};
+
+/// Iterations MART needs to bring the relative residual below `threshold`.
+fn iterations_to_reach(initial_guess: InitialGuess, threshold: f64) -> Option<usize> {
+    let geometry = Geometry::from_json(
+        r#"{
+            "kind": "parallel_beam",
+            "num_angles": 24,
+            "num_detectors": 24,
+            "volume_shape": [16, 16]
+        }"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+
+    // faint disc on a near-zero background, far from the uniform 1.0 start
+    let phantom = Array1::from_shape_fn(geometry.num_voxels(), |j| {
+        let (r, c) = ((j / 16) as f64 - 7.5, (j % 16) as f64 - 7.5);
+        if r * r + c * c < 25.0 {
+            0.2
+        } else {
+            0.05
+        }
+    });
+    let projections = system_matrix.dot(&phantom);
+
+    let options = ReconOptions {
+        initial_guess,
+        ..ReconOptions::default()
+    };
+    let mut reached = None;
+    mart_reconstruct_sparse_with_callback(
+        &projections,
+        &system_matrix,
+        50,
+        0.5,
+        &options,
+        |iter, _, residual| {
+            if reached.is_none() && residual < threshold {
+                reached = Some(iter + 1);
+            }
+        },
+    )
+    .unwrap();
+    reached
+}
+
+#[test]
+fn backprojection_warm_start_converges_faster() {
+    let uniform = iterations_to_reach(InitialGuess::Uniform(1.0), 0.02)
+        .expect("uniform start did not converge");
+    let warm = iterations_to_reach(InitialGuess::Backprojection, 0.02)
+        .expect("warm start did not converge");
+    assert!(
+        warm < uniform,
+        "backprojection start took {warm} iterations, uniform {uniform}"
+    );
+}
+
+#[test]
+fn coarse_warm_start_halves_the_iterations() {
+    // the same rays through a 32x32 grid and a 16x16 grid of twice the pitch
+    let geometry = |size: usize, pixel_size: f64| {
+        Geometry::from_json(&format!(
+            r#"{{"kind": "parallel_beam", "num_angles": 32, "num_detectors": 32,
+                "volume_shape": [{size}, {size}], "pixel_size": {pixel_size}}}"#
+        ))
+        .unwrap()
+        .build_system_matrix::<f64>()
+    };
+    let (fine, coarse) = (geometry(32, 1.0), geometry(16, 2.0));
+    let truth = phantom::<f64>(PhantomKind::Shapes, [32, 32]).mapv(|x| x + 0.05);
+    let projections = fine.dot(&truth);
+
+    let coarse_volume =
+        mart_reconstruct_sparse(&projections, &coarse, 5, 0.5, &ReconOptions::default()).unwrap();
+    let warm_options = ReconOptions {
+        initial_guess: InitialGuess::FromArray(resample_linear(
+            &coarse_volume,
+            &[16, 16],
+            &[32, 32],
+        )),
+        ..ReconOptions::default()
+    };
+    let iterations_below = |options: &ReconOptions| {
+        let report = mart_reconstruct_sparse_report(&projections, &fine, 10, 0.5, options).unwrap();
+        report
+            .residual_history
+            .iter()
+            .position(|&r| r < 0.02)
+            .map(|iter| iter + 1)
+    };
+
+    let cold = iterations_below(&ReconOptions::default()).expect("cold start did not converge");
+    let warm = iterations_below(&warm_options).expect("warm start did not converge");
+    assert!(
+        2 * warm <= cold,
+        "warm start took {warm} iterations, cold {cold}"
+    );
+}
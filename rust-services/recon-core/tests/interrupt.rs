@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use ndarray::array;
+
+use recon_core::{
+    mart_reconstruct_report, mart_reconstruct_with_callback, sirt_reconstruct_report, ReconOptions,
+};
+
+#[test]
+fn stop_flag_ends_the_run_after_the_current_iteration() {
+    let system_matrix = array![[1.0, 1.0, 0.0], [0.0, 1.0, 1.0], [1.0, 0.0, 1.0]];
+    let projections = system_matrix.dot(&array![0.5, 1.5, 1.0]);
+    let stop = Arc::new(AtomicBool::new(false));
+    let options = ReconOptions {
+        stop: Some(Arc::clone(&stop)),
+        ..ReconOptions::default()
+    };
+
+    let mut seen = Vec::new();
+    let report = mart_reconstruct_with_callback(
+        &projections,
+        &system_matrix,
+        50,
+        0.5,
+        &options,
+        |iter, _, _| {
+            seen.push(iter);
+            if iter == 2 {
+                stop.store(true, Ordering::Relaxed);
+            }
+        },
+    )
+    .unwrap();
+    assert_eq!(seen, vec![0, 1, 2]);
+    assert!(report.interrupted && !report.converged);
+    assert_eq!(report.iterations_run, 3);
+
+    // the partial volume is the one a 3-iteration run returns
+    let full = mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        3,
+        0.5,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(report.volume, full.volume);
+    assert!(!full.interrupted);
+}
+
+#[test]
+fn flag_set_before_the_run_still_completes_one_iteration() {
+    let system_matrix = array![[2.0, 0.0], [0.0, 4.0]];
+    let options = ReconOptions {
+        stop: Some(Arc::new(AtomicBool::new(true))),
+        ..ReconOptions::default()
+    };
+
+    let report =
+        sirt_reconstruct_report(&array![2.0, 8.0], &system_matrix, 10, 1.0, &options).unwrap();
+    assert_eq!(report.iterations_run, 1);
+    assert!(report.interrupted);
+}
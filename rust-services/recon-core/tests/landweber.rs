@@ -0,0 +1,104 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    estimate_landweber_spectral_radius, landweber_reconstruct, landweber_reconstruct_with_callback,
+    safe_relaxation, ReconOptions, SparseSystemMatrix,
+};
+
+fn max_abs_diff(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+/// Inconsistent data for a system with orthogonal columns, and its
+/// least-squares solution `x_j = a_j . y / a_j . a_j`.
+fn problem() -> (Array2<f64>, Array1<f64>, Array1<f64>) {
+    let system_matrix = array![
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 2.0],
+        [0.0, 0.0, 1.0],
+    ];
+    let projections = array![0.9, 1.1, 2.5, 1.5, 1.0, 0.9];
+    let least_squares = array![1.0, 2.0, 2.9 / 5.0];
+    (system_matrix, projections, least_squares)
+}
+
+#[test]
+fn converges_to_the_least_squares_solution() {
+    let (system_matrix, projections, least_squares) = problem();
+    // sigma_max^2 is the largest a_j . a_j
+    let rho = estimate_landweber_spectral_radius(&system_matrix, 30);
+    assert!((rho - 5.0).abs() < 1e-9, "{rho}");
+
+    let mut residuals = Vec::new();
+    let report = landweber_reconstruct_with_callback(
+        &projections,
+        &system_matrix,
+        200,
+        safe_relaxation(rho),
+        &ReconOptions::default(),
+        |_, _, residual| residuals.push(residual),
+    )
+    .unwrap();
+    assert!(
+        max_abs_diff(&report.volume, &least_squares) < 1e-9,
+        "{}",
+        report.volume
+    );
+    assert!(
+        residuals.windows(2).all(|w| w[1] <= w[0] + 1e-15),
+        "{residuals:?}"
+    );
+    // the data are inconsistent, so the residual levels off above zero
+    assert!(report.final_residual > 0.1);
+
+    let sparse = landweber_reconstruct(
+        &projections,
+        &SparseSystemMatrix::from_dense(&system_matrix),
+        200,
+        safe_relaxation(rho),
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert!(max_abs_diff(&sparse, &report.volume) < 1e-12);
+}
+
+#[test]
+fn clamp_projects_onto_nonnegative_volumes() {
+    let (system_matrix, _, _) = problem();
+    // the least-squares solution is [-0.5, 2, 0.58]; the columns decouple,
+    // so projected Landweber settles on [0, 2, 0.58]
+    let projections = array![-0.4, -0.6, 2.5, 1.5, 1.0, 0.9];
+    let options = ReconOptions {
+        clamp_nonnegative: true,
+        ..ReconOptions::default()
+    };
+
+    let volume = landweber_reconstruct(&projections, &system_matrix, 200, 0.3, &options).unwrap();
+    assert!(
+        max_abs_diff(&volume, &array![0.0, 2.0, 0.58]) < 1e-9,
+        "{volume}"
+    );
+}
+
+#[test]
+fn relaxation_above_the_bound_diverges() {
+    let (system_matrix, projections, _) = problem();
+    let volume = landweber_reconstruct(
+        &projections,
+        &system_matrix,
+        200,
+        0.45,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert!(
+        volume.iter().any(|v| !v.is_finite() || v.abs() > 1e6),
+        "{volume}"
+    );
+}
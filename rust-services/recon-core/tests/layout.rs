@@ -0,0 +1,141 @@
+use ndarray::{Array1, Array3, ArrayD, IxDyn};
+use ndarray_npy::{read_npy, write_npy};
+
+use recon_core::filter::{gaussian_blur, resample_linear};
+use recon_core::{Geometry, HuberRegularization, L2Regularization, TvRegularization};
+
+/// A volume of `shape` with no symmetry a transposition could hide.
+fn ramp(shape: &[usize]) -> Array1<f64> {
+    Array1::from_shape_fn(shape.iter().product::<usize>(), |j| {
+        ((j * 7919) % 31) as f64 + 0.1 * j as f64
+    })
+}
+
+/// `volume` (C order, `shape`) with its axes reversed, flattened again.
+fn transposed(volume: &Array1<f64>, shape: &[usize]) -> Array1<f64> {
+    let array = volume.clone().into_shape(IxDyn(shape)).unwrap();
+    array.reversed_axes().iter().copied().collect()
+}
+
+fn reversed(shape: &[usize]) -> Vec<usize> {
+    shape.iter().rev().copied().collect()
+}
+
+fn assert_close(a: &Array1<f64>, b: &Array1<f64>) {
+    assert_eq!(a.len(), b.len());
+    assert!(
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-10),
+        "{a}\n{b}"
+    );
+}
+
+#[test]
+fn npy_round_trip_keeps_numpy_c_order() {
+    let (depth, rows, cols) = (3, 4, 5);
+    let volume = ramp(&[depth, rows, cols]);
+    let stack = Array3::from_shape_vec((depth, rows, cols), volume.to_vec()).unwrap();
+    // voxel (z, y, x) is flat index (z * rows + y) * cols + x, as in NumPy
+    assert_eq!(stack[[2, 1, 3]], volume[(2 * rows + 1) * cols + 3]);
+
+    let path = std::env::temp_dir().join(format!("recon_core_layout_{}.npy", std::process::id()));
+    write_npy(&path, &stack).unwrap();
+    let read: ArrayD<f64> = read_npy(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.shape(), &[depth, rows, cols]);
+    assert_eq!(read.iter().copied().collect::<Array1<f64>>(), volume);
+}
+
+#[test]
+fn non_square_grid_maps_columns_and_rows_to_detector_axes() {
+    // 3 rows x 5 columns; at 0 degrees the detector runs along x (columns),
+    // at 90 degrees along y (rows), with one ray through each pixel center
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "angles_deg": [0, 90], "num_detectors": 5,
+         "volume_shape": [3, 5]}"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    assert_eq!(system_matrix.dim(), (10, 15));
+
+    let (row, col) = (2, 1);
+    let mut volume = Array1::zeros(15);
+    volume[row * 5 + col] = 1.0;
+    let projections = system_matrix.dot(&volume);
+    let lit: Vec<usize> = (0..10).filter(|&i| projections[i] > 0.0).collect();
+    assert_eq!(lit, vec![col, 5 + row + 1]);
+}
+
+#[test]
+fn smoothing_commutes_with_transposing_a_non_square_grid() {
+    let shape = [4, 7];
+    let volume = ramp(&shape);
+    let flipped = transposed(&volume, &shape);
+    let apply_both = |apply: &dyn Fn(&mut Array1<f64>, [usize; 2])| {
+        let (mut a, mut b) = (volume.clone(), flipped.clone());
+        apply(&mut a, shape);
+        apply(&mut b, [shape[1], shape[0]]);
+        assert_close(&transposed(&a, &shape), &b);
+    };
+
+    apply_both(&|v, volume_shape| {
+        L2Regularization {
+            weight: 0.1,
+            volume_shape,
+        }
+        .apply(v)
+    });
+    apply_both(&|v, volume_shape| {
+        TvRegularization {
+            weight: 0.5,
+            every: 1,
+            volume_shape,
+        }
+        .apply(v)
+    });
+    apply_both(&|v, [rows, cols]| {
+        HuberRegularization {
+            delta: 2.0,
+            weight: 0.1,
+            volume_shape: [1, rows, cols],
+        }
+        .apply(v)
+    });
+    apply_both(&|v, volume_shape| *v = gaussian_blur(v, &volume_shape, 0.8));
+}
+
+#[test]
+fn stack_filters_commute_with_reversing_a_non_cubic_stack() {
+    let shape = [3, 4, 6];
+    let volume = ramp(&shape);
+    let flipped = transposed(&volume, &shape);
+    let [slices, rows, cols] = shape;
+
+    let mut a = volume.clone();
+    HuberRegularization {
+        delta: 2.0,
+        weight: 0.05,
+        volume_shape: shape,
+    }
+    .apply(&mut a);
+    let mut b = flipped.clone();
+    HuberRegularization {
+        delta: 2.0,
+        weight: 0.05,
+        volume_shape: [cols, rows, slices],
+    }
+    .apply(&mut b);
+    assert_close(&transposed(&a, &shape), &b);
+
+    let blurred = gaussian_blur(&volume, &shape, 1.1);
+    assert_close(
+        &transposed(&blurred, &shape),
+        &gaussian_blur(&flipped, &reversed(&shape), 1.1),
+    );
+
+    let new_shape = [5, 4, 9];
+    let resampled = resample_linear(&volume, &shape, &new_shape);
+    assert_close(
+        &transposed(&resampled, &new_shape),
+        &resample_linear(&flipped, &reversed(&shape), &reversed(&new_shape)),
+    );
+}
@@ -0,0 +1,104 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    art_reconstruct, mart_reconstruct, mart_reconstruct_sparse, phantom, sirt_reconstruct,
+    Geometry, PhantomKind, ReconOptions, SparseSystemMatrix,
+};
+
+/// One ray crossing a masked voxel (0) and an unmasked one (1).
+///
+/// From a uniform start of 1, the unmasked y_hat is 1 against a measured 2,
+/// so every solver should double voxel 1 in a single full-strength pass.
+/// Counting the masked voxel would give y_hat = 2 and no update at all.
+fn problem() -> (Array1<f64>, Array2<f64>, ReconOptions) {
+    let options = ReconOptions {
+        mask: Some(array![false, true]),
+        ..ReconOptions::default()
+    };
+    (array![2.0], array![[1.0, 1.0]], options)
+}
+
+#[test]
+fn mart_skips_masked_voxels_in_y_hat() {
+    let (projections, system_matrix, options) = problem();
+
+    let dense = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    assert_eq!(dense, array![0.0, 2.0]);
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &options).unwrap(),
+        dense
+    );
+}
+
+#[test]
+fn additive_solvers_only_update_unmasked_voxels() {
+    let (projections, system_matrix, options) = problem();
+
+    assert_eq!(
+        art_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap(),
+        array![0.0, 2.0]
+    );
+    assert_eq!(
+        sirt_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap(),
+        array![0.0, 2.0]
+    );
+}
+
+#[test]
+fn mask_length_is_checked() {
+    let (projections, system_matrix, _) = problem();
+    let options = ReconOptions {
+        mask: Some(array![true]),
+        ..ReconOptions::default()
+    };
+
+    assert!(mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).is_err());
+}
+
+#[test]
+fn tight_support_reduces_few_view_artifacts() {
+    // 6 views of a centered Shepp-Logan: SIRT smears streaks outside the head
+    let geometry = Geometry::from_json(
+        r#"{
+            "kind": "parallel_beam",
+            "num_angles": 6,
+            "num_detectors": 32,
+            "volume_shape": [32, 32]
+        }"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    let truth: Array1<f64> = phantom(PhantomKind::SheppLogan, [32, 32]);
+    let projections = system_matrix.dot(&truth);
+
+    // the head's outline, the ellipse of half-axes 0.69 x 0.92 (pixel
+    // centers as `phantom` samples them), is a tight support
+    let support = Array1::from_shape_fn(32 * 32, |j| {
+        let (y, x) = ((j / 32) as f64 / 16.0 - 0.97, (j % 32) as f64 / 16.0 - 0.97);
+        (x / 0.7).powi(2) + (y / 0.93).powi(2) <= 1.0
+    });
+    assert!(support
+        .iter()
+        .zip(&truth)
+        .all(|(&inside, &v)| inside || v == 0.0));
+
+    let plain = ReconOptions::default();
+    let supported = ReconOptions {
+        mask: Some(support),
+        ..ReconOptions::default()
+    };
+    let error = |options: &ReconOptions, n_iters: usize| {
+        let volume = sirt_reconstruct(&projections, &system_matrix, n_iters, 1.0, options).unwrap();
+        ((&volume - &truth).mapv(|d| d * d).sum() / truth.mapv(|v| v * v).sum()).sqrt()
+    };
+
+    let (unsupported, tight) = (error(&plain, 20), error(&supported, 20));
+    assert!(
+        tight < 0.6 * unsupported,
+        "relative error {tight} with the support vs {unsupported} without"
+    );
+    // and 5 iterations with the support beat 50 without it
+    assert!(error(&supported, 5) < error(&plain, 50));
+}
@@ -0,0 +1,109 @@
+use ndarray::{array, Array1};
+
+use recon_core::metrics::{fraction_at_bounds, psnr, rmse, ssim_2d, ssim_slices};
+
+/// A `rows x cols` image with structure at several scales.
+fn image(rows: usize, cols: usize) -> Array1<f64> {
+    Array1::from_shape_fn(rows * cols, |j| {
+        let (r, c) = ((j / cols) as f64, (j % cols) as f64);
+        (0.4 * r).sin()
+            + (0.25 * c).cos()
+            + if (r - 8.0).powi(2) + (c - 10.0).powi(2) < 16.0 {
+                1.0
+            } else {
+                0.0
+            }
+    })
+}
+
+#[test]
+fn rmse_and_psnr_follow_their_definitions() {
+    let reference = array![0.0, 1.0, 2.0, 4.0];
+    let volume = array![0.5, 1.0, 1.5, 4.0];
+    assert!((rmse(&volume, &reference) - (0.125f64).sqrt()).abs() < 1e-12);
+    // range 4, so 20 log10(4 / sqrt(1/8))
+    assert!((psnr(&volume, &reference) - 20.0 * (4.0 / 0.125f64.sqrt()).log10()).abs() < 1e-12);
+
+    assert_eq!(rmse(&reference, &reference), 0.0);
+    assert_eq!(psnr(&reference, &reference), f64::INFINITY);
+    assert!(psnr(&volume, &array![1.0, 1.0, 1.0, 1.0]).is_nan());
+}
+
+#[test]
+fn ssim_is_one_for_identical_images_and_drops_with_degradation() {
+    let (rows, cols) = (16, 20);
+    let reference = image(rows, cols);
+    let range = 4.0;
+    assert!((ssim_2d(&reference, &reference, [rows, cols], range) - 1.0).abs() < 1e-12);
+
+    let noisy = Array1::from_shape_fn(reference.len(), |j| {
+        reference[j] + if j % 2 == 0 { 0.1 } else { -0.1 }
+    });
+    let noisier = Array1::from_shape_fn(reference.len(), |j| {
+        reference[j] + if j % 2 == 0 { 0.4 } else { -0.4 }
+    });
+    let mild = ssim_2d(&noisy, &reference, [rows, cols], range);
+    let strong = ssim_2d(&noisier, &reference, [rows, cols], range);
+    assert!(
+        1.0 > mild && mild > strong && strong > 0.0,
+        "{mild} {strong}"
+    );
+
+    // on a positive image a small shift of the mean only costs a little
+    // luminance, while flipping the structure makes the covariance, and
+    // with it SSIM, negative
+    let positive = &reference + 5.0;
+    let brighter = ssim_2d(&(&positive + 0.5), &positive, [rows, cols], range);
+    let flipped = ssim_2d(&positive.mapv(|v| 11.0 - v), &positive, [rows, cols], range);
+    assert!(brighter > 0.99 && flipped < 0.0, "{brighter} {flipped}");
+}
+
+#[test]
+fn ssim_slices_scores_every_slice_against_the_volume_range() {
+    let (rows, cols) = (16, 20);
+    let slice = image(rows, cols);
+    let reference = ndarray::concatenate![ndarray::Axis(0), slice, 2.0 * &slice];
+    let mut volume = reference.clone();
+    volume
+        .iter_mut()
+        .skip(rows * cols)
+        .step_by(3)
+        .for_each(|v| *v += 0.5);
+
+    let scores = ssim_slices(&volume, &reference, [2, rows, cols]);
+    assert_eq!(scores.len(), 2);
+    assert!((scores[0] - 1.0).abs() < 1e-12);
+    assert!(scores[1] < 0.99);
+
+    let range = reference.fold(f64::NEG_INFINITY, |a, &b| a.max(b))
+        - reference.fold(f64::INFINITY, |a, &b| a.min(b));
+    let second = |v: &Array1<f64>| v.slice(ndarray::s![rows * cols..]).to_owned();
+    assert_eq!(
+        scores[1],
+        ssim_2d(&second(&volume), &second(&reference), [rows, cols], range)
+    );
+}
+
+#[test]
+fn fraction_at_bounds_counts_clamped_voxels_inside_the_mask() {
+    let volume = array![0.0_f32, 0.0, 0.3, 1.0, 0.7, 0.0];
+    assert_eq!(
+        fraction_at_bounds(&volume, (0.0, 1.0), None),
+        (0.5, 1.0 / 6.0)
+    );
+    assert_eq!(
+        fraction_at_bounds(&volume, (0.0, f64::INFINITY), None),
+        (0.5, 0.0)
+    );
+
+    // the masked-out zero is not a clamped voxel
+    let mask = array![true, true, true, true, true, false];
+    assert_eq!(
+        fraction_at_bounds(&volume, (0.0, 1.0), Some(&mask)),
+        (0.4, 0.2)
+    );
+    assert_eq!(
+        fraction_at_bounds(&volume, (0.0, 1.0), Some(&Array1::from_elem(6, false))),
+        (0.0, 0.0)
+    );
+}
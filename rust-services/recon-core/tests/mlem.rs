@@ -0,0 +1,87 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{mlem_reconstruct, mlem_reconstruct_with_callback, ReconError, ReconOptions};
+
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 0.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+/// Poisson log-likelihood up to terms that do not depend on the volume.
+fn log_likelihood(
+    projections: &Array1<f64>,
+    system_matrix: &Array2<f64>,
+    volume: &Array1<f64>,
+) -> f64 {
+    let y_hat = system_matrix.dot(volume);
+    projections
+        .iter()
+        .zip(&y_hat)
+        .map(|(&y, &y_hat)| y * y_hat.ln() - y_hat)
+        .sum()
+}
+
+#[test]
+fn likelihood_increases_every_iteration() {
+    let (projections, system_matrix) = problem();
+
+    let mut likelihoods = Vec::new();
+    let options = ReconOptions::default();
+    let report = mlem_reconstruct_with_callback(
+        &projections,
+        &system_matrix,
+        200,
+        &options,
+        |_, volume, _| likelihoods.push(log_likelihood(&projections, &system_matrix, volume)),
+    )
+    .unwrap();
+    assert!(
+        likelihoods.windows(2).all(|w| w[1] >= w[0] - 1e-12),
+        "{likelihoods:?}"
+    );
+    assert!(
+        report.final_residual < 1e-3,
+        "residual {}",
+        report.final_residual
+    );
+    assert_eq!(
+        mlem_reconstruct(&projections, &system_matrix, 200).unwrap(),
+        report.volume
+    );
+}
+
+#[test]
+fn empty_rays_and_unseen_voxels_stay_finite() {
+    // ray 2 misses the volume, voxel 2 is on no ray
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 2.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0f64]
+    ];
+    let projections = array![3.0, 4.0, 5.0, 1.0];
+
+    let volume = mlem_reconstruct(&projections, &system_matrix, 50).unwrap();
+    assert!(volume.iter().all(|v| v.is_finite()), "{volume}");
+    assert_eq!(volume[2], 1.0);
+}
+
+#[test]
+fn negative_projections_are_rejected() {
+    let (mut projections, system_matrix) = problem();
+    projections[3] = -0.5;
+
+    let result = mlem_reconstruct(&projections, &system_matrix, 10);
+    assert!(
+        matches!(result, Err(ReconError::NegativeProjection { index: 3, .. })),
+        "{result:?}"
+    );
+}
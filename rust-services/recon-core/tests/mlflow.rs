@@ -0,0 +1,71 @@
+#![cfg(feature = "mlflow")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use recon_core::mlflow::MlflowLogger;
+
+/// Accept one HTTP request, answer 200 and return its request line and body.
+fn serve_once(listener: TcpListener) -> thread::JoinHandle<(String, String)> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+            .unwrap();
+        (request_line, String::from_utf8(body).unwrap())
+    })
+}
+
+#[test]
+fn posts_metric_to_log_metric_endpoint() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let uri = format!("http://{}/", listener.local_addr().unwrap());
+    let server = serve_once(listener);
+
+    let mut logger = MlflowLogger::new(&uri, "abc123");
+    logger.log_metric("residual", 0.25, 7);
+    let (request_line, body) = server.join().unwrap();
+
+    assert!(request_line.starts_with("POST /api/2.0/mlflow/runs/log-metric "));
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["run_id"], "abc123");
+    assert_eq!(body["key"], "residual");
+    assert_eq!(body["value"], 0.25);
+    assert_eq!(body["step"], 7);
+    assert_eq!(logger.failures(), 0);
+}
+
+#[test]
+fn unreachable_server_is_not_fatal() {
+    // grab a free port, then close it so connections are refused
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut logger = MlflowLogger::new(&format!("http://{addr}"), "run");
+
+    for step in 0..10 {
+        logger.log_metric("residual", 1.0, step);
+    }
+    assert!(logger.gave_up());
+    assert_eq!(logger.failures(), 3);
+}
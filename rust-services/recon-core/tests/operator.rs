@@ -0,0 +1,102 @@
+use ndarray::{Array1, Array2};
+
+use recon_core::{
+    backprojection, cgls_reconstruct, sirt_reconstruct, LinearOperator, ReconOptions,
+    SparseSystemMatrix,
+};
+
+const ROWS: usize = 4;
+const COLS: usize = 5;
+
+/// Matrix-free projector of a ROWS x COLS image along its rows and columns:
+/// the first ROWS rays are row sums, the remaining COLS rays column sums.
+struct RowColumnSums;
+
+impl LinearOperator<f64> for RowColumnSums {
+    fn dim(&self) -> (usize, usize) {
+        (ROWS + COLS, ROWS * COLS)
+    }
+
+    fn forward(&self, x: &Array1<f64>) -> Array1<f64> {
+        let image = x.view().into_shape((ROWS, COLS)).unwrap();
+        image
+            .rows()
+            .into_iter()
+            .map(|row| row.sum())
+            .chain(image.columns().into_iter().map(|col| col.sum()))
+            .collect()
+    }
+
+    fn adjoint(&self, y: &Array1<f64>) -> Array1<f64> {
+        Array1::from_shape_fn(ROWS * COLS, |j| y[j / COLS] + y[ROWS + j % COLS])
+    }
+}
+
+/// The explicit matrix of `RowColumnSums`.
+fn dense() -> Array2<f64> {
+    Array2::from_shape_fn((ROWS + COLS, ROWS * COLS), |(i, j)| {
+        let hit = if i < ROWS {
+            j / COLS == i
+        } else {
+            j % COLS == i - ROWS
+        };
+        if hit {
+            1.0
+        } else {
+            0.0
+        }
+    })
+}
+
+fn projections() -> Array1<f64> {
+    let image = Array1::from_shape_fn(ROWS * COLS, |j| 1.0 + (j % 3) as f64);
+    dense().dot(&image)
+}
+
+fn assert_close(a: &Array1<f64>, b: &Array1<f64>) {
+    let worst = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max);
+    assert!(worst < 1e-12, "differ by {worst}");
+}
+
+#[test]
+fn matrix_free_operator_matches_dense_matrix() {
+    let projections = projections();
+    let options = ReconOptions::default();
+
+    let sirt = sirt_reconstruct(&projections, &RowColumnSums, 20, 1.0, &options).unwrap();
+    assert_close(
+        &sirt,
+        &sirt_reconstruct(&projections, &dense(), 20, 1.0, &options).unwrap(),
+    );
+
+    let cgls = cgls_reconstruct(&projections, &RowColumnSums, 10).unwrap();
+    assert_close(
+        &cgls,
+        &cgls_reconstruct(&projections, &dense(), 10).unwrap(),
+    );
+    assert_close(&RowColumnSums.forward(&cgls), &projections);
+
+    assert_close(
+        &backprojection(&projections, &RowColumnSums),
+        &backprojection(&projections, &dense()),
+    );
+}
+
+#[test]
+fn sparse_sirt_matches_dense() {
+    let projections = projections();
+    let mask = Array1::from_shape_fn(ROWS * COLS, |j| j % 7 != 3);
+    let options = ReconOptions {
+        mask: Some(mask),
+        ..ReconOptions::default()
+    };
+    let sparse = SparseSystemMatrix::from_dense(&dense());
+    assert_close(
+        &sirt_reconstruct(&projections, &sparse, 20, 1.0, &options).unwrap(),
+        &sirt_reconstruct(&projections, &dense(), 20, 1.0, &options).unwrap(),
+    );
+}
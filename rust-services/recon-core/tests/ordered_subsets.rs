@@ -0,0 +1,87 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{os_mart_reconstruct, os_mart_reconstruct_report, Geometry, ReconOptions};
+
+fn max_abs_diff(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+fn dense_system_matrix(geometry: &Geometry) -> Array2<f64> {
+    let sparse = geometry.build_system_matrix::<f64>();
+    let mut dense = Array2::zeros(sparse.dim());
+    for i in 0..sparse.dim().0 {
+        let (cols, vals) = sparse.row(i);
+        for (&j, &a_ij) in cols.iter().zip(vals) {
+            dense[[i, j]] = a_ij;
+        }
+    }
+    dense
+}
+
+fn parallel() -> ReconOptions {
+    ReconOptions {
+        parallel_subsets: true,
+        ..ReconOptions::default()
+    }
+}
+
+#[test]
+fn disjoint_subsets_match_the_serial_order() {
+    // interleaved subsets {0, 2} and {1, 3} see disjoint voxels, so running
+    // them side by side changes nothing
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 2.0],
+        [2.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+    ];
+    let projections = system_matrix.dot(&array![0.5, 1.5, 2.0, 0.25]);
+
+    let serial = os_mart_reconstruct(
+        &projections,
+        &system_matrix,
+        10,
+        2,
+        0.7,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let parallel =
+        os_mart_reconstruct(&projections, &system_matrix, 10, 2, 0.7, &parallel()).unwrap();
+    assert!(
+        max_abs_diff(&serial, &parallel) < 1e-12,
+        "{serial} vs {parallel}"
+    );
+}
+
+#[test]
+fn overlapping_subsets_converge_more_slowly() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 16, "num_detectors": 12,
+         "volume_shape": [12, 12]}"#,
+    )
+    .unwrap();
+    let system_matrix = dense_system_matrix(&geometry);
+    let truth = Array1::from_shape_fn(144, |j| 1.0 + (j % 7) as f64 / 7.0);
+    let projections = system_matrix.dot(&truth);
+
+    let serial = os_mart_reconstruct_report(
+        &projections,
+        &system_matrix,
+        20,
+        4,
+        0.5,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let parallel =
+        os_mart_reconstruct_report(&projections, &system_matrix, 20, 4, 0.5, &parallel()).unwrap();
+
+    let history = &parallel.residual_history;
+    assert!(history.windows(2).all(|w| w[1] <= w[0]), "{history:?}");
+    assert!(parallel.final_residual < 0.2 * history[0]);
+    assert!(parallel.final_residual > serial.final_residual);
+}
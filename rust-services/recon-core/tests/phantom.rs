@@ -0,0 +1,55 @@
+use ndarray::Array1;
+
+use recon_core::{add_noise, phantom, NoiseModel, PhantomKind};
+
+fn mean_and_std(values: &Array1<f64>) -> (f64, f64) {
+    let mean = values.mean().unwrap();
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, var.sqrt())
+}
+
+#[test]
+fn shepp_logan_has_skull_brain_and_empty_corners() {
+    let volume: Array1<f64> = phantom(PhantomKind::SheppLogan, [64, 64]);
+    assert_eq!(volume.len(), 64 * 64);
+    assert!(
+        volume.iter().all(|&v| v >= -1e-12),
+        "phantom must stay non-negative"
+    );
+    assert_eq!(volume[0], 0.0);
+    assert_eq!(volume[64 * 64 - 1], 0.0);
+    // skull of 1.0 at the top edge of the head, brain of 0.2 in the middle
+    assert_eq!(volume[3 * 64 + 32], 1.0);
+    assert!((volume[32 * 64 + 32] - 0.2).abs() < 1e-12);
+}
+
+#[test]
+fn shapes_peak_where_the_disk_sits_on_the_background() {
+    let volume: Array1<f32> = phantom(PhantomKind::Shapes, [20, 30]);
+    assert_eq!(volume.len(), 600);
+    let max = volume.iter().copied().fold(0.0, f32::max);
+    assert!((max - 1.1).abs() < 1e-6, "{max}");
+}
+
+#[test]
+fn noise_matches_the_requested_level() {
+    let projections = Array1::from_elem(20_000, 5.0);
+    for model in [NoiseModel::Gaussian, NoiseModel::Poisson] {
+        let noisy = add_noise(&projections, model, 0.05, 7);
+        let (mean, std) = mean_and_std(&noisy);
+        assert!((mean - 5.0).abs() < 0.01, "{model:?}: mean {mean}");
+        assert!(
+            (std / 5.0 - 0.05).abs() < 0.003,
+            "{model:?}: relative std {}",
+            std / 5.0
+        );
+        assert!(noisy.iter().all(|&v| v >= 0.0));
+
+        assert_eq!(add_noise(&projections, model, 0.05, 7), noisy);
+        assert_ne!(add_noise(&projections, model, 0.05, 8), noisy);
+    }
+    assert_eq!(
+        add_noise(&projections, NoiseModel::Poisson, 0.0, 7),
+        projections
+    );
+}
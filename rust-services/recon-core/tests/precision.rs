@@ -0,0 +1,81 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{art_reconstruct, mart_step_unchecked, ReconOptions};
+
+/// Relative L2 residual ||A*x - y|| / ||y||, always evaluated in f64.
+fn relative_residual(
+    system_matrix: &Array2<f64>,
+    volume: &Array1<f64>,
+    projections: &Array1<f64>,
+) -> f64 {
+    let diff = system_matrix.dot(volume) - projections;
+    diff.dot(&diff).sqrt() / projections.dot(projections).sqrt()
+}
+
+#[test]
+fn f64_reaches_lower_residual_than_f32() {
+    // Small consistent system: both precisions converge, so the remaining
+    // residual is set by rounding error rather than iteration count.
+    let system_matrix: Array2<f64> = array![[0.7, 0.2, 0.1], [0.3, 0.9, 0.4], [0.1, 0.3, 0.8]];
+    let truth: Array1<f64> = array![1.0, 2.0, 3.0];
+    let projections = system_matrix.dot(&truth);
+
+    let options = ReconOptions::default();
+    let vol_f64 = art_reconstruct(&projections, &system_matrix, 500, 1.0, &options).unwrap();
+    let vol_f32 = art_reconstruct(
+        &projections.mapv(|v| v as f32),
+        &system_matrix.mapv(|v| v as f32),
+        500,
+        1.0f32,
+        &options,
+    )
+    .unwrap();
+
+    let res_f64 = relative_residual(&system_matrix, &vol_f64, &projections);
+    let res_f32 = relative_residual(&system_matrix, &vol_f32.mapv(f64::from), &projections);
+
+    assert!(
+        res_f64 < res_f32,
+        "f64 residual {res_f64:e} not below f32 residual {res_f32:e}"
+    );
+}
+
+#[test]
+fn mart_step_matches_scalar_loop_within_f32_tolerance() {
+    let (m, n) = (64, 256);
+    let system_matrix = Array2::from_shape_fn((m, n), |(i, j)| {
+        let v = ((i * 7919 + j * 104_729) % 1013) as f32 / 1013.0;
+        if v > 0.5 {
+            v
+        } else {
+            0.0
+        }
+    });
+    let projections =
+        system_matrix.dot(&Array1::from_shape_fn(n, |j| 1.0 + (j % 13) as f32 / 13.0));
+
+    // reference: the plain left-to-right accumulation of y_hat
+    let mut expected = Array1::<f32>::from_elem(n, 1.0);
+    for i in 0..m {
+        let mut y_hat = 0.0;
+        for j in 0..n {
+            y_hat += system_matrix[[i, j]] * expected[j];
+        }
+        let factor = (projections[i] / y_hat).powf(0.5);
+        for j in 0..n {
+            if system_matrix[[i, j]] > 0.0 {
+                expected[j] *= factor;
+            }
+        }
+    }
+
+    let mut volume = Array1::<f32>::from_elem(n, 1.0);
+    mart_step_unchecked(&projections, &system_matrix, &mut volume, 0.5);
+
+    for (j, (&got, &want)) in volume.iter().zip(&expected).enumerate() {
+        assert!(
+            (got - want).abs() <= 1e-4 * want.abs(),
+            "voxel {j}: {got} vs {want}"
+        );
+    }
+}
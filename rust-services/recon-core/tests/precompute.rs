@@ -0,0 +1,24 @@
+use ndarray::array;
+
+use recon_core::Precomputed;
+
+#[test]
+fn sums_match_the_matrix() {
+    let system_matrix = array![[1.0, 2.0, 0.0], [0.0, 3.0, 4.0]];
+    let precomputed = Precomputed::new(&system_matrix, None);
+
+    assert_eq!(precomputed.row_norms_sq, array![5.0, 25.0]);
+    assert_eq!(precomputed.row_sums, array![3.0, 7.0]);
+    assert_eq!(precomputed.col_sums, array![1.0, 5.0, 4.0]);
+}
+
+#[test]
+fn mask_drops_voxels_from_every_sum() {
+    let system_matrix = array![[1.0, 2.0, 0.0], [0.0, 3.0, 4.0]];
+    let mask = array![true, false, true];
+    let precomputed = Precomputed::new(&system_matrix, Some(&mask));
+
+    assert_eq!(precomputed.row_norms_sq, array![1.0, 16.0]);
+    assert_eq!(precomputed.row_sums, array![1.0, 4.0]);
+    assert_eq!(precomputed.col_sums, array![1.0, 0.0, 4.0]);
+}
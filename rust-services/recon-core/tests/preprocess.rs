@@ -0,0 +1,106 @@
+use ndarray::{array, Array1};
+
+use recon_core::preprocess::{MIN_FLAT_DARK_GAP, MIN_TRANSMISSION};
+use recon_core::{flat_dark_correct, log_transform, ReconError};
+
+#[test]
+fn intensities_become_line_integrals() {
+    let line_integrals = array![0.0, 0.5, 2.0];
+    let intensities = line_integrals.mapv(|p: f64| 1000.0 * (-p).exp());
+
+    let scalar = log_transform(&intensities, &array![1000.0]).unwrap();
+    assert!(
+        (&scalar - &line_integrals).iter().all(|d| d.abs() < 1e-12),
+        "{scalar}"
+    );
+
+    // per-ray flat field: the same reading under twice the I0 adds ln 2
+    let per_ray = log_transform(&intensities, &array![1000.0, 2000.0, 1000.0]).unwrap();
+    assert!((per_ray[1] - (0.5 + 2f64.ln())).abs() < 1e-12);
+}
+
+#[test]
+fn transmissions_are_clamped_before_the_log() {
+    let projections = log_transform(&array![0.0, -3.0, 1200.0], &array![1000.0]).unwrap();
+
+    let max = -MIN_TRANSMISSION.ln();
+    assert_eq!(projections, array![max, max, 0.0]);
+}
+
+#[test]
+fn invalid_flat_field_is_rejected() {
+    let intensities = array![1.0, 2.0];
+
+    assert!(matches!(
+        log_transform(&intensities, &array![1.0, 0.0]),
+        Err(ReconError::InvalidFlatField { index: 1, .. })
+    ));
+    assert!(matches!(
+        log_transform(&intensities, &array![1.0, 1.0, 1.0]),
+        Err(ReconError::DimensionMismatch { actual: 3, .. })
+    ));
+}
+
+#[test]
+fn flat_and_dark_fields_normalize_raw_readings() {
+    let transmission = array![1.0f64, 0.5, 0.25];
+    let dark = array![10.0, 20.0, 10.0];
+    let flat = array![1010.0];
+    let raw = &transmission * 1000.0 + &dark;
+
+    let (corrected, clamped) = flat_dark_correct(&raw, &dark, &flat).unwrap();
+    // per-ray dark against a shared flat: the gain differs where the dark does
+    assert!(
+        (corrected[0] - 1.0).abs() < 1e-12 && (corrected[2] - 0.25).abs() < 1e-12,
+        "{corrected}"
+    );
+    assert!((corrected[1] - 500.0 / 990.0).abs() < 1e-12);
+    assert!(clamped.is_empty());
+
+    let (no_dark, _) = flat_dark_correct(&array![500.0f64], &array![0.0], &array![1000.0]).unwrap();
+    assert!((log_transform(&no_dark, &array![1.0]).unwrap()[0] - 2f64.ln()).abs() < 1e-12);
+}
+
+#[test]
+fn flat_at_or_below_dark_is_clamped_and_reported() {
+    let raw = array![5.0f64, 5.0, 8.0];
+    let (corrected, clamped) =
+        flat_dark_correct(&raw, &array![4.0], &array![6.0, 4.0, 3.0]).unwrap();
+
+    assert_eq!(clamped, vec![1, 2]);
+    assert_eq!(corrected[0], 0.5);
+    assert!(corrected.iter().all(|v| v.is_finite()));
+    assert_eq!(corrected[1], 1.0 / MIN_FLAT_DARK_GAP);
+
+    assert!(matches!(
+        flat_dark_correct(&raw, &array![0.0, 0.0], &array![1.0]),
+        Err(ReconError::DimensionMismatch {
+            what: "dark field",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn u16_counts_become_line_integrals() {
+    // raw 16-bit detector counts, cast to f32 as the CLI loads them
+    let (dark, flat) = (array![100u16], array![65535u16]);
+    let line_integrals = array![0.0f32, 1.0, 4.0, 12.0];
+    let counts = line_integrals.mapv(|p| (100.0 + 65435.0 * (-p).exp()).round() as u16);
+    let cast = |c: &Array1<u16>| c.mapv(f32::from);
+
+    let (transmission, clamped) =
+        flat_dark_correct(&cast(&counts), &cast(&dark), &cast(&flat)).unwrap();
+    assert!(clamped.is_empty());
+    let projections = log_transform(&transmission, &array![1.0]).unwrap();
+    assert!(
+        (&projections - &line_integrals)
+            .iter()
+            .take(3)
+            .all(|d| d.abs() < 1e-3),
+        "{projections}"
+    );
+    // fully shadowed: the reading rounds to the dark level and the log is capped
+    assert_eq!(counts[3], 100);
+    assert_eq!(projections[3], -(MIN_TRANSMISSION as f32).ln());
+}
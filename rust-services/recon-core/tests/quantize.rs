@@ -0,0 +1,41 @@
+use ndarray::{array, Array1};
+
+use recon_core::Quantization;
+
+#[test]
+fn round_trip_is_within_one_step() {
+    let volume = Array1::from_shape_fn(1000, |j| ((j as f64) * 0.37).sin() * 3.0 + 0.5);
+    let quantization = Quantization::from_volume(&volume);
+    let codes = quantization.quantize(&volume);
+    assert_eq!(codes.iter().min(), Some(&0));
+    assert_eq!(codes.iter().max(), Some(&u16::MAX));
+
+    let restored: Array1<f64> = quantization.dequantize(&codes);
+    let worst = volume
+        .iter()
+        .zip(&restored)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max);
+    assert!(
+        worst <= quantization.scale,
+        "error {worst}, step {}",
+        quantization.scale
+    );
+}
+
+#[test]
+fn explicit_range_clamps_outliers() {
+    let quantization = Quantization::from_range(0.0, 1.0);
+    let codes = quantization.quantize(&array![-0.5f32, 0.0, 0.5, 1.0, 7.0, f32::NAN]);
+    assert_eq!(codes, array![0, 0, 32768, u16::MAX, u16::MAX, 0]);
+}
+
+#[test]
+fn constant_volume_quantizes_to_zero() {
+    let volume = Array1::from_elem(5, 2.5f64);
+    let quantization = Quantization::from_volume(&volume);
+    assert_eq!(quantization.scale, 0.0);
+    let codes = quantization.quantize(&volume);
+    assert!(codes.iter().all(|&q| q == 0));
+    assert_eq!(quantization.dequantize::<f64>(&codes), volume);
+}
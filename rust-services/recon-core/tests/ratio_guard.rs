@@ -0,0 +1,106 @@
+use ndarray::array;
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse, mart_reconstruct_sparse_report, phantom, Geometry,
+    PhantomKind, ReconOptions, SparseSystemMatrix,
+};
+
+// one ray over two voxels: from a uniform start of 1, y_hat = 2 and the
+// unclamped ratio is 10 / 2 = 5
+
+#[test]
+fn ratio_clamp_limits_single_ray_update() {
+    let (projections, system_matrix) = (array![10.0], array![[1.0, 1.0]]);
+    let options = ReconOptions {
+        ratio_clamp: Some((0.5, 2.0)),
+        ..ReconOptions::default()
+    };
+
+    let plain = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        1,
+        1.0,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(plain, array![5.0, 5.0]);
+
+    let clamped = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    assert_eq!(clamped, array![2.0, 2.0]);
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &options).unwrap(),
+        clamped
+    );
+}
+
+#[test]
+fn rays_at_or_below_floor_are_skipped() {
+    let (projections, system_matrix) = (array![10.0], array![[1.0, 1.0]]);
+    let options = ReconOptions {
+        floor_eps: 2.0,
+        ..ReconOptions::default()
+    };
+
+    let volume = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    assert_eq!(volume, array![1.0, 1.0]);
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &options).unwrap(),
+        volume
+    );
+}
+
+#[test]
+fn rays_with_negligible_factor_are_skipped() {
+    let (projections, system_matrix) = (array![10.0], array![[1.0, 1.0]]);
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let skip = |skip_eps| ReconOptions {
+        skip_eps,
+        ..ReconOptions::default()
+    };
+
+    // the factor is 5, so 4.5 skips the ray and 3.5 does not
+    let skipped = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &skip(4.5)).unwrap();
+    assert_eq!(skipped, array![1.0, 1.0]);
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &skip(4.5)).unwrap(),
+        skipped
+    );
+    assert_eq!(
+        mart_reconstruct(&projections, &system_matrix, 1, 1.0, &skip(3.5)).unwrap(),
+        array![5.0, 5.0]
+    );
+}
+
+#[test]
+fn small_skip_eps_keeps_the_converged_volume() {
+    let geometry = Geometry::from_json(
+        r#"{"kind": "parallel_beam", "num_angles": 24, "num_detectors": 16,
+            "volume_shape": [16, 16]}"#,
+    )
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    let projections =
+        system_matrix.dot(&phantom::<f64>(PhantomKind::SheppLogan, [16, 16]).mapv(|v| v + 0.1));
+
+    let run = |skip_eps| {
+        let options = ReconOptions {
+            skip_eps,
+            ..ReconOptions::default()
+        };
+        mart_reconstruct_sparse_report(&projections, &system_matrix, 40, 1.0, &options).unwrap()
+    };
+    let (plain, skipping) = (run(0.0), run(1e-6));
+    let worst = plain
+        .volume
+        .iter()
+        .zip(&skipping.volume)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max);
+    assert!(worst < 1e-4, "volumes differ by {worst}");
+    assert!((plain.final_residual - skipping.final_residual).abs() < 1e-6);
+}
@@ -0,0 +1,91 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse, ReconError, ReconOptions, SparseSystemMatrix,
+};
+
+/// Two rays over the same two voxels that disagree: from a uniform start of
+/// 1 the first ray is already satisfied (ratio 1), the second asks for a
+/// factor of 2.
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    (array![2.0, 4.0], array![[1.0, 1.0], [1.0, 1.0]])
+}
+
+fn weighted(weights: Array1<f64>) -> ReconOptions {
+    ReconOptions {
+        ray_weights: Some(weights),
+        ..ReconOptions::default()
+    }
+}
+
+#[test]
+fn weight_scales_ray_relaxation() {
+    let (projections, system_matrix) = problem();
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+
+    let full = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        1,
+        1.0,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(full, array![2.0, 2.0]);
+
+    let options = weighted(array![1.0, 0.5]);
+    let half = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    let expected = 2f64.sqrt();
+    assert!(half.iter().all(|&x| (x - expected).abs() < 1e-12), "{half}");
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &options).unwrap(),
+        half
+    );
+}
+
+#[test]
+fn zero_weight_drops_the_ray() {
+    let (projections, system_matrix) = problem();
+
+    let options = weighted(array![1.0, 0.0]);
+    let volume = mart_reconstruct(&projections, &system_matrix, 10, 1.0, &options).unwrap();
+    assert_eq!(volume, array![1.0, 1.0]);
+}
+
+#[test]
+fn invalid_weights_are_rejected() {
+    let (projections, system_matrix) = problem();
+
+    let negative = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        1,
+        1.0,
+        &weighted(array![1.0, -0.5]),
+    );
+    assert_eq!(
+        negative,
+        Err(ReconError::NegativeWeight {
+            index: 1,
+            value: -0.5
+        })
+    );
+
+    let short = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &weighted(array![1.0]));
+    assert!(
+        matches!(short, Err(ReconError::DimensionMismatch { .. })),
+        "{short:?}"
+    );
+
+    let nan = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        1,
+        1.0,
+        &weighted(array![f64::NAN, 1.0]),
+    );
+    assert!(
+        matches!(nan, Err(ReconError::NonFinite { index: 0, .. })),
+        "{nan:?}"
+    );
+}
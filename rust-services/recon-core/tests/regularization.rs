@@ -0,0 +1,133 @@
+use ndarray::Array1;
+
+use recon_core::{
+    add_noise, mart_reconstruct_sparse, total_variation, Geometry, HuberRegularization,
+    L2Regularization, NoiseModel, ReconOptions,
+};
+
+const SIZE: usize = 24;
+
+/// Vertical step edge: 0.2 left of the middle column, 1.0 from it on.
+fn step_edge() -> Array1<f64> {
+    Array1::from_shape_fn(SIZE * SIZE, |j| if j % SIZE < SIZE / 2 { 0.2 } else { 1.0 })
+}
+
+/// Mean jump between the two columns either side of the edge.
+fn edge_jump(volume: &Array1<f64>) -> f64 {
+    (0..SIZE)
+        .map(|r| volume[r * SIZE + SIZE / 2] - volume[r * SIZE + SIZE / 2 - 1])
+        .sum::<f64>()
+        / SIZE as f64
+}
+
+/// Standard deviation of the voxels at least three columns from the edge.
+fn flat_noise(volume: &Array1<f64>, truth: &Array1<f64>) -> f64 {
+    let errors: Vec<f64> = (0..SIZE * SIZE)
+        .filter(|j| (j % SIZE).abs_diff(SIZE / 2) >= 3)
+        .map(|j| volume[j] - truth[j])
+        .collect();
+    let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+    (errors.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / errors.len() as f64).sqrt()
+}
+
+#[test]
+fn huber_keeps_edges_sharper_than_l2() {
+    let geometry = Geometry::from_json(&format!(
+        r#"{{"kind": "parallel_beam", "num_angles": 30, "num_detectors": {SIZE},
+         "volume_shape": [{SIZE}, {SIZE}]}}"#
+    ))
+    .unwrap();
+    let system_matrix = geometry.build_system_matrix::<f64>();
+    let truth = step_edge();
+    let projections = add_noise(&system_matrix.dot(&truth), NoiseModel::Gaussian, 0.005, 7);
+
+    let run = |options: ReconOptions| {
+        mart_reconstruct_sparse(&projections, &system_matrix, 20, 0.5, &options).unwrap()
+    };
+    let plain = run(ReconOptions::default());
+    let l2 = run(ReconOptions {
+        l2_regularization: Some(L2Regularization {
+            weight: 0.1,
+            volume_shape: [SIZE, SIZE],
+        }),
+        ..ReconOptions::default()
+    });
+    let huber = run(ReconOptions {
+        huber_regularization: Some(HuberRegularization {
+            delta: 0.1,
+            weight: 0.1,
+            volume_shape: [1, SIZE, SIZE],
+        }),
+        ..ReconOptions::default()
+    });
+
+    // comparable smoothing of the flat regions...
+    assert!(flat_noise(&l2, &truth) < 0.5 * flat_noise(&plain, &truth));
+    assert!(flat_noise(&huber, &truth) < 1.5 * flat_noise(&l2, &truth));
+    // ...but L2 smears the 0.8 step and Huber mostly keeps it
+    let (l2_jump, huber_jump) = (edge_jump(&l2), edge_jump(&huber));
+    assert!(
+        huber_jump > 1.25 * l2_jump,
+        "Huber edge {huber_jump}, L2 edge {l2_jump}"
+    );
+}
+
+#[test]
+fn huber_with_large_delta_is_the_l2_step() {
+    let mut l2 = step_edge();
+    let mut huber = l2.clone();
+    L2Regularization {
+        weight: 0.1,
+        volume_shape: [SIZE, SIZE],
+    }
+    .apply(&mut l2);
+    HuberRegularization {
+        delta: 1e6,
+        weight: 0.1,
+        volume_shape: [1, SIZE, SIZE],
+    }
+    .apply(&mut huber);
+    assert!(l2.iter().zip(&huber).all(|(a, b)| (a - b).abs() < 1e-12));
+}
+
+#[test]
+fn huber_smooths_across_slices() {
+    // single bright voxel in the middle of a 3x3x3 stack
+    let mut volume = Array1::<f64>::zeros(27);
+    volume[13] = 1.0;
+    HuberRegularization {
+        delta: 10.0,
+        weight: 0.05,
+        volume_shape: [3, 3, 3],
+    }
+    .apply(&mut volume);
+    assert!((volume[13] - 0.7).abs() < 1e-12, "{}", volume[13]);
+    for neighbor in [4, 22, 10, 16, 12, 14] {
+        assert!(
+            (volume[neighbor] - 0.05).abs() < 1e-12,
+            "voxel {neighbor} is {}",
+            volume[neighbor]
+        );
+    }
+    assert!((volume.sum() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn total_variation_sums_gradient_magnitudes() {
+    let edge = step_edge();
+    // one jump of 0.8 per row
+    assert!((total_variation(&edge, [1, SIZE, SIZE]) - 0.8 * SIZE as f64).abs() < 1e-12);
+    assert_eq!(
+        total_variation(&Array1::from_elem(SIZE * SIZE, 0.7), [1, SIZE, SIZE]),
+        0.0
+    );
+
+    let noisy = add_noise(&edge, NoiseModel::Gaussian, 0.05, 1);
+    assert!(
+        total_variation(&noisy, [1, SIZE, SIZE]) > 2.0 * total_variation(&edge, [1, SIZE, SIZE])
+    );
+
+    // two flat slices 0.5 apart: every voxel of the first steps up once
+    let stack = Array1::from_shape_fn(2 * SIZE * SIZE, |j| if j < SIZE * SIZE { 0.2 } else { 0.7 });
+    assert!((total_variation(&stack, [2, SIZE, SIZE]) - 0.5 * (SIZE * SIZE) as f64).abs() < 1e-9);
+}
@@ -0,0 +1,60 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{mart_reconstruct, ReconOptions, RelaxationSchedule};
+
+fn problem() -> (Array1<f32>, Array2<f32>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+#[test]
+fn constant_schedule_matches_fixed_relaxation() {
+    let (projections, system_matrix) = problem();
+
+    let fixed = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        25,
+        0.3,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let scheduled = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        25,
+        // ignored: the schedule takes precedence
+        0.9,
+        &ReconOptions {
+            relaxation_schedule: Some(RelaxationSchedule::Constant(0.3)),
+            ..ReconOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(fixed, scheduled);
+}
+
+#[test]
+fn schedules_interpolate_between_endpoints() {
+    let linear = RelaxationSchedule::Linear {
+        start: 1.0,
+        end: 0.2,
+    };
+    assert_eq!(linear.at(0, 5), 1.0);
+    assert!((linear.at(2, 5) - 0.6).abs() < 1e-12);
+    assert!((linear.at(4, 5) - 0.2).abs() < 1e-12);
+
+    let geometric = RelaxationSchedule::Geometric {
+        start: 1.0,
+        factor: 0.5,
+    };
+    assert_eq!(geometric.at(3, 10), 0.125);
+}
@@ -0,0 +1,65 @@
+use ndarray::array;
+
+use recon_core::{
+    mart_reconstruct_report, mart_reconstruct_sparse_report, relative_residual, residual_vector,
+    sirt_reconstruct_report, ReconOptions, SparseSystemMatrix,
+};
+
+#[test]
+fn residual_is_forward_projection_minus_data() {
+    let system_matrix = array![[1.0, 2.0], [0.0, 1.0], [3.0, 0.0]];
+    let projections = array![3.0, 2.0, 0.0];
+    let volume = array![1.0, 1.0];
+
+    assert_eq!(
+        residual_vector(&projections, &system_matrix, &volume),
+        array![0.0, -1.0, 3.0]
+    );
+    let expected = (10.0f64 / 13.0).sqrt();
+    assert!((relative_residual(&projections, &system_matrix, &volume) - expected).abs() < 1e-15);
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    assert_eq!(
+        residual_vector(&projections, &sparse, &volume),
+        array![0.0, -1.0, 3.0]
+    );
+
+    // all-zero data fall back to the absolute norm
+    let zeros = array![0.0, 0.0, 0.0];
+    assert_eq!(
+        relative_residual(&zeros, &system_matrix, &volume),
+        19.0f64.sqrt()
+    );
+}
+
+#[test]
+fn solvers_report_the_public_residual() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = array![1.4, 2.1, 1.1, 2.5];
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let options = ReconOptions::default();
+
+    let mart = mart_reconstruct_report(&projections, &system_matrix, 7, 0.5, &options).unwrap();
+    assert_eq!(
+        mart.final_residual,
+        relative_residual(&projections, &system_matrix, &mart.volume)
+    );
+
+    let sparse_mart =
+        mart_reconstruct_sparse_report(&projections, &sparse, 7, 0.5, &options).unwrap();
+    assert_eq!(
+        sparse_mart.final_residual,
+        relative_residual(&projections, &sparse, &sparse_mart.volume)
+    );
+
+    let sirt = sirt_reconstruct_report(&projections, &system_matrix, 7, 0.5, &options).unwrap();
+    assert_eq!(
+        sirt.final_residual,
+        relative_residual(&projections, &system_matrix, &sirt.volume)
+    );
+}
@@ -0,0 +1,74 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse, mart_reconstruct_sparse_with_callback, mart_resume,
+    InitialGuess, ReconOptions, RelaxationSchedule, RowOrder, SparseSystemMatrix,
+};
+
+fn problem() -> (Array1<f32>, Array2<f32>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+#[test]
+fn resume_matches_uninterrupted_run() {
+    let (projections, system_matrix) = problem();
+    let options = ReconOptions::default();
+
+    let full = mart_reconstruct(&projections, &system_matrix, 20, 0.5, &options).unwrap();
+    let checkpoint = mart_reconstruct(&projections, &system_matrix, 8, 0.5, &options).unwrap();
+    let resumed = mart_resume(&projections, &system_matrix, &checkpoint, 12, 0.5).unwrap();
+
+    assert_eq!(full, resumed);
+}
+
+#[test]
+fn start_iteration_continues_schedules_and_shuffles() {
+    let (projections, system_matrix) = problem();
+    let system_matrix = SparseSystemMatrix::from_dense(&system_matrix);
+    let options = ReconOptions {
+        row_order: RowOrder::Shuffled { seed: 3 },
+        relaxation_schedule: Some(RelaxationSchedule::Linear {
+            start: 1.0,
+            end: 0.1,
+        }),
+        ..ReconOptions::default()
+    };
+
+    // snapshot the volume after 8 of 20 iterations, like a CLI checkpoint
+    let mut checkpoint = None;
+    let full = mart_reconstruct_sparse_with_callback(
+        &projections,
+        &system_matrix,
+        20,
+        1.0,
+        &options,
+        |iter, volume, _| {
+            if iter == 7 {
+                checkpoint = Some(volume.mapv(f64::from));
+            }
+        },
+    )
+    .unwrap();
+    let resumed = mart_reconstruct_sparse(
+        &projections,
+        &system_matrix,
+        12,
+        1.0,
+        &ReconOptions {
+            initial_guess: InitialGuess::FromArray(checkpoint.unwrap()),
+            start_iteration: 8,
+            ..options
+        },
+    )
+    .unwrap();
+
+    assert_eq!(full.volume, resumed);
+}
@@ -0,0 +1,89 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    cgls_reconstruct, mart_reconstruct, normalize_rows, normalize_rows_sparse, restore_rows,
+    ReconOptions, SparseSystemMatrix,
+};
+
+/// An overdetermined, consistent system whose row norms span six orders of
+/// magnitude, plus an all-zero ray.
+fn badly_scaled() -> (Array2<f64>, Array1<f64>, Array1<f64>) {
+    let system_matrix = array![
+        [1e-3, 2e-3, 0.0, 0.0],
+        [0.0, 1.0, 1.0, 0.0],
+        [0.0, 0.0, 4e2, 3e2],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 0.0, 0.0, 0.0],
+        [2e3, 0.0, 1e3, 0.0],
+        [0.0, 0.5, 0.0, 0.5],
+    ];
+    let truth = array![0.4, 1.1, 0.7, 0.2];
+    let projections = system_matrix.dot(&truth);
+    (system_matrix, truth, projections)
+}
+
+#[test]
+fn rows_get_unit_norm() {
+    let (mut system_matrix, _, mut projections) = badly_scaled();
+    let (original_matrix, original_projections) = (system_matrix.clone(), projections.clone());
+    let norms = normalize_rows(&mut projections, &mut system_matrix);
+
+    assert_eq!(norms[4], 1.0);
+    assert!((norms[2] - 500.0).abs() < 1e-9);
+    for (i, row) in system_matrix
+        .rows()
+        .into_iter()
+        .enumerate()
+        .filter(|&(i, _)| i != 4)
+    {
+        assert!((row.dot(&row) - 1.0).abs() < 1e-12, "row {i}");
+        assert!(
+            (projections[i] * norms[i] - original_projections[i]).abs() < 1e-9,
+            "ray {i}"
+        );
+    }
+
+    let mut sparse = SparseSystemMatrix::from_dense(&original_matrix);
+    let mut sparse_projections = original_projections.clone();
+    let sparse_norms = normalize_rows_sparse(&mut sparse_projections, &mut sparse);
+    assert_eq!(sparse_norms, norms);
+    assert_eq!(sparse, SparseSystemMatrix::from_dense(&system_matrix));
+    assert_eq!(sparse_projections, projections);
+
+    restore_rows(&mut projections, &mut system_matrix, &norms);
+    assert!((&system_matrix - &original_matrix)
+        .iter()
+        .all(|d| d.abs() < 1e-9));
+    assert!((&projections - &original_projections)
+        .iter()
+        .all(|d| d.abs() < 1e-9));
+}
+
+#[test]
+fn scaled_problem_has_the_same_solution() {
+    let (system_matrix, truth, projections) = badly_scaled();
+    let unscaled = cgls_reconstruct(&projections, &system_matrix, 50).unwrap();
+
+    let (mut scaled_matrix, mut scaled_projections) = (system_matrix.clone(), projections.clone());
+    normalize_rows(&mut scaled_projections, &mut scaled_matrix);
+    let scaled = cgls_reconstruct(&scaled_projections, &scaled_matrix, 50).unwrap();
+    assert!(
+        (&scaled - &unscaled).iter().all(|d| d.abs() < 1e-6),
+        "{scaled} vs {unscaled}"
+    );
+    assert!(
+        (&scaled - &truth).iter().all(|d| d.abs() < 1e-6),
+        "{scaled}"
+    );
+
+    // one relaxation for every ray: MART converges on the scaled rays
+    let mart = mart_reconstruct(
+        &scaled_projections,
+        &scaled_matrix,
+        2000,
+        0.5,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert!((&mart - &truth).iter().all(|d| d.abs() < 1e-4), "{mart}");
+}
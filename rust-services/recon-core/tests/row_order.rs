@@ -0,0 +1,52 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{mart_reconstruct, ReconOptions, RowOrder};
+
+fn problem() -> (Array1<f32>, Array2<f32>) {
+    let system_matrix = array![
+        [1.0, 0.5, 0.0, 0.2],
+        [0.0, 1.0, 0.7, 0.0],
+        [0.3, 0.0, 1.0, 0.4],
+        [0.6, 0.2, 0.0, 1.0],
+        [0.1, 0.8, 0.3, 0.5],
+    ];
+    let truth = array![1.0, 2.0, 0.5, 1.5];
+    let projections = system_matrix.dot(&truth);
+    (projections, system_matrix)
+}
+
+fn shuffled(seed: u64) -> ReconOptions {
+    ReconOptions {
+        row_order: RowOrder::Shuffled { seed },
+        ..ReconOptions::default()
+    }
+}
+
+#[test]
+fn same_seed_gives_bit_identical_output() {
+    let (projections, system_matrix) = problem();
+
+    let first = mart_reconstruct(&projections, &system_matrix, 20, 0.5, &shuffled(42)).unwrap();
+    let second = mart_reconstruct(&projections, &system_matrix, 20, 0.5, &shuffled(42)).unwrap();
+
+    let first_bits: Vec<u32> = first.iter().map(|v| v.to_bits()).collect();
+    let second_bits: Vec<u32> = second.iter().map(|v| v.to_bits()).collect();
+    assert_eq!(first_bits, second_bits);
+}
+
+#[test]
+fn shuffled_order_differs_from_sequential() {
+    let (projections, system_matrix) = problem();
+
+    let sequential = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        3,
+        0.5,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    let random = mart_reconstruct(&projections, &system_matrix, 3, 0.5, &shuffled(7)).unwrap();
+
+    assert_ne!(sequential, random);
+}
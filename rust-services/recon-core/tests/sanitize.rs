@@ -0,0 +1,76 @@
+use ndarray::{array, Array1};
+
+use recon_core::{
+    mart_reconstruct, sanitize_inputs, sanitize_inputs_sparse, ReconOptions, SparseSystemMatrix,
+};
+
+#[test]
+fn non_finite_values_disable_their_rays() {
+    let mut system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, f64::INFINITY],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+    ];
+    let mut projections = array![0.9, 1.7, f64::NAN, 1.1];
+    let report = sanitize_inputs(&mut projections, &mut system_matrix);
+
+    assert_eq!((report.projections, report.matrix_entries), (1, 1));
+    assert_eq!(report.disabled_rays, vec![1, 2]);
+    assert_eq!(projections, array![0.9, 0.0, 0.0, 1.1]);
+    assert!(system_matrix
+        .row(1)
+        .iter()
+        .chain(system_matrix.row(2))
+        .all(|&a| a == 0.0));
+    assert_eq!(system_matrix.row(3), array![0.0, 1.0, 0.0, 1.0]);
+
+    // already clean inputs are left untouched
+    let again = sanitize_inputs(&mut projections, &mut system_matrix);
+    assert!(again.is_clean());
+}
+
+#[test]
+fn sparse_sanitize_matches_dense() {
+    let dense = array![[1.0, f64::NAN, 0.0], [0.0, 1.0, 1.0], [1.0, 0.0, 1.0]];
+    let mut sparse = SparseSystemMatrix::from_dense(&dense);
+    let mut dense = dense;
+    let projections = array![1.0, f64::NEG_INFINITY, 2.0];
+    let (mut dense_y, mut sparse_y) = (projections.clone(), projections);
+
+    let dense_report = sanitize_inputs(&mut dense_y, &mut dense);
+    let sparse_report = sanitize_inputs_sparse(&mut sparse_y, &mut sparse);
+    assert_eq!(dense_report, sparse_report);
+    assert_eq!(dense_y, sparse_y);
+    let ones = Array1::ones(3);
+    assert_eq!(sparse.dot(&ones), dense.dot(&ones));
+}
+
+#[test]
+fn mart_reconstructs_around_dropouts() {
+    let mut system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 0.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    let mut projections = system_matrix.dot(&phantom);
+    projections[4] = f64::NAN;
+
+    let report = sanitize_inputs(&mut projections, &mut system_matrix);
+    assert_eq!(report.disabled_rays, vec![4]);
+    let volume: Array1<f64> = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        200,
+        1.0,
+        &ReconOptions::default(),
+    )
+    .unwrap();
+    assert!(volume.iter().all(|x| x.is_finite()));
+    let residual = (system_matrix.dot(&volume) - &projections).mapv(f64::abs);
+    assert!(residual.iter().all(|&r| r < 1e-3), "residual {residual}");
+}
@@ -0,0 +1,68 @@
+use ndarray::{array, Array1};
+
+use recon_core::{
+    sart_reconstruct, sirt_reconstruct, InitialGuess, ReconOptions, SparseSystemMatrix,
+};
+
+fn max_abs_diff(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn diagonal_system_closes_the_gap_geometrically() {
+    // rowsum_i = colsum_i = a_ii, so each pass removes a `relaxation`
+    // fraction of the remaining error: x_k = x* + (x_0 - x*) (1 - relaxation)^k
+    let system_matrix = array![[2.0, 0.0], [0.0, 4.0]];
+    let truth = array![1.0, 2.0];
+    let projections = system_matrix.dot(&truth);
+    let options = ReconOptions {
+        initial_guess: InitialGuess::Uniform(0.0),
+        ..ReconOptions::default()
+    };
+
+    for n_iters in 1..=4 {
+        let volume =
+            sart_reconstruct(&projections, &system_matrix, n_iters, 0.5, &options).unwrap();
+        let expected = &truth * (1.0 - 0.5f64.powi(n_iters as i32));
+        assert!(
+            max_abs_diff(&volume, &expected) < 1e-12,
+            "{n_iters} iterations: {volume}"
+        );
+    }
+}
+
+#[test]
+fn single_ray_is_shared_by_area() {
+    // one ray through two voxels of 1 and 3 units: y_hat = 0 and the
+    // residual 8 spread over rowsum 4, weighted by each voxel's length
+    let system_matrix = array![[1.0, 3.0]];
+    let options = ReconOptions {
+        initial_guess: InitialGuess::Uniform(0.0),
+        ..ReconOptions::default()
+    };
+    let volume = sart_reconstruct(&array![8.0], &system_matrix, 1, 1.0, &options).unwrap();
+    assert_eq!(volume, array![2.0, 2.0]);
+}
+
+#[test]
+fn matches_sirt() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.3, 1.2, 0.7]);
+    let options = ReconOptions::default();
+
+    let sart = sart_reconstruct(&projections, &system_matrix, 10, 0.8, &options).unwrap();
+    let sirt = sirt_reconstruct(&projections, &system_matrix, 10, 0.8, &options).unwrap();
+    assert!(max_abs_diff(&sart, &sirt) < 1e-12);
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let sparse_sart = sart_reconstruct(&projections, &sparse, 10, 0.8, &options).unwrap();
+    assert!(max_abs_diff(&sparse_sart, &sart) < 1e-12);
+}
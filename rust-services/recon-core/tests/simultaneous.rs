@@ -0,0 +1,99 @@
+use ndarray::{Array1, Array2, Axis};
+
+use recon_core::{
+    mart_reconstruct, sart_reconstruct, sirt_reconstruct, smart_reconstruct, ReconOptions,
+    SparseSystemMatrix,
+};
+
+/// An overdetermined system with inconsistent projections, where a
+/// sequential update would show its ray and voxel order.
+fn problem() -> (Array2<f64>, Array1<f64>) {
+    let system_matrix = Array2::from_shape_fn((7, 5), |(i, j)| {
+        ((i * 5 + j * 3) % 4 + (i == j) as usize) as f64 * 0.5
+    });
+    let truth = Array1::from_shape_fn(5, |j| 0.5 + 0.3 * j as f64);
+    let noise = Array1::from_shape_fn(7, |i| if i % 2 == 0 { 0.05 } else { -0.04 });
+    let projections = system_matrix.dot(&truth) + noise;
+    (system_matrix, projections)
+}
+
+/// Voxel order: column `k` of the permuted matrix is column `VOXELS[k]`.
+const VOXELS: [usize; 5] = [3, 0, 4, 1, 2];
+/// Ray permutation applied to the matrix rows and the projections.
+const RAYS: [usize; 7] = [6, 2, 0, 5, 3, 1, 4];
+
+fn assert_close(a: &Array1<f64>, b: &Array1<f64>) {
+    assert!(
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12),
+        "{a}\n{b}"
+    );
+}
+
+#[test]
+fn simultaneous_solvers_do_not_depend_on_the_voxel_order() {
+    let (system_matrix, projections) = problem();
+    let permuted = system_matrix.select(Axis(1), &VOXELS);
+    let unpermute = |volume: Array1<f64>| {
+        let mut restored = Array1::zeros(volume.len());
+        for (k, &j) in VOXELS.iter().enumerate() {
+            restored[j] = volume[k];
+        }
+        restored
+    };
+    let options = ReconOptions::default();
+
+    let sirt = sirt_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    assert_close(
+        &unpermute(sirt_reconstruct(&projections, &permuted, 30, 1.0, &options).unwrap()),
+        &sirt,
+    );
+    let sparse = SparseSystemMatrix::from_dense(&permuted);
+    assert_close(
+        &unpermute(sirt_reconstruct(&projections, &sparse, 30, 1.0, &options).unwrap()),
+        &sirt,
+    );
+
+    let sart = sart_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    assert_close(
+        &unpermute(sart_reconstruct(&projections, &permuted, 30, 1.0, &options).unwrap()),
+        &sart,
+    );
+
+    let smart = smart_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    assert_close(
+        &unpermute(smart_reconstruct(&projections, &permuted, 30, 1.0, &options).unwrap()),
+        &smart,
+    );
+}
+
+#[test]
+fn simultaneous_solvers_do_not_depend_on_the_ray_order() {
+    let (system_matrix, projections) = problem();
+    let (shuffled_matrix, shuffled) = (
+        system_matrix.select(Axis(0), &RAYS),
+        projections.select(Axis(0), &RAYS),
+    );
+    let options = ReconOptions::default();
+
+    let sirt = sirt_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    assert_close(
+        &sirt_reconstruct(&shuffled, &shuffled_matrix, 30, 1.0, &options).unwrap(),
+        &sirt,
+    );
+    let smart = smart_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    assert_close(
+        &smart_reconstruct(&shuffled, &shuffled_matrix, 30, 1.0, &options).unwrap(),
+        &smart,
+    );
+
+    // sequential MART applies each ray before projecting the next, so the
+    // same reordering changes its result on inconsistent data
+    let mart = mart_reconstruct(&projections, &system_matrix, 30, 1.0, &options).unwrap();
+    let reordered = mart_reconstruct(&shuffled, &shuffled_matrix, 30, 1.0, &options).unwrap();
+    assert!(
+        mart.iter()
+            .zip(&reordered)
+            .any(|(a, b)| (a - b).abs() > 1e-6),
+        "{mart}\n{reordered}"
+    );
+}
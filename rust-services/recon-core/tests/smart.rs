@@ -0,0 +1,90 @@
+use ndarray::{array, Array1, Array2, Axis};
+
+use recon_core::{
+    mart_reconstruct, smart_reconstruct, smart_reconstruct_with_callback, ReconError, ReconOptions,
+};
+
+/// Inconsistent data: the measurements of the 4-voxel problem with a few
+/// percent of fixed "noise", so no volume fits every ray.
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 0.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    let noise = array![1.05, 0.97, 1.02, 0.94, 1.04, 0.98];
+    (system_matrix.dot(&phantom) * &noise, system_matrix)
+}
+
+fn max_abs_diff(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn ray_order_does_not_change_the_result() {
+    let (projections, system_matrix) = problem();
+    let reversed: Vec<usize> = (0..projections.len()).rev().collect();
+    let reversed_projections = projections.select(Axis(0), &reversed);
+    let reversed_matrix = system_matrix.select(Axis(0), &reversed);
+    let options = ReconOptions::default();
+
+    let mart = mart_reconstruct(&projections, &system_matrix, 50, 1.0, &options).unwrap();
+    let mart_reversed =
+        mart_reconstruct(&reversed_projections, &reversed_matrix, 50, 1.0, &options).unwrap();
+    let smart = smart_reconstruct(&projections, &system_matrix, 50, 1.0, &options).unwrap();
+    let smart_reversed =
+        smart_reconstruct(&reversed_projections, &reversed_matrix, 50, 1.0, &options).unwrap();
+
+    let mart_spread = max_abs_diff(&mart, &mart_reversed);
+    let smart_spread = max_abs_diff(&smart, &smart_reversed);
+    assert!(mart_spread > 1e-3, "MART spread {mart_spread}");
+    assert!(smart_spread < 1e-12, "SMART spread {smart_spread}");
+}
+
+#[test]
+fn fits_consistent_data() {
+    let system_matrix = problem().1;
+    let projections = system_matrix.dot(&array![0.2, 0.7, 1.3, 0.4]);
+
+    let mut residuals = Vec::new();
+    smart_reconstruct_with_callback(
+        &projections,
+        &system_matrix,
+        500,
+        1.0,
+        &ReconOptions::default(),
+        |_, _, r| residuals.push(r),
+    )
+    .unwrap();
+    assert!(
+        residuals[499] < 1e-3 * residuals[0],
+        "{} -> {}",
+        residuals[0],
+        residuals[499]
+    );
+}
+
+#[test]
+fn rejects_negative_projections() {
+    let (mut projections, system_matrix) = problem();
+    projections[2] = -0.1;
+    let err = smart_reconstruct(
+        &projections,
+        &system_matrix,
+        5,
+        1.0,
+        &ReconOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, ReconError::NegativeProjection { index: 2, .. }),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,145 @@
+use ndarray::{array, concatenate, Array1, Array2, Axis};
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse_spectral, mart_reconstruct_spectral,
+    BasisDecomposition, ReconError, ReconOptions, SparseSystemMatrix,
+};
+
+fn system_matrix() -> Array2<f64> {
+    array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ]
+}
+
+/// Two channels stacked as `(num_energies, M)`.
+fn stack(rows: &[Array1<f64>]) -> Array2<f64> {
+    let views: Vec<_> = rows
+        .iter()
+        .map(|row| row.view().insert_axis(Axis(0)))
+        .collect();
+    concatenate(Axis(0), &views).unwrap()
+}
+
+#[test]
+fn channels_are_reconstructed_independently_with_a_shared_matrix() {
+    let system_matrix = system_matrix();
+    let (low, high) = (array![0.4, 1.2, 0.7], array![0.2, 0.5, 0.3]);
+    let projections = stack(&[system_matrix.dot(&low), system_matrix.dot(&high)]);
+    let options = ReconOptions::default();
+
+    let shared = std::slice::from_ref(&system_matrix);
+    let volume = mart_reconstruct_spectral(&projections, shared, 200, 0.5, &options).unwrap();
+    assert_eq!(volume.dim(), (2, 3));
+    for (energy, truth) in [low, high].iter().enumerate() {
+        let channel = mart_reconstruct(
+            &system_matrix.dot(truth),
+            &system_matrix,
+            200,
+            0.5,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(volume.row(energy), channel);
+        assert!(
+            channel.iter().zip(truth).all(|(x, t)| (x - t).abs() < 1e-4),
+            "{channel}"
+        );
+    }
+
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let sparse_volume =
+        mart_reconstruct_sparse_spectral(&projections, &[sparse], 200, 0.5, &options).unwrap();
+    assert!(sparse_volume
+        .iter()
+        .zip(&volume)
+        .all(|(a, b)| (a - b).abs() < 1e-12));
+}
+
+#[test]
+fn each_energy_can_have_its_own_matrix() {
+    let matrices = [system_matrix(), 2.0 * system_matrix()];
+    let truth = array![0.4, 1.2, 0.7];
+    let projections = stack(&[matrices[0].dot(&truth), matrices[1].dot(&truth)]);
+    let options = ReconOptions::default();
+
+    let volume = mart_reconstruct_spectral(&projections, &matrices, 200, 0.5, &options).unwrap();
+    assert!(
+        volume
+            .iter()
+            .zip(truth.iter().chain(&truth))
+            .all(|(x, t)| (x - t).abs() < 1e-4),
+        "{volume}"
+    );
+
+    let three = [system_matrix(), system_matrix(), system_matrix()];
+    let result = mart_reconstruct_spectral(&projections, &three, 10, 0.5, &options);
+    assert!(matches!(
+        result,
+        Err(ReconError::DimensionMismatch {
+            expected: 2,
+            actual: 3,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn basis_decomposition_recovers_the_material_maps() {
+    // water-like and bone-like coefficients at a low and a high energy
+    let basis = BasisDecomposition::new(vec![[0.27, 0.90], [0.20, 0.45]]).unwrap();
+    let materials: Array2<f64> = array![[1.0, 0.0, 0.6, 0.2], [0.0, 1.0, 0.3, 0.0]];
+    let channels = basis.synthesize(&materials).unwrap();
+    let decomposed = basis.decompose(&channels).unwrap();
+    assert!(
+        decomposed
+            .iter()
+            .zip(&materials)
+            .all(|(a, b)| (a - b).abs() < 1e-12),
+        "{decomposed}"
+    );
+
+    // a third energy turns it into a least-squares fit of the same model
+    let overdetermined =
+        BasisDecomposition::new(vec![[0.27, 0.90], [0.20, 0.45], [0.18, 0.30]]).unwrap();
+    let channels = overdetermined.synthesize(&materials).unwrap();
+    let decomposed = overdetermined.decompose(&channels).unwrap();
+    assert!(
+        decomposed
+            .iter()
+            .zip(&materials)
+            .all(|(a, b)| (a - b).abs() < 1e-12),
+        "{decomposed}"
+    );
+
+    let result = basis.decompose(&channels);
+    assert!(matches!(
+        result,
+        Err(ReconError::DimensionMismatch {
+            expected: 2,
+            actual: 3,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn indistinguishable_materials_are_rejected() {
+    assert_eq!(
+        BasisDecomposition::new(vec![[0.2, 0.4], [0.1, 0.2]]),
+        Err(ReconError::SingularBasis { energies: 2 })
+    );
+    assert_eq!(
+        BasisDecomposition::new(vec![[0.2, 0.4]]),
+        Err(ReconError::SingularBasis { energies: 1 })
+    );
+    assert_eq!(
+        BasisDecomposition::new(vec![[0.2, 0.4], [f64::NAN, 0.2]]),
+        Err(ReconError::NonFinite {
+            what: "basis coefficients",
+            index: 2
+        })
+    );
+}
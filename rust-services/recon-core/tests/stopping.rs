@@ -0,0 +1,78 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{mart_reconstruct_report, sirt_reconstruct_report, ReconOptions, StopCriterion};
+
+fn problem() -> (Array2<f64>, Array1<f64>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.5, 1.5, 1.0]);
+    (system_matrix, projections)
+}
+
+fn norm(v: &Array1<f64>) -> f64 {
+    v.dot(v).sqrt()
+}
+
+#[test]
+fn volume_tolerance_stops_once_an_iteration_barely_moves_the_volume() {
+    let (system_matrix, projections) = problem();
+    let options = ReconOptions {
+        volume_tolerance: Some(1e-5),
+        ..ReconOptions::default()
+    };
+    let report =
+        sirt_reconstruct_report(&projections, &system_matrix, 5000, 1.0, &options).unwrap();
+    assert!(report.converged);
+    assert_eq!(report.stopped_by, Some(StopCriterion::VolumeChange));
+    assert!(report.iterations_run < 5000);
+
+    // the last iteration moved the volume by less than the tolerance, the
+    // one before it did not
+    let n = report.iterations_run;
+    let run = |iters| {
+        sirt_reconstruct_report(
+            &projections,
+            &system_matrix,
+            iters,
+            1.0,
+            &ReconOptions::default(),
+        )
+        .unwrap()
+    };
+    let (before_last, before_that) = (run(n - 1).volume, run(n - 2).volume);
+    assert!(norm(&(&report.volume - &before_last)) / norm(&before_last) < 1e-5);
+    assert!(norm(&(&before_last - &before_that)) / norm(&before_that) >= 1e-5);
+}
+
+#[test]
+fn the_first_criterion_to_fire_is_reported() {
+    let (system_matrix, projections) = problem();
+    let with = |tolerance, volume_tolerance| ReconOptions {
+        tolerance,
+        volume_tolerance,
+        ..ReconOptions::default()
+    };
+    let run = |options: &ReconOptions| {
+        mart_reconstruct_report(&projections, &system_matrix, 5000, 0.5, options).unwrap()
+    };
+
+    let residual = run(&with(Some(1e-4), None));
+    let volume = run(&with(None, Some(1e-10)));
+    assert_eq!(residual.stopped_by, Some(StopCriterion::Residual));
+    assert_eq!(volume.stopped_by, Some(StopCriterion::VolumeChange));
+    assert!(residual.iterations_run < volume.iterations_run);
+
+    let both = run(&with(Some(1e-4), Some(1e-10)));
+    assert_eq!(both.stopped_by, Some(StopCriterion::Residual));
+    assert_eq!(both.iterations_run, residual.iterations_run);
+    let both = run(&with(Some(1e-14), Some(1e-3)));
+    assert_eq!(both.stopped_by, Some(StopCriterion::VolumeChange));
+
+    let neither = run(&with(None, None));
+    assert!(!neither.converged && neither.stopped_by.is_none());
+    assert_eq!(neither.iterations_run, 5000);
+}
@@ -0,0 +1,74 @@
+use ndarray::{array, s, Array1, Array2};
+
+use recon_core::{mart_reconstruct, MartState, ReconError, ReconOptions};
+
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 0.0],
+    ];
+    let phantom = array![0.2, 0.7, 1.3, 0.4];
+    (system_matrix.dot(&phantom), system_matrix)
+}
+
+#[test]
+fn batches_reproduce_one_full_iteration() {
+    let (projections, system_matrix) = problem();
+    let options = ReconOptions::default();
+
+    let mut state = MartState::new(4, 0.5, &options).unwrap();
+    for (start, end) in [(0, 2), (2, 3), (3, 6)] {
+        state
+            .update(
+                &projections.slice(s![start..end]).to_owned(),
+                &system_matrix.slice(s![start..end, ..]),
+            )
+            .unwrap();
+    }
+    assert_eq!(state.rays_seen(), 6);
+    assert_eq!(state.updates(), 3);
+    assert_eq!(
+        state.column_sums(),
+        &system_matrix.sum_axis(ndarray::Axis(0))
+    );
+
+    let full = mart_reconstruct(&projections, &system_matrix, 1, 0.5, &options).unwrap();
+    assert_eq!(state.volume(), &full);
+}
+
+#[test]
+fn repeated_updates_fit_the_batch() {
+    let (projections, system_matrix) = problem();
+
+    let mut state = MartState::new(4, 1.0, &ReconOptions::default()).unwrap();
+    let first = state.update(&projections, &system_matrix).unwrap();
+    let mut last = first;
+    for _ in 0..50 {
+        last = state.update(&projections, &system_matrix).unwrap();
+    }
+    assert!(last < first * 1e-3, "{first} -> {last}");
+    assert_eq!(state.rays_seen(), 51 * 6);
+}
+
+#[test]
+fn mismatched_batches_are_rejected() {
+    let (projections, system_matrix) = problem();
+    let mut state = MartState::new(3, 0.5, &ReconOptions::default()).unwrap();
+
+    let result = state.update(&projections, &system_matrix);
+    assert!(
+        matches!(result, Err(ReconError::DimensionMismatch { .. })),
+        "{result:?}"
+    );
+    let result = state.update(&array![1.0, -1.0], &Array2::ones((2, 3)));
+    assert!(
+        matches!(result, Err(ReconError::NegativeProjection { index: 1, .. })),
+        "{result:?}"
+    );
+    assert_eq!(state.updates(), 0);
+    assert_eq!(state.volume(), &Array1::<f64>::ones(3));
+}
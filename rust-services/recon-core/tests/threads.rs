@@ -0,0 +1,94 @@
+#![cfg(feature = "rayon")]
+
+use ndarray::{Array1, Array2, Axis};
+
+use recon_core::{mart_reconstruct, ReconOptions};
+
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    let system_matrix = Array2::from_shape_fn((60, 200), |(i, j)| {
+        let v = ((i * 7919 + j * 104_729) % 1013) as f64 / 1013.0;
+        if v > 0.5 {
+            v
+        } else {
+            0.0
+        }
+    });
+    let truth = Array1::from_shape_fn(200, |j| 1.0 + (j % 13) as f64 / 13.0);
+    (system_matrix.dot(&truth), system_matrix)
+}
+
+/// Plain MART with the serial kernels: `row.dot` for y_hat, then scale the
+/// touched voxels.
+fn serial_mart(
+    projections: &Array1<f64>,
+    system_matrix: &Array2<f64>,
+    n_iters: usize,
+    relaxation: f64,
+) -> Array1<f64> {
+    let mut volume = Array1::from_elem(system_matrix.dim().1, 1.0);
+    for _ in 0..n_iters {
+        for (i, row) in system_matrix.axis_iter(Axis(0)).enumerate() {
+            let y_hat = row.dot(&volume);
+            if y_hat <= 0.0 {
+                continue;
+            }
+            let factor = (projections[i] / y_hat).powf(relaxation);
+            volume.zip_mut_with(&row, |x_j, &a_ij| {
+                if a_ij > 0.0 {
+                    *x_j *= factor;
+                }
+            });
+        }
+    }
+    volume
+}
+
+fn with_threads(threads: usize) -> ReconOptions {
+    ReconOptions {
+        threads: Some(threads),
+        ..ReconOptions::default()
+    }
+}
+
+#[test]
+fn one_thread_matches_serial_kernels_exactly() {
+    let (projections, system_matrix) = problem();
+
+    let volume = mart_reconstruct(&projections, &system_matrix, 10, 0.5, &with_threads(1)).unwrap();
+    assert_eq!(volume, serial_mart(&projections, &system_matrix, 10, 0.5));
+}
+
+#[test]
+fn thread_count_only_changes_rounding() {
+    let (projections, system_matrix) = problem();
+
+    let serial = mart_reconstruct(&projections, &system_matrix, 10, 0.5, &with_threads(1)).unwrap();
+    let parallel =
+        mart_reconstruct(&projections, &system_matrix, 10, 0.5, &with_threads(4)).unwrap();
+    assert!((&parallel - &serial)
+        .iter()
+        .zip(&serial)
+        .all(|(d, s)| d.abs() <= 1e-12 * s.abs()));
+}
+
+#[test]
+fn deterministic_reduction_is_identical_for_any_thread_count() {
+    // rows long enough for several levels of the pairwise tree
+    let system_matrix = Array2::from_shape_fn((40, 3000), |(i, j)| {
+        ((i * 31 + j * 17) % 23) as f64 / 23.0 + 0.01
+    });
+    let truth = Array1::from_shape_fn(3000, |j| 1.0 + (j % 7) as f64 / 7.0);
+    let projections = system_matrix.dot(&truth);
+
+    let run = |threads| {
+        let options = ReconOptions {
+            deterministic: true,
+            ..with_threads(threads)
+        };
+        let volume = mart_reconstruct(&projections, &system_matrix, 5, 0.5, &options).unwrap();
+        volume.mapv(f64::to_bits)
+    };
+    let one = run(1);
+    assert_eq!(run(2), one);
+    assert_eq!(run(4), one);
+}
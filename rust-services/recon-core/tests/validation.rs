@@ -0,0 +1,122 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    art_reconstruct, cgls_reconstruct, landweber_reconstruct, mart_reconstruct_sparse,
+    mart_reconstruct_sparse_blocked, os_mart_reconstruct, sirt_reconstruct, InitialGuess,
+    ReconError, ReconOptions, SparseSystemMatrix,
+};
+
+fn system_matrix() -> Array2<f64> {
+    array![[1.0, 1.0, 0.0], [0.0, 1.0, 1.0]]
+}
+
+fn mismatch(what: &'static str, expected: usize, actual: usize) -> ReconError {
+    ReconError::DimensionMismatch {
+        what,
+        expected,
+        actual,
+    }
+}
+
+#[test]
+fn sparse_mart_rejects_mismatched_inputs() {
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix());
+    let defaults = ReconOptions::default();
+
+    let result = mart_reconstruct_sparse(&array![1.0, 2.0, 3.0], &sparse, 5, 1.0, &defaults);
+    assert_eq!(result, Err(mismatch("projections", 2, 3)));
+
+    let options = ReconOptions {
+        initial_guess: InitialGuess::FromArray(Array1::ones(2)),
+        ..ReconOptions::default()
+    };
+    let result = mart_reconstruct_sparse(&array![1.0, 2.0], &sparse, 5, 1.0, &options);
+    assert_eq!(result, Err(mismatch("initial volume", 3, 2)));
+
+    let options = ReconOptions {
+        ray_weights: Some(array![1.0]),
+        ..ReconOptions::default()
+    };
+    let result = mart_reconstruct_sparse(&array![1.0, 2.0], &sparse, 5, 1.0, &options);
+    assert_eq!(result, Err(mismatch("ray weights", 2, 1)));
+}
+
+#[test]
+fn blocked_sparse_mart_rejects_mismatched_inputs_and_zero_blocks() {
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix());
+    let defaults = ReconOptions::default();
+    let projections = array![1.0, 2.0];
+
+    let result = mart_reconstruct_sparse_blocked(&array![1.0], &sparse, 5, 1.0, 2, &defaults);
+    assert_eq!(result, Err(mismatch("projections", 2, 1)));
+
+    let options = ReconOptions {
+        mask: Some(array![true, false]),
+        ..ReconOptions::default()
+    };
+    let result = mart_reconstruct_sparse_blocked(&projections, &sparse, 5, 1.0, 2, &options);
+    assert_eq!(result, Err(mismatch("mask", 3, 2)));
+
+    let result = mart_reconstruct_sparse_blocked(&projections, &sparse, 5, 1.0, 0, &defaults);
+    assert_eq!(
+        result,
+        Err(ReconError::InvalidBlocks {
+            blocks: 0,
+            voxels: 3
+        })
+    );
+}
+
+#[test]
+fn art_rejects_mismatched_inputs() {
+    let system_matrix = system_matrix();
+    let defaults = ReconOptions::default();
+
+    let result = art_reconstruct(&array![1.0], &system_matrix, 5, 1.0, &defaults);
+    assert_eq!(result, Err(mismatch("projections", 2, 1)));
+
+    let result = art_reconstruct(&array![1.0, f64::NAN], &system_matrix, 5, 1.0, &defaults);
+    assert_eq!(
+        result,
+        Err(ReconError::NonFinite {
+            what: "projections",
+            index: 1
+        })
+    );
+
+    let options = ReconOptions {
+        mask: Some(array![true, true, true, true]),
+        ..ReconOptions::default()
+    };
+    let result = art_reconstruct(&array![1.0, 2.0], &system_matrix, 5, 1.0, &options);
+    assert_eq!(result, Err(mismatch("mask", 3, 4)));
+
+    // additive ART, unlike MART, takes negative projections
+    assert!(art_reconstruct(&array![-1.0, 2.0], &system_matrix, 5, 1.0, &defaults).is_ok());
+}
+
+#[test]
+fn every_solver_rejects_short_projections() {
+    let system_matrix = system_matrix();
+    let options = ReconOptions::default();
+    let short = array![1.0];
+    let expected = Err(mismatch("projections", 2, 1));
+
+    assert_eq!(
+        art_reconstruct(&short, &system_matrix, 5, 1.0, &options),
+        expected
+    );
+    assert_eq!(
+        sirt_reconstruct(&short, &system_matrix, 5, 1.0, &options),
+        expected
+    );
+    assert_eq!(
+        landweber_reconstruct(&short, &system_matrix, 5, 0.1, &options),
+        expected
+    );
+    assert_eq!(
+        os_mart_reconstruct(&short, &system_matrix, 5, 2, 1.0, &options),
+        expected
+    );
+    assert_eq!(cgls_reconstruct(&short, &system_matrix, 5), expected);
+}
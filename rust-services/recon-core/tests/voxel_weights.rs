@@ -0,0 +1,107 @@
+use ndarray::{array, Array1, Array2};
+
+use recon_core::{
+    mart_reconstruct, mart_reconstruct_sparse, smart_reconstruct, ReconError, ReconOptions,
+    SparseSystemMatrix,
+};
+
+/// One ray over two voxels asking for a factor of 2 from the uniform start.
+fn problem() -> (Array1<f64>, Array2<f64>) {
+    (array![4.0], array![[1.0, 1.0]])
+}
+
+fn weighted(weights: Array1<f64>) -> ReconOptions {
+    ReconOptions {
+        voxel_weights: Some(weights),
+        ..ReconOptions::default()
+    }
+}
+
+fn max_abs_diff(a: &Array1<f64>, b: &Array1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn weight_scales_voxel_exponent() {
+    let (projections, system_matrix) = problem();
+    let sparse = SparseSystemMatrix::from_dense(&system_matrix);
+    let options = weighted(array![1.0, 0.5]);
+
+    let volume = mart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    let expected = array![2.0, 2f64.sqrt()];
+    assert!(max_abs_diff(&volume, &expected) < 1e-12, "{volume}");
+    assert_eq!(
+        mart_reconstruct_sparse(&projections, &sparse, 1, 1.0, &options).unwrap(),
+        volume
+    );
+
+    let smart = smart_reconstruct(&projections, &system_matrix, 1, 1.0, &options).unwrap();
+    assert!(max_abs_diff(&smart, &expected) < 1e-12, "{smart}");
+}
+
+#[test]
+fn zero_weight_freezes_the_voxel() {
+    // a zero measurement drives touched voxels to zero, except frozen ones
+    let options = weighted(array![0.0, 1.0]);
+    let volume = mart_reconstruct(&array![0.0], &array![[1.0, 1.0]], 5, 1.0, &options).unwrap();
+    assert_eq!(volume, array![1.0, 0.0]);
+}
+
+#[test]
+fn uniform_weight_acts_like_relaxation() {
+    let system_matrix = array![
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0]
+    ];
+    let projections = system_matrix.dot(&array![0.3, 1.2, 0.7]);
+
+    for column_weighted in [false, true] {
+        let scaled = ReconOptions {
+            column_weighted,
+            ..weighted(Array1::from_elem(3, 0.4))
+        };
+        let unscaled = ReconOptions {
+            column_weighted,
+            ..ReconOptions::default()
+        };
+        let with_weights =
+            mart_reconstruct(&projections, &system_matrix, 20, 1.0, &scaled).unwrap();
+        let with_relaxation =
+            mart_reconstruct(&projections, &system_matrix, 20, 0.4, &unscaled).unwrap();
+        assert!(
+            max_abs_diff(&with_weights, &with_relaxation) < 1e-12,
+            "column_weighted = {column_weighted}"
+        );
+    }
+}
+
+#[test]
+fn invalid_weights_are_rejected() {
+    let (projections, system_matrix) = problem();
+
+    let negative = mart_reconstruct(
+        &projections,
+        &system_matrix,
+        1,
+        1.0,
+        &weighted(array![1.0, -0.5]),
+    );
+    assert_eq!(
+        negative,
+        Err(ReconError::NegativeVoxelWeight {
+            index: 1,
+            value: -0.5
+        })
+    );
+
+    let short = smart_reconstruct(&projections, &system_matrix, 1, 1.0, &weighted(array![1.0]));
+    assert!(
+        matches!(short, Err(ReconError::DimensionMismatch { .. })),
+        "{short:?}"
+    );
+}